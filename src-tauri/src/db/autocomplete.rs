@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tauri::State;
+use sqlx::Row;
+use tiberius::{Client, QueryItem};
+use tokio::net::TcpStream;
+use tokio_util::compat::Compat;
+use futures_util::TryStreamExt;
+use crate::db::connections::{create_mssql_client, load_connections, resolve_schema, ConnectionConfig};
+use crate::db::pool_manager::{PoolManager, DatabasePool};
+use crate::db::quote::quote_string;
+
+/// Table name to column names, in column order
+pub type AutocompleteSchema = BTreeMap<String, Vec<String>>;
+
+/// Cached autocomplete metadata, keyed by `{connection_id}:{database}`
+pub struct SchemaCache {
+    entries: Arc<RwLock<HashMap<String, AutocompleteSchema>>>,
+}
+
+impl SchemaCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn key(connection_id: &str, database: Option<&str>) -> String {
+        format!("{}:{}", connection_id, database.unwrap_or(""))
+    }
+
+    pub async fn get(&self, connection_id: &str, database: Option<&str>) -> Option<AutocompleteSchema> {
+        let entries = self.entries.read().await;
+        entries.get(&Self::key(connection_id, database)).cloned()
+    }
+
+    pub async fn insert(&self, connection_id: &str, database: Option<&str>, schema: AutocompleteSchema) {
+        let mut entries = self.entries.write().await;
+        entries.insert(Self::key(connection_id, database), schema);
+    }
+
+    /// Drop all cached entries of a connection
+    pub async fn invalidate(&self, connection_id: &str) {
+        let prefix = format!("{}:", connection_id);
+        let mut entries = self.entries.write().await;
+        entries.retain(|key, _| !key.starts_with(&prefix));
+    }
+}
+
+impl Default for SchemaCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Group (table, column) pairs into the autocomplete map
+fn group_columns(pairs: impl IntoIterator<Item = (String, String)>) -> AutocompleteSchema {
+    let mut schema = AutocompleteSchema::new();
+    for (table, column) in pairs {
+        schema.entry(table).or_default().push(column);
+    }
+    schema
+}
+
+/// Tables and their column names for editor autocomplete, fetched in a single
+/// query per engine. Results are cached until `refresh` is set or the
+/// connection is disconnected.
+#[tauri::command]
+pub async fn get_autocomplete_schema(
+    connection_id: String,
+    database: Option<String>,
+    refresh: Option<bool>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    schema_cache: State<'_, SchemaCache>,
+) -> Result<AutocompleteSchema, String> {
+    if !refresh.unwrap_or(false) {
+        if let Some(schema) = schema_cache.get(&connection_id, database.as_deref()).await {
+            return Ok(schema);
+        }
+    }
+
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let schema = if connection.db_type == "mssql" {
+        match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                let db_name = database.as_deref().or(config_db.as_deref());
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
+
+                let query = format!(
+                    "SELECT TABLE_NAME, COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS WHERE TABLE_SCHEMA = {} ORDER BY TABLE_NAME, ORDINAL_POSITION",
+                    quote_string("mssql", &resolve_schema(&connection.config, None))
+                );
+                let mut stream: tiberius::QueryStream<'_> = client.query(&query, &[])
+                    .await
+                    .map_err(|e| format!("查询表结构失败: {}", e))?;
+
+                let mut pairs = Vec::new();
+                while let Some(item) = stream.try_next().await
+                    .map_err(|e| format!("读取结果失败: {}", e))? {
+                    if let QueryItem::Row(row) = item {
+                        let table = row.try_get::<&str, _>(0).ok().flatten().unwrap_or("");
+                        let column = row.try_get::<&str, _>(1).ok().flatten().unwrap_or("");
+                        pairs.push((table.to_string(), column.to_string()));
+                    }
+                }
+                group_columns(pairs)
+            }
+            _ => return Err("无效的 MSSQL 配置".to_string()),
+        }
+    } else {
+        let pool = pool_manager.get_or_create_pool(connection, database.as_deref()).await?;
+
+        let rows = match pool {
+            DatabasePool::Sqlite(p) => {
+                // pragma_table_info as a table-valued function covers every table in one query
+                sqlx::query(
+                    "SELECT m.name, p.name FROM sqlite_master m
+                     JOIN pragma_table_info(m.name) p
+                     WHERE m.type IN ('table', 'view') AND m.name NOT LIKE 'sqlite_%'
+                     ORDER BY m.name, p.cid"
+                )
+                    .fetch_all(&p)
+                    .await
+                    .map(|rows| rows.into_iter().map(|r| (r.get::<String, _>(0), r.get::<String, _>(1))).collect::<Vec<_>>())
+            }
+            DatabasePool::Mysql(p) => {
+                sqlx::query(
+                    "SELECT TABLE_NAME, COLUMN_NAME FROM information_schema.columns
+                     WHERE TABLE_SCHEMA = COALESCE(?, DATABASE())
+                     ORDER BY TABLE_NAME, ORDINAL_POSITION"
+                )
+                    .bind(database.as_deref())
+                    .fetch_all(&p)
+                    .await
+                    .map(|rows| rows.into_iter().map(|r| (r.get::<String, _>(0), r.get::<String, _>(1))).collect::<Vec<_>>())
+            }
+            DatabasePool::Postgres(p) => {
+                sqlx::query(
+                    "SELECT table_name, column_name FROM information_schema.columns
+                     WHERE table_schema = $1
+                     ORDER BY table_name, ordinal_position"
+                )
+                    .bind(resolve_schema(&connection.config, None))
+                    .fetch_all(&p)
+                    .await
+                    .map(|rows| rows.into_iter().map(|r| (r.get::<String, _>(0), r.get::<String, _>(1))).collect::<Vec<_>>())
+            }
+        }
+        .map_err(|e| format!("查询表结构失败: {}", e))?;
+
+        group_columns(rows)
+    };
+
+    schema_cache.insert(&connection_id, database.as_deref(), schema.clone()).await;
+    Ok(schema)
+}
@@ -0,0 +1,82 @@
+use std::time::Instant;
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::connections::load_connections;
+use crate::db::execute::execute_on_held;
+use crate::db::pool_manager::PoolManager;
+use crate::db::session::ActiveDatabases;
+use crate::db::sqlkind::{classify_statement, StatementKind};
+use crate::db::transaction::HeldConnection;
+
+const DEFAULT_ITERATIONS: usize = 10;
+const MAX_ITERATIONS: usize = 1000;
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkResult {
+    /// Timed runs, not counting the warmup
+    pub iterations: usize,
+    /// Rows returned by the warmup run
+    pub row_count: usize,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    /// Elapsed time of each timed run, in order
+    pub samples_ms: Vec<f64>,
+}
+
+/// Value below which `fraction` of the sorted samples fall (nearest rank)
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Run a SELECT `iterations` times (default 10) and report timing stats.
+/// All runs go one after another on a single connection so they do not
+/// compete with each other; a first warmup run fills the caches and is not
+/// counted. Each run fetches every row, and only the warmup's rows are
+/// counted. Statements that could change data are refused.
+#[tauri::command]
+pub async fn benchmark_query(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    iterations: Option<usize>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<BenchmarkResult, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if classify_statement(&sql) != StatementKind::Query {
+        return Err("Only SELECT statements can be benchmarked".to_string());
+    }
+    let iterations = iterations.unwrap_or(DEFAULT_ITERATIONS).clamp(1, MAX_ITERATIONS);
+
+    let database = app.state::<ActiveDatabases>().resolve(connection, database).await;
+    let mut conn = HeldConnection::open(connection, database.as_deref(), &pool_manager).await?;
+
+    let row_count = execute_on_held(&mut conn, &sql, usize::MAX).await?.rows.len();
+    let mut samples_ms = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let started = Instant::now();
+        execute_on_held(&mut conn, &sql, usize::MAX).await?;
+        samples_ms.push(started.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    let mut sorted = samples_ms.clone();
+    sorted.sort_by(f64::total_cmp);
+    Ok(BenchmarkResult {
+        iterations,
+        row_count,
+        min_ms: sorted[0],
+        max_ms: sorted[sorted.len() - 1],
+        mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        median_ms: percentile(&sorted, 0.5),
+        p95_ms: percentile(&sorted, 0.95),
+        samples_ms,
+    })
+}
@@ -0,0 +1,506 @@
+use crate::db::connections::{load_connections, create_mssql_client, Connection, ConnectionConfig};
+use crate::db::quote::quote_ident;
+use crate::db::ddl::ensure_writable;
+use crate::db::pool_manager::{PoolManager, DatabasePool};
+use crate::db::query_cache::QueryCache;
+use crate::db::session::ActiveDatabases;
+use serde::Serialize;
+use tauri::{Manager, State};
+use tiberius::Client;
+use tokio::net::TcpStream;
+use tokio_util::compat::Compat;
+
+// Maximum bound parameters per statement for each engine
+const SQLITE_MAX_PARAMS: usize = 999;
+const MYSQL_MAX_PARAMS: usize = 65535;
+const MSSQL_MAX_PARAMS: usize = 2000;
+// SQL Server accepts at most 1000 row constructors in one VALUES clause
+const MSSQL_MAX_ROWS_PER_INSERT: usize = 1000;
+
+/// Bind a JSON value to a sqlx query as the closest native type
+macro_rules! bind_json_value {
+    ($query:expr, $value:expr) => {
+        match $value {
+            serde_json::Value::Null => $query.bind(None::<String>),
+            serde_json::Value::Bool(b) => $query.bind(*b),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    $query.bind(i)
+                } else if n.is_f64() {
+                    $query.bind(n.as_f64())
+                } else {
+                    // u64 beyond i64 range: let the server convert from text
+                    $query.bind(n.to_string())
+                }
+            }
+            serde_json::Value::String(s) => $query.bind(s.clone()),
+            other => $query.bind(other.to_string()),
+        }
+    };
+}
+
+/// Run a statement once per parameter set in a transaction on a sqlx pool.
+/// sqlx keeps the prepared statement on the connection, so it is only
+/// parsed once. With `continue_on_error` each set runs in a savepoint, since
+/// a failed statement would otherwise abort the whole transaction on Postgres.
+macro_rules! execute_param_sets {
+    ($pool:expr, $sql:expr, $param_sets:expr, $continue_on_error:expr) => {{
+        let mut tx = $pool.begin().await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+        let mut result = ExecuteManyResult { rows_affected: 0, failed: Vec::new() };
+        for (index, params) in $param_sets.iter().enumerate() {
+            let mut query = sqlx::query($sql);
+            for value in params {
+                query = bind_json_value!(query, value);
+            }
+            if $continue_on_error {
+                let mut savepoint = sqlx::Connection::begin(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to create savepoint: {}", e))?;
+                match query.execute(&mut *savepoint).await {
+                    Ok(done) => {
+                        savepoint.commit().await.map_err(|e| format!("Failed to release savepoint: {}", e))?;
+                        result.rows_affected += done.rows_affected();
+                    }
+                    Err(e) => {
+                        savepoint.rollback().await.map_err(|e| format!("Failed to roll back savepoint: {}", e))?;
+                        result.failed.push(ParamSetError { index, message: e.to_string() });
+                    }
+                }
+            } else {
+                // Dropping the transaction rolls it back
+                let done = query
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Parameter set {} failed: {}", index + 1, e))?;
+                result.rows_affected += done.rows_affected();
+            }
+        }
+        tx.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        Ok(result)
+    }};
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParamSetError {
+    /// Zero-based position of the parameter set
+    pub index: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExecuteManyResult {
+    pub rows_affected: u64,
+    /// Sets that failed and were skipped, with `continue_on_error`
+    pub failed: Vec<ParamSetError>,
+}
+
+/// Build `INSERT INTO t (cols) VALUES (...), (...)` with one placeholder per value
+fn build_insert_sql(
+    db_type: &str,
+    table_name: &str,
+    columns: &[String],
+    row_count: usize,
+) -> String {
+    let column_list = columns
+        .iter()
+        .map(|c| quote_ident(db_type, c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut param_index = 0;
+    let values = (0..row_count)
+        .map(|_| {
+            let placeholders = (0..columns.len())
+                .map(|_| {
+                    param_index += 1;
+                    if db_type == "mssql" {
+                        format!("@P{}", param_index)
+                    } else {
+                        "?".to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({})", placeholders)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        quote_ident(db_type, table_name),
+        column_list,
+        values
+    )
+}
+
+/// Number of rows that fit into one INSERT without exceeding the parameter limit
+fn rows_per_chunk(max_params: usize, column_count: usize) -> usize {
+    (max_params / column_count.max(1)).max(1)
+}
+
+/// Render a JSON value as a PostgreSQL COPY CSV field (unquoted empty field is NULL)
+fn copy_csv_field(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => return String::new(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Insert many rows in a single transaction.
+///
+/// Rows are sent as multi-row INSERT statements with bound parameters, chunked to
+/// stay under each engine's parameter limit. PostgreSQL uses `COPY FROM STDIN`.
+/// Returns the total number of rows inserted.
+#[tauri::command]
+pub async fn bulk_insert(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    columns: Vec<String>,
+    rows: Vec<Vec<serde_json::Value>>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    if columns.is_empty() {
+        return Err("No columns specified".to_string());
+    }
+    if let Some(index) = rows.iter().position(|r| r.len() != columns.len()) {
+        return Err(format!(
+            "Row {} has {} values but {} columns were specified",
+            index + 1,
+            rows[index].len(),
+            columns.len()
+        ));
+    }
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let inserted = insert_rows(connection, database.as_deref(), &table_name, &columns, &rows, &pool_manager).await?;
+    app.state::<QueryCache>().invalidate(&connection.id).await;
+    Ok(inserted)
+}
+
+/// Insert rows into a table in a single transaction (shared by bulk_insert and imports)
+pub(crate) async fn insert_rows(
+    connection: &Connection,
+    database: Option<&str>,
+    table_name: &str,
+    columns: &[String],
+    rows: &[Vec<serde_json::Value>],
+    pool_manager: &PoolManager,
+) -> Result<u64, String> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    // Handle MSSQL separately since it uses tiberius instead of sqlx
+    if connection.db_type == "mssql" {
+        return match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                let db_name = database.or(config_db.as_deref());
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
+                bulk_insert_mssql(&mut client, table_name, columns, rows).await
+            }
+            _ => Err("无效的 MSSQL 配置".to_string()),
+        };
+    }
+
+    let pool = pool_manager.get_or_create_pool(connection, database).await?;
+
+    match pool {
+        DatabasePool::Sqlite(p) => {
+            let _write_guard = pool_manager.sqlite_write_guard(connection).await;
+            let mut tx = p.begin().await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+            let mut inserted = 0;
+            for chunk in rows.chunks(rows_per_chunk(SQLITE_MAX_PARAMS, columns.len())) {
+                let sql = build_insert_sql("sqlite", table_name, columns, chunk.len());
+                let mut query = sqlx::query(&sql);
+                for value in chunk.iter().flatten() {
+                    query = bind_json_value!(query, value);
+                }
+                inserted += query
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Bulk insert failed: {}", e))?
+                    .rows_affected();
+            }
+            tx.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(inserted)
+        }
+        DatabasePool::Mysql(p) => {
+            let mut tx = p.begin().await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+            let mut inserted = 0;
+            for chunk in rows.chunks(rows_per_chunk(MYSQL_MAX_PARAMS, columns.len())) {
+                let sql = build_insert_sql("mysql", table_name, columns, chunk.len());
+                let mut query = sqlx::query(&sql);
+                for value in chunk.iter().flatten() {
+                    query = bind_json_value!(query, value);
+                }
+                inserted += query
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Bulk insert failed: {}", e))?
+                    .rows_affected();
+            }
+            tx.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(inserted)
+        }
+        DatabasePool::Postgres(p) => {
+            // COPY parses the text per column type, avoiding typed-parameter mismatches
+            let column_list = columns
+                .iter()
+                .map(|c| quote_ident("postgres", c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let statement = format!(
+                "COPY {} ({}) FROM STDIN WITH (FORMAT csv)",
+                quote_ident("postgres", table_name),
+                column_list
+            );
+
+            let mut tx = p.begin().await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+            let mut copy = tx
+                .copy_in_raw(&statement)
+                .await
+                .map_err(|e| format!("Bulk insert failed: {}", e))?;
+            for chunk in rows.chunks(1000) {
+                let mut data = String::new();
+                for row in chunk {
+                    let fields: Vec<String> = row.iter().map(copy_csv_field).collect();
+                    data.push_str(&fields.join(","));
+                    data.push('\n');
+                }
+                copy.send(data.into_bytes())
+                    .await
+                    .map_err(|e| format!("Bulk insert failed: {}", e))?;
+            }
+            let inserted = copy.finish().await.map_err(|e| format!("Bulk insert failed: {}", e))?;
+            tx.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(inserted)
+        }
+    }
+}
+
+/// Bind a JSON value to a tiberius query, like `bind_json_value!` does for sqlx
+fn bind_mssql_value(query: &mut tiberius::Query<'_>, value: &serde_json::Value) {
+    match value {
+        serde_json::Value::Null => query.bind(None::<String>),
+        serde_json::Value::Bool(b) => query.bind(*b),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                query.bind(i)
+            } else if n.is_f64() {
+                query.bind(n.as_f64())
+            } else {
+                query.bind(n.to_string())
+            }
+        }
+        serde_json::Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+async fn bulk_insert_mssql(
+    client: &mut Client<Compat<TcpStream>>,
+    table_name: &str,
+    columns: &[String],
+    rows: &[Vec<serde_json::Value>],
+) -> Result<u64, String> {
+    client
+        .simple_query("BEGIN TRANSACTION")
+        .await
+        .map_err(|e| format!("开启事务失败: {}", e))?
+        .into_results()
+        .await
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let chunk_size = rows_per_chunk(MSSQL_MAX_PARAMS, columns.len()).min(MSSQL_MAX_ROWS_PER_INSERT);
+    let mut inserted = 0;
+    for chunk in rows.chunks(chunk_size) {
+        let sql = build_insert_sql("mssql", table_name, columns, chunk.len());
+        let mut query = tiberius::Query::new(sql);
+        for value in chunk.iter().flatten() {
+            bind_mssql_value(&mut query, value);
+        }
+
+        match query.execute(client).await {
+            Ok(result) => inserted += result.total(),
+            Err(e) => {
+                // Best effort: the connection is dropped afterwards anyway
+                if let Ok(stream) = client.simple_query("ROLLBACK TRANSACTION").await {
+                    let _ = stream.into_results().await;
+                }
+                return Err(format!("批量插入失败: {}", e));
+            }
+        }
+    }
+
+    client
+        .simple_query("COMMIT TRANSACTION")
+        .await
+        .map_err(|e| format!("提交事务失败: {}", e))?
+        .into_results()
+        .await
+        .map_err(|e| format!("提交事务失败: {}", e))?;
+
+    Ok(inserted)
+}
+
+/// Run a parameterized statement once for each parameter set, in one
+/// transaction. Placeholders use the engine's own syntax (`?`, `$1`, `@P1`).
+/// The first failure rolls everything back, unless `continue_on_error` is
+/// set: then failed sets are skipped and reported, and the rest is committed.
+#[tauri::command]
+pub async fn execute_many(
+    connection_id: String,
+    sql: String,
+    param_sets: Vec<Vec<serde_json::Value>>,
+    database: Option<String>,
+    continue_on_error: Option<bool>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<ExecuteManyResult, String> {
+    if sql.trim().is_empty() {
+        return Err("SQL is required".to_string());
+    }
+    if let Some(first) = param_sets.first() {
+        if let Some(index) = param_sets.iter().position(|p| p.len() != first.len()) {
+            return Err(format!(
+                "Parameter set {} has {} values but the first one has {}",
+                index + 1,
+                param_sets[index].len(),
+                first.len()
+            ));
+        }
+    }
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_writable(connection)?;
+    if param_sets.is_empty() {
+        return Ok(ExecuteManyResult { rows_affected: 0, failed: Vec::new() });
+    }
+    let database = app.state::<ActiveDatabases>().resolve(connection, database).await;
+    let continue_on_error = continue_on_error.unwrap_or(false);
+
+    let result = if connection.db_type == "mssql" {
+        match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                let db_name = database.as_deref().or(config_db.as_deref());
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
+                execute_many_mssql(&mut client, &sql, &param_sets, continue_on_error).await
+            }
+            _ => Err("无效的 MSSQL 配置".to_string()),
+        }
+    } else {
+        match pool_manager.get_or_create_pool(connection, database.as_deref()).await? {
+            DatabasePool::Sqlite(p) => execute_param_sets!(p, &sql, param_sets, continue_on_error),
+            DatabasePool::Mysql(p) => execute_param_sets!(p, &sql, param_sets, continue_on_error),
+            DatabasePool::Postgres(p) => execute_param_sets!(p, &sql, param_sets, continue_on_error),
+        }
+    }?;
+    app.state::<QueryCache>().invalidate(&connection.id).await;
+    Ok(result)
+}
+
+/// Run a statement without results on a tiberius client
+async fn mssql_simple(client: &mut Client<Compat<TcpStream>>, sql: &str) -> Result<(), tiberius::error::Error> {
+    client.simple_query(sql).await?.into_results().await?;
+    Ok(())
+}
+
+async fn execute_many_mssql(
+    client: &mut Client<Compat<TcpStream>>,
+    sql: &str,
+    param_sets: &[Vec<serde_json::Value>],
+    continue_on_error: bool,
+) -> Result<ExecuteManyResult, String> {
+    mssql_simple(client, "BEGIN TRANSACTION")
+        .await
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let mut result = ExecuteManyResult { rows_affected: 0, failed: Vec::new() };
+    for (index, params) in param_sets.iter().enumerate() {
+        if continue_on_error {
+            mssql_simple(client, "SAVE TRANSACTION param_set")
+                .await
+                .map_err(|e| format!("创建保存点失败: {}", e))?;
+        }
+        let mut query = tiberius::Query::new(sql);
+        for value in params {
+            bind_mssql_value(&mut query, value);
+        }
+        match query.execute(&mut *client).await {
+            Ok(done) => result.rows_affected += done.total(),
+            Err(e) if continue_on_error => {
+                // Fails when the error doomed the whole transaction
+                if let Err(rollback) = mssql_simple(client, "ROLLBACK TRANSACTION param_set").await {
+                    let _ = mssql_simple(client, "IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION").await;
+                    return Err(format!("参数组 {} 执行失败: {}; 回滚失败: {}", index + 1, e, rollback));
+                }
+                result.failed.push(ParamSetError { index, message: e.to_string() });
+            }
+            Err(e) => {
+                let _ = mssql_simple(client, "IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION").await;
+                return Err(format!("参数组 {} 执行失败: {}", index + 1, e));
+            }
+        }
+    }
+
+    mssql_simple(client, "COMMIT TRANSACTION")
+        .await
+        .map_err(|e| format!("提交事务失败: {}", e))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connections::sqlite_test_connection;
+    use crate::db::execute::execute_statement;
+
+    #[tokio::test]
+    async fn concurrent_sqlite_writes_wait_for_each_other() {
+        let connection = sqlite_test_connection("bulk-concurrent");
+        let pool_manager = std::sync::Arc::new(PoolManager::new());
+        execute_statement(&connection, None, "CREATE TABLE t (id INTEGER, name TEXT)", &pool_manager)
+            .await
+            .unwrap();
+
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let rows: Vec<Vec<serde_json::Value>> =
+            (0..2000).map(|i| vec![serde_json::json!(i), serde_json::json!(format!("row {}", i))]).collect();
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let (connection, pool_manager, columns, rows) =
+                    (connection.clone(), pool_manager.clone(), columns.clone(), rows.clone());
+                tokio::spawn(async move {
+                    if i % 2 == 0 {
+                        insert_rows(&connection, None, "t", &columns, &rows, &pool_manager).await
+                    } else {
+                        execute_statement(&connection, None, "UPDATE t SET name = name || '!'", &pool_manager).await
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let DatabasePool::Sqlite(pool) = pool_manager.get_or_create_pool(&connection, None).await.unwrap() else {
+            unreachable!();
+        };
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM t").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 4 * 2000);
+    }
+}
@@ -0,0 +1,312 @@
+use std::ops::Range;
+use serde::{Deserialize, Serialize};
+use tiberius::Client;
+use tokio::net::TcpStream;
+use tokio_util::compat::Compat;
+
+/// The table column a result column was read from, so the grid can turn an
+/// edit of that cell into an UPDATE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSource {
+    pub schema: Option<String>,
+    pub table: String,
+    pub column: String,
+}
+
+// Anywhere in a query, these mean a result row is not a single table row
+const MULTI_SOURCE_KEYWORDS: &[&str] = &["JOIN", "UNION", "INTERSECT", "EXCEPT", "GROUP", "DISTINCT", "HAVING", "INTO"];
+// Clauses that may follow the table in `FROM table [alias]`
+const CLAUSE_KEYWORDS: &[&str] = &["WHERE", "ORDER", "LIMIT", "OFFSET", "FETCH", "FOR"];
+// Bare words in a select list that are values, not columns
+const VALUE_KEYWORDS: &[&str] = &[
+    "NULL", "TRUE", "FALSE", "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP",
+    "CURRENT_USER", "SESSION_USER", "USER", "LOCALTIME", "LOCALTIMESTAMP",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// Keyword, unquoted identifier or number
+    Word(String),
+    /// Quoted identifier, without the quotes
+    Quoted(String),
+    /// String literal
+    Literal,
+    Symbol(char),
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '@' | '#')
+}
+
+fn tokenize(db_type: &str, sql: &str) -> Vec<Token> {
+    tokenize_spans(db_type, sql).into_iter().map(|(token, _)| token).collect()
+}
+
+/// Split SQL into tokens, each with its byte range in `sql`, dropping
+/// comments. `[name]` is a quoted identifier only in SQLite and MSSQL;
+/// elsewhere it is array syntax.
+pub(crate) fn tokenize_spans(db_type: &str, sql: &str) -> Vec<(Token, Range<usize>)> {
+    let chars: Vec<char> = sql.chars().collect();
+    // Byte offset of each char, and of the end
+    let offsets: Vec<usize> = sql.char_indices().map(|(at, _)| at).chain([sql.len()]).collect();
+    let span = |start: usize, end: usize| offsets[start]..offsets[end.min(chars.len())];
+    let brackets = matches!(db_type, "sqlite" | "mssql");
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+
+        let close = match c {
+            '"' | '`' | '\'' => Some(c),
+            '[' if brackets => Some(']'),
+            _ => None,
+        };
+        if let Some(close) = close {
+            let mut text = String::new();
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == close {
+                    // A doubled closing quote stands for itself
+                    if chars.get(i + 1) == Some(&close) {
+                        text.push(close);
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                text.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+            tokens.push((if c == '\'' { Token::Literal } else { Token::Quoted(text) }, span(start, i)));
+            continue;
+        }
+
+        if is_word_char(c) {
+            while i < chars.len() && is_word_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push((Token::Word(chars[start..i].iter().collect()), span(start, i)));
+            continue;
+        }
+        i += 1;
+        tokens.push((Token::Symbol(c), span(start, i)));
+    }
+    tokens
+}
+
+pub(crate) fn is_keyword(token: &Token, keywords: &[&str]) -> bool {
+    matches!(token, Token::Word(w) if keywords.iter().any(|k| w.eq_ignore_ascii_case(k)))
+}
+
+/// An identifier that can name a column or table: quoted, or a bare word
+/// that is not a number or a value keyword
+fn identifier(token: &Token) -> Option<String> {
+    match token {
+        Token::Quoted(name) => Some(name.clone()),
+        Token::Word(word) if !word.starts_with(|c: char| c.is_ascii_digit()) && !is_keyword(token, VALUE_KEYWORDS) => {
+            Some(word.clone())
+        }
+        _ => None,
+    }
+}
+
+/// A dotted name (`a`, `a.b`, `a.b.c`) at the start of `tokens`, and the tokens after it
+pub(crate) fn dotted_name(tokens: &[Token]) -> Option<(Vec<String>, &[Token])> {
+    let mut parts = vec![identifier(tokens.first()?)?];
+    let mut rest = &tokens[1..];
+    while let [Token::Symbol('.'), next, tail @ ..] = rest {
+        parts.push(identifier(next)?);
+        rest = tail;
+    }
+    Some((parts, rest))
+}
+
+/// Whether `tokens` is empty or an alias: `[AS] name`
+fn is_alias(tokens: &[Token]) -> bool {
+    let tokens = match tokens {
+        [first, rest @ ..] if is_keyword(first, &["AS"]) => rest,
+        _ => tokens,
+    };
+    match tokens {
+        [] => true,
+        [alias] => identifier(alias).is_some() && !is_keyword(alias, CLAUSE_KEYWORDS),
+        _ => false,
+    }
+}
+
+/// Split at commas outside parentheses
+fn split_top_level(tokens: &[Token]) -> Vec<&[Token]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => depth = depth.saturating_sub(1),
+            Token::Symbol(',') if depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+enum SelectItem {
+    /// `*` or `t.*`
+    Star,
+    /// A plain column, possibly qualified and aliased
+    Column(String),
+    Expression,
+}
+
+/// Sources of the result columns of a `SELECT ... FROM table` that reads a
+/// single table. Plain columns map to the table; expressions, and every
+/// column of a query that joins, groups or combines results, map to None.
+pub(crate) fn single_table_sources(db_type: &str, sql: &str, columns: &[String]) -> Vec<Option<ColumnSource>> {
+    let mut sources = vec![None; columns.len()];
+    let tokens = tokenize(db_type, sql);
+    let tokens = match tokens.as_slice() {
+        [body @ .., Token::Symbol(';')] => body,
+        all => all,
+    };
+    let [first, tokens @ ..] = tokens else {
+        return sources;
+    };
+    if !is_keyword(first, &["SELECT"]) || tokens.iter().any(|t| is_keyword(t, MULTI_SOURCE_KEYWORDS)) {
+        return sources;
+    }
+
+    // The FROM of this SELECT, not of a subquery in the select list
+    let mut depth = 0usize;
+    let from = tokens.iter().position(|t| {
+        match t {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        depth == 0 && is_keyword(t, &["FROM"])
+    });
+    let Some(from) = from else {
+        return sources;
+    };
+
+    let Some((table_path, after_table)) = dotted_name(&tokens[from + 1..]) else {
+        return sources;
+    };
+    let table_end = after_table
+        .iter()
+        .position(|t| is_keyword(t, CLAUSE_KEYWORDS))
+        .unwrap_or(after_table.len());
+    if !is_alias(&after_table[..table_end]) {
+        return sources;
+    }
+    let table = table_path[table_path.len() - 1].clone();
+    let schema = (table_path.len() > 1).then(|| table_path[table_path.len() - 2].clone());
+    // Columns may be qualified with the alias, or with the table name if there is none
+    let qualifier = after_table[..table_end]
+        .last()
+        .and_then(identifier)
+        .unwrap_or_else(|| table.clone());
+
+    let items: Vec<SelectItem> = split_top_level(&tokens[..from])
+        .into_iter()
+        .map(|item| {
+            let qualified_by_table = |path: &[String]| match path {
+                [_] => true,
+                [.., q, _] => q == &qualifier,
+                [] => false,
+            };
+            match item {
+                [Token::Symbol('*')] => return SelectItem::Star,
+                [.., Token::Symbol('.'), Token::Symbol('*')] => {
+                    let star_of_table = dotted_name(&item[..item.len() - 2])
+                        .is_some_and(|(path, rest)| rest.is_empty() && path.last() == Some(&qualifier));
+                    return if star_of_table { SelectItem::Star } else { SelectItem::Expression };
+                }
+                _ => {}
+            }
+            match dotted_name(item) {
+                Some((path, rest)) if path.len() <= 2 && qualified_by_table(&path) && is_alias(rest) => {
+                    SelectItem::Column(path[path.len() - 1].clone())
+                }
+                _ => SelectItem::Expression,
+            }
+        })
+        .collect();
+
+    let source = |column: &str| ColumnSource { schema: schema.clone(), table: table.clone(), column: column.to_string() };
+    match items.as_slice() {
+        // Every column of the table, under its own name
+        [SelectItem::Star] => {
+            sources = columns.iter().map(|c| Some(source(c))).collect();
+        }
+        // Positions are only known when no item expands to several columns
+        _ if items.len() == columns.len() && !items.iter().any(|i| matches!(i, SelectItem::Star)) => {
+            sources = items
+                .iter()
+                .map(|item| match item {
+                    SelectItem::Column(column) => Some(source(column)),
+                    _ => None,
+                })
+                .collect();
+        }
+        _ => {}
+    }
+    sources
+}
+
+/// Sources of the result columns of an MSSQL query, as reported by
+/// `sp_describe_first_result_set`. None if the server cannot describe it.
+pub(crate) async fn mssql_column_sources(
+    client: &mut Client<Compat<TcpStream>>,
+    sql: &str,
+) -> Option<Vec<Option<ColumnSource>>> {
+    let rows = client
+        .query(
+            "EXEC sp_describe_first_result_set @tsql = @P1, @params = NULL, @browse_information_mode = 1",
+            &[&sql],
+        )
+        .await
+        .ok()?
+        .into_first_result()
+        .await
+        .ok()?;
+
+    let sources = rows
+        .iter()
+        // Browse mode adds hidden key columns that are not in the result
+        .filter(|row| !row.try_get::<bool, _>("is_hidden").ok().flatten().unwrap_or(false))
+        .map(|row| {
+            let text = |name: &str| row.try_get::<&str, _>(name).ok().flatten().map(|s| s.to_string());
+            Some(ColumnSource {
+                schema: text("source_schema"),
+                table: text("source_table")?,
+                column: text("source_column")?,
+            })
+        })
+        .collect();
+    Some(sources)
+}
@@ -0,0 +1,221 @@
+use std::path::Path;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use crate::db::connections::{load_connections, parse_connection_config, save_connections, Connection};
+use crate::error::AppError;
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionImportResult {
+    pub imported: usize,
+    pub skipped: usize,
+    /// One line per skipped connection or missing password
+    pub warnings: Vec<String>,
+}
+
+/// A connection read from another tool, before validation
+struct ImportedConnection {
+    name: String,
+    db_type: String,
+    config: Value,
+}
+
+/// Map a driver or type name used by other tools to our database type
+fn normalize_db_type(driver: &str) -> Option<&'static str> {
+    let driver = driver.to_lowercase();
+    if driver.contains("sqlite") {
+        Some("sqlite")
+    } else if driver.contains("mysql") || driver.contains("mariadb") {
+        Some("mysql")
+    } else if driver.contains("postgres") || driver == "pg" {
+        Some("postgres")
+    } else if driver.contains("mssql") || driver.contains("sqlserver") || driver.contains("sql_server") {
+        Some("mssql")
+    } else {
+        None
+    }
+}
+
+fn default_port(db_type: &str) -> u64 {
+    match db_type {
+        "mysql" => 3306,
+        "postgres" => 5432,
+        _ => 1433,
+    }
+}
+
+fn text(object: &Map<String, Value>, key: &str) -> Option<String> {
+    object.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string())
+}
+
+/// Ports are strings in DBeaver's files and may be either in generic JSON
+fn port(object: &Map<String, Value>, db_type: &str) -> u64 {
+    match object.get("port") {
+        Some(Value::Number(n)) => n.as_u64(),
+        Some(Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .unwrap_or_else(|| default_port(db_type))
+}
+
+/// Our config payload for a server connection; a missing password is left blank
+fn server_config(db_type: &str, object: &Map<String, Value>, user: Option<String>, password: Option<String>) -> Value {
+    let mut config = object.clone();
+    config.insert("host".to_string(), json!(text(object, "host").unwrap_or_else(|| "localhost".to_string())));
+    config.insert("port".to_string(), json!(port(object, db_type)));
+    config.insert("user".to_string(), json!(user.unwrap_or_default()));
+    config.insert("password".to_string(), json!(password.unwrap_or_default()));
+    Value::Object(config)
+}
+
+/// A generic JSON array of `{name, type|driver, host, port, user, password,
+/// database, filepath, ...}` objects. Other keys are passed through, so our
+/// own connection config fields are accepted as well.
+fn read_generic(content: &str, report: &mut ConnectionImportResult) -> Result<Vec<ImportedConnection>, String> {
+    let items: Vec<Value> = serde_json::from_str(content).map_err(AppError::from)?;
+    let mut connections = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let Some(object) = item.as_object() else {
+            report.warnings.push(format!("Entry {} is not an object", index + 1));
+            report.skipped += 1;
+            continue;
+        };
+        let name = text(object, "name").unwrap_or_else(|| format!("Imported {}", index + 1));
+        let driver = text(object, "type").or_else(|| text(object, "driver")).unwrap_or_default();
+        let Some(db_type) = normalize_db_type(&driver) else {
+            report.warnings.push(format!("{}: unsupported driver '{}'", name, driver));
+            report.skipped += 1;
+            continue;
+        };
+
+        let config = if db_type == "sqlite" {
+            let filepath = text(object, "filepath").or_else(|| text(object, "database")).unwrap_or_default();
+            let mut config = object.clone();
+            config.insert("filepath".to_string(), json!(filepath));
+            Value::Object(config)
+        } else {
+            let user = text(object, "user").or_else(|| text(object, "username"));
+            let password = text(object, "password");
+            server_config(db_type, object, user, password)
+        };
+        connections.push(ImportedConnection { name, db_type: db_type.to_string(), config });
+    }
+    Ok(connections)
+}
+
+/// DBeaver's `credentials-config.json` next to `data-sources.json`, when it
+/// is readable as plain JSON. DBeaver normally encrypts it, in which case no
+/// credentials are imported.
+fn read_dbeaver_credentials(data_sources: &Path, report: &mut ConnectionImportResult) -> Map<String, Value> {
+    let Some(path) = data_sources.parent().map(|dir| dir.join("credentials-config.json")) else {
+        return Map::new();
+    };
+    let Ok(bytes) = std::fs::read(&path) else {
+        return Map::new();
+    };
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(credentials)) => credentials,
+        _ => {
+            report.warnings.push("credentials-config.json is encrypted; user names and passwords were left blank".to_string());
+            Map::new()
+        }
+    }
+}
+
+/// DBeaver's `data-sources.json`: connections keyed by id, each with a
+/// provider/driver and a `configuration` object
+fn read_dbeaver(content: &str, file_path: &Path, report: &mut ConnectionImportResult) -> Result<Vec<ImportedConnection>, String> {
+    let root: Value = serde_json::from_str(content).map_err(AppError::from)?;
+    let entries = root
+        .get("connections")
+        .and_then(|c| c.as_object())
+        .ok_or_else(|| "Not a DBeaver data-sources.json file: missing 'connections'".to_string())?;
+    let credentials = read_dbeaver_credentials(file_path, report);
+
+    let mut connections = Vec::new();
+    for (id, entry) in entries {
+        let name = entry.get("name").and_then(|v| v.as_str()).unwrap_or(id).to_string();
+        let provider = entry.get("provider").and_then(|v| v.as_str()).unwrap_or("");
+        let driver = entry.get("driver").and_then(|v| v.as_str()).unwrap_or("");
+        // Generic-provider connections (e.g. SQLite) only name the engine in the driver
+        let Some(db_type) = normalize_db_type(driver).or_else(|| normalize_db_type(provider)) else {
+            report.warnings.push(format!("{}: unsupported driver '{}'", name, if driver.is_empty() { provider } else { driver }));
+            report.skipped += 1;
+            continue;
+        };
+        let empty = Map::new();
+        let configuration = entry.get("configuration").and_then(|c| c.as_object()).unwrap_or(&empty);
+
+        let config = if db_type == "sqlite" {
+            let url_path = text(configuration, "url").and_then(|u| u.strip_prefix("jdbc:sqlite:").map(|s| s.to_string()));
+            let filepath = text(configuration, "database").or(url_path).unwrap_or_default();
+            json!({ "filepath": filepath })
+        } else {
+            let saved = credentials
+                .get(id)
+                .and_then(|c| c.get("#connection"))
+                .and_then(|c| c.as_object())
+                .unwrap_or(&empty);
+            let user = text(saved, "user").or_else(|| text(configuration, "user"));
+            let password = text(saved, "password").or_else(|| text(configuration, "password"));
+            if password.is_none() {
+                report.warnings.push(format!("{}: password not available, left blank", name));
+            }
+            let mut object = Map::new();
+            object.insert("host".to_string(), json!(text(configuration, "host")));
+            object.insert("port".to_string(), configuration.get("port").cloned().unwrap_or(Value::Null));
+            if let Some(database) = text(configuration, "database") {
+                object.insert("database".to_string(), json!(database));
+            }
+            server_config(db_type, &object, user, password)
+        };
+        connections.push(ImportedConnection { name, db_type: db_type.to_string(), config });
+    }
+    Ok(connections)
+}
+
+/// Import connections from another tool's export: `format` is `dbeaver` for
+/// DBeaver's `data-sources.json` (credentials are read from a plain
+/// `credentials-config.json` beside it) or `json` for a generic array.
+/// Unsupported drivers and invalid entries are skipped and listed in `warnings`.
+#[tauri::command]
+pub async fn import_connections_from(
+    format: String,
+    file_path: String,
+    app: tauri::AppHandle,
+) -> Result<ConnectionImportResult, String> {
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+
+    let mut report = ConnectionImportResult { imported: 0, skipped: 0, warnings: Vec::new() };
+    let candidates = match format.as_str() {
+        "dbeaver" => read_dbeaver(&content, Path::new(&file_path), &mut report)?,
+        "json" => read_generic(&content, &mut report)?,
+        _ => return Err(format!("Unsupported import format: {}", format)),
+    };
+
+    let mut connections = load_connections(&app);
+    for candidate in candidates {
+        match parse_connection_config(&candidate.db_type, &candidate.config) {
+            Ok(config) => {
+                connections.push(Connection {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name: candidate.name,
+                    db_type: candidate.db_type,
+                    config,
+                    last_database: None,
+                    search_path: None,
+                    autocommit: true,
+                    fetch_strategy: None,
+                });
+                report.imported += 1;
+            }
+            Err(e) => {
+                report.warnings.push(format!("{}: {}", candidate.name, e));
+                report.skipped += 1;
+            }
+        }
+    }
+    save_connections(&app, &connections)?;
+
+    Ok(report)
+}
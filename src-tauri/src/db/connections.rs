@@ -1,27 +1,64 @@
 use serde::{Deserialize, Serialize};
 use tauri::{Manager, State};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 use sqlx::Row;
-use tiberius::{Config, AuthMethod, Client, QueryItem};
+use sqlx::mysql::MySqlConnectOptions;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
+use tiberius::{Config, AuthMethod, Client, EncryptionLevel, QueryItem};
 use tokio::net::TcpStream;
+use tokio::sync::oneshot;
 use tokio_util::compat::{TokioAsyncWriteCompatExt, Compat};
 use futures_util::TryStreamExt;
 use crate::db::pool_manager::{PoolManager, DatabasePool};
+use crate::db::autocomplete::SchemaCache;
+use crate::db::cursor::OpenCursors;
+use crate::db::execute::QueryQueue;
+use crate::db::listen::PgListeners;
+use crate::db::server::{server_info, ServerInfoCache};
+use crate::db::query_cache::QueryCache;
+use crate::db::quote::{quote_ident, quote_string};
+use crate::db::recent::{record_recent, remove_recent, RecentKind};
+use crate::db::session::{ensure_database_exists, ActiveDatabases, DatabaseListCache};
+use crate::db::settings;
+use crate::db::store::write_json_atomic;
+use crate::db::transaction::OpenTransactions;
+use crate::error::AppError;
 
 // Helper function to create MSSQL client connection
-async fn create_mssql_client(
-    host: &str,
-    port: u16,
-    user: &str,
-    password: &str,
+pub(crate) async fn create_mssql_client(
+    connection_config: &ConnectionConfig,
     database: Option<&str>,
 ) -> Result<Client<Compat<TcpStream>>, String> {
+    let ConnectionConfig::Mssql {
+        host,
+        port,
+        user,
+        password,
+        tls_mode,
+        ca_cert_path,
+        auth_method,
+        domain,
+        extra_params,
+        ..
+    } = connection_config else {
+        return Err("无效的 MSSQL 配置".to_string());
+    };
+
     let mut config = Config::new();
     config.host(host);
-    config.port(port);
-    config.authentication(AuthMethod::sql_server(user, password));
-    config.trust_cert();
+    config.port(*port);
+    if let Some(name) = settings::application_name(None) {
+        config.application_name(name);
+    }
+    config.authentication(mssql_auth(auth_method.unwrap_or_default(), user, password, domain.as_deref())?);
+    apply_mssql_tls(&mut config, *tls_mode, ca_cert_path.as_deref());
+    apply_mssql_extra_params(&mut config, extra_params.as_ref())?;
     
     if let Some(db) = database {
         config.database(db);
@@ -39,6 +76,321 @@ async fn create_mssql_client(
         .map_err(|e| format!("MSSQL 连接失败: {}", e))
 }
 
+/// Build the tiberius authentication for the configured method. Windows
+/// authentication relies on SSPI and is only available on Windows builds.
+#[cfg_attr(not(windows), allow(unused_variables))]
+fn mssql_auth(
+    auth_method: MssqlAuthMethod,
+    user: &str,
+    password: &str,
+    domain: Option<&str>,
+) -> Result<AuthMethod, String> {
+    match auth_method {
+        MssqlAuthMethod::SqlServer => Ok(AuthMethod::sql_server(user, password)),
+        #[cfg(windows)]
+        MssqlAuthMethod::WindowsIntegrated => Ok(AuthMethod::Integrated),
+        #[cfg(windows)]
+        MssqlAuthMethod::WindowsNtlm => {
+            // tiberius takes the domain as a `DOMAIN\user` prefix
+            let user = match domain.filter(|d| !d.is_empty()) {
+                Some(d) => format!("{}\\{}", d, user),
+                None => user.to_string(),
+            };
+            Ok(AuthMethod::windows(user, password))
+        }
+        #[cfg(not(windows))]
+        MssqlAuthMethod::WindowsIntegrated | MssqlAuthMethod::WindowsNtlm => Err(AppError::PlatformNotSupported(
+            "Windows authentication for MSSQL is only available on Windows".to_string(),
+        ).into()),
+    }
+}
+
+/// Configure encryption and certificate trust for a tiberius connection
+fn apply_mssql_tls(config: &mut Config, tls_mode: Option<TlsMode>, ca_cert_path: Option<&str>) {
+    match tls_mode {
+        // Connections saved before tls_mode existed keep trusting any certificate
+        None => config.trust_cert(),
+        Some(TlsMode::Disable) => config.encryption(EncryptionLevel::NotSupported),
+        Some(TlsMode::Prefer) => {
+            config.encryption(EncryptionLevel::On);
+            config.trust_cert();
+        }
+        Some(TlsMode::Require) => {
+            config.encryption(EncryptionLevel::Required);
+            config.trust_cert();
+        }
+        Some(TlsMode::VerifyCa) | Some(TlsMode::VerifyFull) => {
+            // native-tls always checks the host name, so both verifying modes behave alike;
+            // without a CA file the system trust store is used
+            config.encryption(EncryptionLevel::Required);
+            if let Some(ca) = ca_cert_path {
+                config.trust_cert_ca(ca);
+            }
+        }
+    }
+}
+
+/// TLS behaviour for networked connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TlsMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl TlsMode {
+    /// Effective mode, falling back to the legacy `ssl` flag when no mode is set
+    pub fn resolve(tls_mode: Option<TlsMode>, ssl: bool) -> TlsMode {
+        tls_mode.unwrap_or(if ssl { TlsMode::Require } else { TlsMode::Disable })
+    }
+
+    /// Query string (including the leading `?`) for a MySQL connection URL
+    pub fn mysql_params(self, ca_cert_path: Option<&str>) -> String {
+        let mode = match self {
+            TlsMode::Disable => "DISABLED",
+            TlsMode::Prefer => "PREFERRED",
+            TlsMode::Require => "REQUIRED",
+            TlsMode::VerifyCa => "VERIFY_CA",
+            TlsMode::VerifyFull => "VERIFY_IDENTITY",
+        };
+        match ca_cert_path {
+            Some(ca) => format!("?ssl-mode={}&ssl-ca={}", mode, encode_query_value(ca)),
+            None => format!("?ssl-mode={}", mode),
+        }
+    }
+
+    /// Query string (including the leading `?`) for a PostgreSQL connection URL
+    pub fn postgres_params(self, ca_cert_path: Option<&str>) -> String {
+        let mode = match self {
+            TlsMode::Disable => "disable",
+            TlsMode::Prefer => "prefer",
+            TlsMode::Require => "require",
+            TlsMode::VerifyCa => "verify-ca",
+            TlsMode::VerifyFull => "verify-full",
+        };
+        match ca_cert_path {
+            Some(ca) => format!("?sslmode={}&sslrootcert={}", mode, encode_query_value(ca)),
+            None => format!("?sslmode={}", mode),
+        }
+    }
+}
+
+/// How an MSSQL connection authenticates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MssqlAuthMethod {
+    /// SQL Server login with user and password
+    #[default]
+    SqlServer,
+    /// Current Windows user via SSPI
+    WindowsIntegrated,
+    /// Explicit Windows account (NTLM), optionally with a domain
+    WindowsNtlm,
+}
+
+/// Percent-encode a value for use in a connection URL query string
+pub(crate) fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Parse the optional `auth_method` field of an MSSQL config payload
+fn parse_mssql_auth_method(config: &serde_json::Value) -> Result<Option<MssqlAuthMethod>, String> {
+    match config.get("auth_method") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => serde_json::from_value(v.clone())
+            .map(Some)
+            .map_err(|e| format!("Invalid auth_method: {}", e)),
+    }
+}
+
+/// Parse the optional `tls_mode` field of a connection config payload
+fn parse_tls_mode(config: &serde_json::Value) -> Result<Option<TlsMode>, String> {
+    match config.get("tls_mode") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => serde_json::from_value(v.clone())
+            .map(Some)
+            .map_err(|e| format!("Invalid tls_mode: {}", e)),
+    }
+}
+
+/// Extra parameter names must be plain words so they cannot add URL structure
+fn validate_extra_param_key(key: &str) -> Result<(), String> {
+    let valid = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid connection parameter name: {}", key))
+    }
+}
+
+/// Parse the optional `extra_params` object of a connection config payload
+fn parse_extra_params(config: &serde_json::Value) -> Result<Option<HashMap<String, String>>, String> {
+    let params = match config.get("extra_params") {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(serde_json::Value::Object(map)) => map,
+        Some(_) => return Err("Invalid extra_params: expected an object".to_string()),
+    };
+
+    let mut result = HashMap::new();
+    for (key, value) in params {
+        validate_extra_param_key(key)?;
+        let value = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => value.to_string(),
+            _ => return Err(format!("Invalid value for connection parameter {}", key)),
+        };
+        result.insert(key.clone(), value);
+    }
+    Ok(Some(result).filter(|m| !m.is_empty()))
+}
+
+/// Render extra parameters as `&key=value` pairs to follow the TLS query string
+pub(crate) fn extra_query_params(extra_params: Option<&HashMap<String, String>>) -> Result<String, String> {
+    let Some(params) = extra_params else {
+        return Ok(String::new());
+    };
+    // Sorted so the same config always yields the same URL
+    let mut keys: Vec<&String> = params.keys().collect();
+    keys.sort();
+
+    let mut query = String::new();
+    for key in keys {
+        validate_extra_param_key(key)?;
+        query.push_str(&format!("&{}={}", key, encode_query_value(&params[key])));
+    }
+    Ok(query)
+}
+
+/// Optional MySQL `charset` from a form payload; only a character set name
+/// is accepted, since it ends up in `SET NAMES`
+fn parse_charset(config: &serde_json::Value) -> Result<Option<String>, String> {
+    let Some(charset) = config.get("charset").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    if !charset.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid character set: {}", charset));
+    }
+    Ok(Some(charset.to_string()))
+}
+
+/// Optional `client_cert_path` / `client_key_path` from a form payload; the
+/// certificate is useless without its key, so both or neither must be given
+fn parse_client_cert(config: &serde_json::Value) -> Result<(Option<String>, Option<String>), String> {
+    let path = |key: &str| config.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()).map(|s| s.to_string());
+    match (path("client_cert_path"), path("client_key_path")) {
+        (Some(_), None) => Err("A client certificate needs its key (client_key_path)".to_string()),
+        (None, Some(_)) => Err("A client key needs its certificate (client_cert_path)".to_string()),
+        paths => Ok(paths),
+    }
+}
+
+/// Client certificate and key paths of a MySQL/PostgreSQL config, after
+/// checking both files can be read. The driver only opens them during the
+/// TLS handshake, where a bad path would surface as an obscure TLS error.
+fn client_cert_files(config: &ConnectionConfig) -> Result<Option<(&str, &str)>, String> {
+    let (cert, key) = match config {
+        ConnectionConfig::Mysql { client_cert_path, client_key_path, .. }
+        | ConnectionConfig::Postgres { client_cert_path, client_key_path, .. } => (client_cert_path, client_key_path),
+        _ => return Ok(None),
+    };
+    let (Some(cert), Some(key)) = (cert.as_deref(), key.as_deref()) else {
+        return Ok(None);
+    };
+    for (label, path) in [("client certificate", cert), ("client key", key)] {
+        fs::File::open(path).map_err(|e| format!("Cannot read {} {}: {}", label, path, e))?;
+    }
+    Ok(Some((cert, key)))
+}
+
+/// MySQL connect options from a connection URL plus the client certificate,
+/// which is set on the options because file paths do not survive a URL
+pub(crate) fn mysql_connect_options(url: &str, config: &ConnectionConfig) -> Result<MySqlConnectOptions, String> {
+    let mut options = MySqlConnectOptions::from_str(url).map_err(|e| format!("Invalid MySQL connection options: {}", e))?;
+    if let Some((cert, key)) = client_cert_files(config)? {
+        options = options.ssl_client_cert(cert).ssl_client_key(key);
+    }
+    Ok(options)
+}
+
+/// PostgreSQL counterpart of `mysql_connect_options`
+pub(crate) fn postgres_connect_options(url: &str, config: &ConnectionConfig) -> Result<PgConnectOptions, String> {
+    let mut options = PgConnectOptions::from_str(url).map_err(|e| format!("Invalid PostgreSQL connection options: {}", e))?;
+    if let Some((cert, key)) = client_cert_files(config)? {
+        options = options.ssl_client_cert(cert).ssl_client_key(key);
+    }
+    Ok(options)
+}
+
+/// Database to connect to for server-level queries: the configured
+/// maintenance database, else the connection's own. PostgreSQL cannot
+/// connect without a database and would try one named after the user, so it
+/// falls back to `postgres`; MySQL connects without one.
+pub(crate) fn maintenance_database(config: &ConnectionConfig) -> Option<&str> {
+    match config {
+        ConnectionConfig::Mysql { maintenance_database, database, .. } => {
+            maintenance_database.as_deref().or(database.as_deref())
+        }
+        ConnectionConfig::Postgres { maintenance_database, database, .. } => {
+            maintenance_database.as_deref().or(database.as_deref()).or(Some("postgres"))
+        }
+        _ => None,
+    }
+}
+
+/// `&charset=...` for a MySQL URL; after the extra params, so it wins over
+/// a `charset` given there
+pub(crate) fn charset_param(charset: Option<&str>) -> String {
+    charset.map(|c| format!("&charset={}", c)).unwrap_or_default()
+}
+
+/// `&socket=...` (MySQL) or `&host=...` (PostgreSQL) to connect through a
+/// Unix socket instead of TCP, after checking that the path exists
+pub(crate) fn socket_param(db_type: &str, socket_path: Option<&str>) -> Result<String, String> {
+    let Some(path) = socket_path else {
+        return Ok(String::new());
+    };
+    if !std::path::Path::new(path).exists() {
+        return Err(format!("Socket path does not exist: {}", path));
+    }
+    let key = if db_type == "mysql" { "socket" } else { "host" };
+    Ok(format!("&{}={}", key, encode_query_value(path)))
+}
+
+/// `&application_name=...` for a Postgres URL, unless the user set it in `extra_params`
+pub(crate) fn application_name_param(
+    extra_params: Option<&HashMap<String, String>>,
+    connection_name: Option<&str>,
+) -> String {
+    if extra_params.is_some_and(|p| p.contains_key("application_name")) {
+        return String::new();
+    }
+    settings::application_name(connection_name)
+        .map(|name| format!("&application_name={}", encode_query_value(&name)))
+        .unwrap_or_default()
+}
+
+/// Apply the extra parameters tiberius has a setting for
+fn apply_mssql_extra_params(config: &mut Config, extra_params: Option<&HashMap<String, String>>) -> Result<(), String> {
+    for (key, value) in extra_params.into_iter().flatten() {
+        match key.as_str() {
+            "application_name" | "app_name" => config.application_name(value),
+            "instance_name" => config.instance_name(value),
+            _ => return Err(format!("不支持的 MSSQL 连接参数: {}", key)),
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Connection {
     pub id: String,
@@ -46,13 +398,57 @@ pub struct Connection {
     #[serde(rename = "type")]
     pub db_type: String,
     pub config: ConnectionConfig,
+    /// Database last selected with `set_active_database`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_database: Option<String>,
+    /// PostgreSQL schemas set as `search_path` on every new session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_path: Option<Vec<String>>,
+    /// When false, `execute_sql` keeps writes in a transaction until
+    /// `commit_transaction` or `rollback_transaction`
+    #[serde(default = "default_autocommit")]
+    pub autocommit: bool,
+    /// How `execute_sql` collects rows; None caps them at `max_result_rows`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_strategy: Option<FetchStrategy>,
+}
+
+/// How `execute_sql` collects the rows of a read on a connection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FetchStrategy {
+    /// Every row, however many there are
+    AllRows,
+    /// At most `max_rows` rows, flagging the result as truncated past that
+    Capped { max_rows: usize },
+    /// Rows are emitted in batches of `batch_size` on the `query_id`
+    /// channel, as `execute_sql_stream` does, instead of being returned
+    Streamed { batch_size: usize },
+}
+
+fn default_autocommit() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ConnectionConfig {
     #[serde(rename = "sqlite")]
-    Sqlite { filepath: String },
+    Sqlite {
+        filepath: String,
+        /// Open the database file read-only
+        #[serde(default)]
+        read_only: bool,
+        /// `PRAGMA journal_mode` to apply, e.g. `wal`
+        #[serde(default)]
+        journal_mode: Option<String>,
+        #[serde(default)]
+        busy_timeout_ms: Option<u64>,
+        /// SQLCipher key, sent as `PRAGMA key` before anything else. Needs a
+        /// SQLite built with SQLCipher; plain SQLite ignores the pragma.
+        #[serde(default)]
+        cipher_key: Option<String>,
+    },
     #[serde(rename = "mysql")]
     Mysql {
         host: String,
@@ -60,7 +456,30 @@ pub enum ConnectionConfig {
         user: String,
         password: String,
         database: Option<String>,
+        /// Database that server-level queries (listing databases, sessions)
+        /// connect to; see `maintenance_database`
+        #[serde(default)]
+        maintenance_database: Option<String>,
         ssl: bool,
+        #[serde(default)]
+        tls_mode: Option<TlsMode>,
+        #[serde(default)]
+        ca_cert_path: Option<String>,
+        /// Client certificate and key (PEM) for servers that require mutual TLS
+        #[serde(default)]
+        client_cert_path: Option<String>,
+        #[serde(default)]
+        client_key_path: Option<String>,
+        /// Unix socket file to connect through instead of host/port
+        #[serde(default)]
+        socket_path: Option<String>,
+        /// Driver options appended to the connection URL, e.g. `connect_timeout`
+        #[serde(default)]
+        extra_params: Option<HashMap<String, String>>,
+        /// Client character set (`SET NAMES`) for every new pooled connection,
+        /// e.g. `utf8mb4`; the driver's default when unset
+        #[serde(default)]
+        charset: Option<String>,
     },
     #[serde(rename = "postgres")]
     Postgres {
@@ -69,7 +488,30 @@ pub enum ConnectionConfig {
         user: String,
         password: String,
         database: Option<String>,
+        /// Database that server-level queries (listing databases, sessions)
+        /// connect to; see `maintenance_database`
+        #[serde(default)]
+        maintenance_database: Option<String>,
         ssl: bool,
+        #[serde(default)]
+        tls_mode: Option<TlsMode>,
+        #[serde(default)]
+        ca_cert_path: Option<String>,
+        /// Client certificate and key (PEM) for servers that require mutual TLS
+        #[serde(default)]
+        client_cert_path: Option<String>,
+        #[serde(default)]
+        client_key_path: Option<String>,
+        /// Schema browsed when none is given (`public` if unset)
+        #[serde(default)]
+        schema: Option<String>,
+        /// Directory holding the server's Unix socket (e.g. `/var/run/postgresql`),
+        /// used instead of host; the port picks the socket file
+        #[serde(default)]
+        socket_path: Option<String>,
+        /// Driver options appended to the connection URL, e.g. `connect_timeout`
+        #[serde(default)]
+        extra_params: Option<HashMap<String, String>>,
     },
     #[serde(rename = "mssql")]
     Mssql {
@@ -79,6 +521,21 @@ pub enum ConnectionConfig {
         password: String,
         database: Option<String>,
         ssl: bool,
+        #[serde(default)]
+        tls_mode: Option<TlsMode>,
+        #[serde(default)]
+        ca_cert_path: Option<String>,
+        /// Schema browsed when none is given (`dbo` if unset)
+        #[serde(default)]
+        schema: Option<String>,
+        #[serde(default)]
+        auth_method: Option<MssqlAuthMethod>,
+        /// Windows domain for `windows_ntlm` authentication
+        #[serde(default)]
+        domain: Option<String>,
+        /// Driver options mapped onto the tiberius config (`application_name`, `instance_name`)
+        #[serde(default)]
+        extra_params: Option<HashMap<String, String>>,
     },
 }
 
@@ -126,13 +583,7 @@ pub(crate) fn load_connections(app: &tauri::AppHandle) -> Vec<Connection> {
 
 pub(crate) fn save_connections(app: &tauri::AppHandle, connections: &[Connection]) -> Result<(), String> {
     let path = get_store_path(app)?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
-    let content = serde_json::to_string_pretty(connections)
-        .map_err(|e| format!("Failed to serialize connections: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))?;
-    Ok(())
+    write_json_atomic(&path, connections)
 }
 
 #[tauri::command]
@@ -144,125 +595,17 @@ pub async fn create_connection(
 ) -> Result<String, String> {
     let id = uuid::Uuid::new_v4().to_string();
     
-    let connection_config = match db_type.as_str() {
-        "sqlite" => {
-            let filepath = config
-                .get("filepath")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing filepath for SQLite connection")?
-                .to_string();
-            ConnectionConfig::Sqlite { filepath }
-        }
-        "mysql" => {
-            let host = config
-                .get("host")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing host for MySQL connection")?
-                .to_string();
-            let port = config
-                .get("port")
-                .and_then(|v| v.as_u64())
-                .ok_or("Missing port for MySQL connection")? as u16;
-            let user = config
-                .get("user")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing user for MySQL connection")?
-                .to_string();
-            let password = config
-                .get("password")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing password for MySQL connection")?
-                .to_string();
-            let database = config
-                .get("database")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            let ssl = config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
-            ConnectionConfig::Mysql {
-                host,
-                port,
-                user,
-                password,
-                database,
-                ssl,
-            }
-        }
-        "postgres" => {
-            let host = config
-                .get("host")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing host for PostgreSQL connection")?
-                .to_string();
-            let port = config
-                .get("port")
-                .and_then(|v| v.as_u64())
-                .ok_or("Missing port for PostgreSQL connection")? as u16;
-            let user = config
-                .get("user")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing user for PostgreSQL connection")?
-                .to_string();
-            let password = config
-                .get("password")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing password for PostgreSQL connection")?
-                .to_string();
-            let database = config
-                .get("database")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            let ssl = config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
-            ConnectionConfig::Postgres {
-                host,
-                port,
-                user,
-                password,
-                database,
-                ssl,
-            }
-        }
-        "mssql" => {
-            let host = config
-                .get("host")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing host for MSSQL connection")?
-                .to_string();
-            let port = config
-                .get("port")
-                .and_then(|v| v.as_u64())
-                .ok_or("Missing port for MSSQL connection")? as u16;
-            let user = config
-                .get("user")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing user for MSSQL connection")?
-                .to_string();
-            let password = config
-                .get("password")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing password for MSSQL connection")?
-                .to_string();
-            let database = config
-                .get("database")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            let ssl = config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
-            ConnectionConfig::Mssql {
-                host,
-                port,
-                user,
-                password,
-                database,
-                ssl,
-            }
-        }
-        _ => return Err(format!("Unsupported database type: {}", db_type)),
-    };
+    let connection_config = parse_connection_config(&db_type, &config)?;
 
     let connection = Connection {
         id: id.clone(),
         name,
         db_type,
         config: connection_config,
+        last_database: None,
+        search_path: None,
+        autocommit: true,
+        fetch_strategy: None,
     };
 
     let mut connections = load_connections(&app);
@@ -281,6 +624,30 @@ pub async fn get_connections(
     Ok(load_connections(&app))
 }
 
+/// Set how `execute_sql` collects rows on a connection. None goes back to
+/// capping them at the global `max_result_rows`.
+#[tauri::command]
+pub async fn set_fetch_strategy(
+    connection_id: String,
+    fetch_strategy: Option<FetchStrategy>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    match fetch_strategy {
+        Some(FetchStrategy::Capped { max_rows: 0 }) => return Err("max_rows must be at least 1".to_string()),
+        Some(FetchStrategy::Streamed { batch_size: 0 }) => return Err("batch_size must be at least 1".to_string()),
+        _ => {}
+    }
+
+    let mut connections = load_connections(&app);
+    let connection = connections
+        .iter_mut()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    connection.fetch_strategy = fetch_strategy;
+    save_connections(&app, &connections)
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn update_connection(
     id: String,
@@ -288,6 +655,11 @@ pub async fn update_connection(
     config: Option<serde_json::Value>,
     app: tauri::AppHandle,
     pool_manager: State<'_, PoolManager>,
+    schema_cache: State<'_, SchemaCache>,
+    server_info_cache: State<'_, ServerInfoCache>,
+    query_cache: State<'_, QueryCache>,
+    database_list_cache: State<'_, DatabaseListCache>,
+    open_transactions: State<'_, OpenTransactions>,
 ) -> Result<(), String> {
     let mut connections = load_connections(&app);
     
@@ -296,48 +668,14 @@ pub async fn update_connection(
             conn.name = new_name;
         }
         if let Some(new_config) = config {
-            // Parse config based on connection type
-            let connection_config = match conn.db_type.as_str() {
-                "sqlite" => {
-                    let filepath = new_config
-                        .get("filepath")
-                        .and_then(|v| v.as_str())
-                        .ok_or("Missing filepath")?
-                        .to_string();
-                    ConnectionConfig::Sqlite { filepath }
-                }
-                "mysql" => {
-                    let host = new_config.get("host").and_then(|v| v.as_str()).unwrap_or("localhost").to_string();
-                    let port = new_config.get("port").and_then(|v| v.as_u64()).unwrap_or(3306) as u16;
-                    let user = new_config.get("user").and_then(|v| v.as_str()).unwrap_or("root").to_string();
-                    let password = new_config.get("password").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                    let database = new_config.get("database").and_then(|v| v.as_str()).map(|s| s.to_string());
-                    let ssl = new_config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
-                    ConnectionConfig::Mysql { host, port, user, password, database, ssl }
-                }
-                "postgres" => {
-                    let host = new_config.get("host").and_then(|v| v.as_str()).unwrap_or("localhost").to_string();
-                    let port = new_config.get("port").and_then(|v| v.as_u64()).unwrap_or(5432) as u16;
-                    let user = new_config.get("user").and_then(|v| v.as_str()).unwrap_or("postgres").to_string();
-                    let password = new_config.get("password").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                    let database = new_config.get("database").and_then(|v| v.as_str()).map(|s| s.to_string());
-                    let ssl = new_config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
-                    ConnectionConfig::Postgres { host, port, user, password, database, ssl }
-                }
-                "mssql" => {
-                    let host = new_config.get("host").and_then(|v| v.as_str()).unwrap_or("localhost").to_string();
-                    let port = new_config.get("port").and_then(|v| v.as_u64()).unwrap_or(1433) as u16;
-                    let user = new_config.get("user").and_then(|v| v.as_str()).unwrap_or("sa").to_string();
-                    let password = new_config.get("password").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                    let database = new_config.get("database").and_then(|v| v.as_str()).map(|s| s.to_string());
-                    let ssl = new_config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
-                    ConnectionConfig::Mssql { host, port, user, password, database, ssl }
-                }
-                _ => return Err("Unsupported database type".to_string()),
-            };
-            conn.config = connection_config;
+            conn.config = parse_connection_config(&conn.db_type, &new_config)?;
             // Clear pool cache when config changes
+            open_transactions.rollback(&id).await;
             pool_manager.remove_pool(&id).await;
+            schema_cache.invalidate(&id).await;
+            server_info_cache.invalidate(&id).await;
+            query_cache.invalidate(&id).await;
+            database_list_cache.invalidate(&id).await;
         }
     } else {
         return Err("Connection not found".to_string());
@@ -351,35 +689,211 @@ pub async fn update_connection(
 pub async fn disconnect_connection(
     id: String,
     pool_manager: State<'_, PoolManager>,
+    schema_cache: State<'_, SchemaCache>,
+    open_transactions: State<'_, OpenTransactions>,
 ) -> Result<(), String> {
+    // Uncommitted changes are rolled back on disconnect
+    open_transactions.rollback(&id).await;
     // Remove pool cache to disconnect
     pool_manager.remove_pool(&id).await;
+    schema_cache.invalidate(&id).await;
+    Ok(())
+}
+
+/// Drop the pools of every connection, rolling back open transactions
+async fn disconnect_every_connection(
+    connections: &[Connection],
+    pool_manager: &PoolManager,
+    schema_cache: &SchemaCache,
+    open_transactions: &OpenTransactions,
+) {
+    open_transactions.rollback_all().await;
+    pool_manager.clear_all().await;
+    for connection in connections {
+        schema_cache.invalidate(&connection.id).await;
+    }
+}
+
+/// Release every server connection before the app exits: listeners and
+/// cursors are stopped, open transactions rolled back and all pools closed,
+/// so servers see clean disconnects and free their locks right away
+pub async fn close_all_connections(app: &tauri::AppHandle) {
+    app.state::<PgListeners>().stop_all();
+    app.state::<OpenCursors>().close_all().await;
+    app.state::<OpenTransactions>().rollback_all().await;
+    app.state::<PoolManager>().close_all().await;
+}
+
+/// Disconnect all connections at once, e.g. after the network changed and
+/// every pool holds dead sockets
+#[tauri::command]
+pub async fn disconnect_all(
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    schema_cache: State<'_, SchemaCache>,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    disconnect_every_connection(&connections, &pool_manager, &schema_cache, &open_transactions).await;
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
+pub struct ReconnectStatus {
+    pub connection_id: String,
+    pub name: String,
+    pub result: ConnectionTestResult,
+}
+
+/// Disconnect all connections, then (unless `ping` is false) try each saved
+/// connection again and report whether it can be reached
+#[tauri::command]
+pub async fn reconnect_all(
+    ping: Option<bool>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    schema_cache: State<'_, SchemaCache>,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<Vec<ReconnectStatus>, String> {
+    let connections = load_connections(&app);
+    disconnect_every_connection(&connections, &pool_manager, &schema_cache, &open_transactions).await;
+    if !ping.unwrap_or(true) {
+        return Ok(Vec::new());
+    }
+
+    // Probed together, so unreachable hosts time out in parallel
+    let probes = connections.iter().map(|connection| async move {
+        ReconnectStatus {
+            connection_id: connection.id.clone(),
+            name: connection.name.clone(),
+            result: ConnectionTestResult::from_probe(probe_connection(&connection.config).await),
+        }
+    });
+    Ok(futures_util::future::join_all(probes).await)
+}
+
+/// Drop the pools of one connection and connect again right away, e.g.
+/// after its server restarted while other connections stayed fine. Open
+/// transactions are rolled back; the new pool is for the active database
+/// and is pinged once. MSSQL has no pool and is only probed.
+#[tauri::command]
+pub async fn refresh_pool(
+    connection_id: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<ConnectionTestResult, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    open_transactions.rollback(&connection_id).await;
+    pool_manager.remove_pool(&connection_id).await;
+    if connection.db_type == "mssql" {
+        return Ok(ConnectionTestResult::from_probe(probe_connection(&connection.config).await));
+    }
+
+    let database = app.state::<ActiveDatabases>().resolve(connection, None).await;
+    let probe = async {
+        let pool = pool_manager.get_or_create_pool(connection, database.as_deref()).await?;
+        let started = Instant::now();
+        if !PoolManager::check_pool_health(&pool).await {
+            return Err("连接失败".to_string());
+        }
+        Ok((started.elapsed().as_millis() as u64, None))
+    };
+    Ok(ConnectionTestResult::from_probe(probe.await))
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn delete_connection(
     id: String,
     app: tauri::AppHandle,
     pool_manager: State<'_, PoolManager>,
+    schema_cache: State<'_, SchemaCache>,
+    server_info_cache: State<'_, ServerInfoCache>,
+    active_databases: State<'_, ActiveDatabases>,
+    query_cache: State<'_, QueryCache>,
+    database_list_cache: State<'_, DatabaseListCache>,
+    open_transactions: State<'_, OpenTransactions>,
 ) -> Result<(), String> {
     let mut connections = load_connections(&app);
     connections.retain(|c| c.id != id);
     save_connections(&app, &connections)?;
     
     // Clear pool cache when connection is deleted
+    open_transactions.rollback(&id).await;
     pool_manager.remove_pool(&id).await;
+    schema_cache.invalidate(&id).await;
+    server_info_cache.invalidate(&id).await;
+    active_databases.invalidate(&id).await;
+    query_cache.invalidate(&id).await;
+    database_list_cache.invalidate(&id).await;
+    app.state::<PgListeners>().stop_connection(&id);
+    app.state::<OpenCursors>().close_connection(&id).await;
+    app.state::<QueryQueue>().remove(&id);
+    if let Err(e) = remove_recent(&app, &id) {
+        eprintln!("Failed to save recent objects: {}", e);
+    }
 
     Ok(())
 }
 
+/// Whether a SQLite file path refers to an in-memory database
+pub(crate) fn is_sqlite_memory(filepath: &str) -> bool {
+    filepath == ":memory:" || filepath.contains("mode=memory")
+}
+
+/// Build SQLite connect options from the config.
+///
+/// The file path may carry URI query parameters (e.g. `data.db?mode=ro` or
+/// `?immutable=true`), and `:memory:` opens an in-memory database.
+pub(crate) fn sqlite_connect_options(config: &ConnectionConfig) -> Result<SqliteConnectOptions, String> {
+    let ConnectionConfig::Sqlite { filepath, read_only, journal_mode, busy_timeout_ms, cipher_key } = config else {
+        return Err("Invalid SQLite configuration".to_string());
+    };
+
+    if !is_sqlite_memory(filepath) {
+        let path = filepath.split('?').next().unwrap_or(filepath);
+        if !std::path::Path::new(path).exists() {
+            return Err(format!("SQLite 文件不存在: {}", path));
+        }
+    }
+
+    let url = if filepath == ":memory:" {
+        "sqlite::memory:".to_string()
+    } else {
+        // sqlx requires sqlite:// prefix
+        format!("sqlite://{}", filepath)
+    };
+    let mut options = SqliteConnectOptions::from_str(&url)
+        .map_err(|e| format!("Invalid SQLite path: {}", e))?;
+
+    if let Some(key) = cipher_key {
+        // sqlx runs `key` before its other pragmas, as SQLCipher requires
+        options = options.pragma("key", quote_string("sqlite", key));
+    }
+    if *read_only {
+        options = options.read_only(true);
+    }
+    if let Some(mode) = journal_mode {
+        let mode = SqliteJournalMode::from_str(mode)
+            .map_err(|_| format!("Invalid journal mode: {}", mode))?;
+        options = options.journal_mode(mode);
+    }
+    if let Some(ms) = busy_timeout_ms {
+        options = options.busy_timeout(Duration::from_millis(*ms));
+    }
+    Ok(options)
+}
+
 fn get_connection_string_for_test(config: &ConnectionConfig) -> Result<String, String> {
     match config {
-        ConnectionConfig::Sqlite { filepath } => {
-            if !std::path::Path::new(filepath).exists() {
-                return Err(format!("SQLite 文件不存在: {}", filepath));
-            }
-            // sqlx requires sqlite:// prefix
+        ConnectionConfig::Sqlite { filepath, .. } => {
+            // Note: SQLite connects through sqlite_connect_options, this is for reference
             Ok(format!("sqlite://{}", filepath))
         }
         ConnectionConfig::Mysql {
@@ -389,12 +903,20 @@ fn get_connection_string_for_test(config: &ConnectionConfig) -> Result<String, S
             password,
             database,
             ssl,
+            tls_mode,
+            ca_cert_path,
+            socket_path,
+            extra_params,
+            charset,
+            ..
         } => {
             let db_part = database.as_ref().map(|d| format!("/{}", d)).unwrap_or_default();
-            let ssl_param = if *ssl { "?ssl-mode=REQUIRED" } else { "?ssl-mode=DISABLED" };
+            let ssl_param = TlsMode::resolve(*tls_mode, *ssl).mysql_params(ca_cert_path.as_deref());
+            let socket = socket_param("mysql", socket_path.as_deref())?;
+            let extra = extra_query_params(extra_params.as_ref())?;
             Ok(format!(
-                "mysql://{}:{}@{}:{}{}{}",
-                user, password, host, port, db_part, ssl_param
+                "mysql://{}:{}@{}:{}{}{}{}{}{}",
+                user, password, host, port, db_part, ssl_param, socket, extra, charset_param(charset.as_deref())
             ))
         }
         ConnectionConfig::Postgres {
@@ -404,12 +926,20 @@ fn get_connection_string_for_test(config: &ConnectionConfig) -> Result<String, S
             password,
             database,
             ssl,
+            tls_mode,
+            ca_cert_path,
+            socket_path,
+            extra_params,
+            ..
         } => {
             let db_part = database.as_ref().map(|d| format!("/{}", d)).unwrap_or_default();
-            let ssl_param = if *ssl { "?sslmode=require" } else { "?sslmode=disable" };
+            let ssl_param = TlsMode::resolve(*tls_mode, *ssl).postgres_params(ca_cert_path.as_deref());
+            let socket = socket_param("postgres", socket_path.as_deref())?;
+            let extra = extra_query_params(extra_params.as_ref())?;
+            let app_name = application_name_param(extra_params.as_ref(), None);
             Ok(format!(
-                "postgres://{}:{}@{}:{}{}{}",
-                user, password, host, port, db_part, ssl_param
+                "postgres://{}:{}@{}:{}{}{}{}{}{}",
+                user, password, host, port, db_part, ssl_param, socket, app_name, extra
             ))
         }
         ConnectionConfig::Mssql {
@@ -418,7 +948,7 @@ fn get_connection_string_for_test(config: &ConnectionConfig) -> Result<String, S
             user,
             password,
             database,
-            ssl: _,
+            ..
         } => {
             // Note: tiberius doesn't use connection strings, but we'll format it for reference
             let db_part = database.as_ref().map(|d| format!(";database={}", d)).unwrap_or_default();
@@ -430,29 +960,38 @@ fn get_connection_string_for_test(config: &ConnectionConfig) -> Result<String, S
     }
 }
 
-#[tauri::command]
-pub async fn test_connection(
-    db_type: String,
-    config: serde_json::Value,
-) -> Result<String, String> {
-    let connection_config = match db_type.as_str() {
+/// Build a connection config from a form payload, without saving it
+pub(crate) fn parse_connection_config(db_type: &str, config: &serde_json::Value) -> Result<ConnectionConfig, String> {
+    let connection_config = match db_type {
         "sqlite" => {
             let filepath = config
                 .get("filepath")
                 .and_then(|v| v.as_str())
                 .ok_or("Missing filepath for SQLite connection")?
                 .to_string();
-            ConnectionConfig::Sqlite { filepath }
+            let read_only = config.get("read_only").and_then(|v| v.as_bool()).unwrap_or(false);
+            let journal_mode = config.get("journal_mode").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            let busy_timeout_ms = config.get("busy_timeout_ms").and_then(|v| v.as_u64());
+            let cipher_key = config.get("cipher_key").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            ConnectionConfig::Sqlite { filepath, read_only, journal_mode, busy_timeout_ms, cipher_key }
         }
         "mysql" => {
+            let socket_path = config
+                .get("socket_path")
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+            // A socket connection needs neither host nor port
             let host = config
                 .get("host")
                 .and_then(|v| v.as_str())
+                .or(socket_path.as_ref().map(|_| "localhost"))
                 .ok_or("Missing host for MySQL connection")?
                 .to_string();
             let port = config
                 .get("port")
                 .and_then(|v| v.as_u64())
+                .or(socket_path.as_ref().map(|_| 3306))
                 .ok_or("Missing port for MySQL connection")? as u16;
             let user = config
                 .get("user")
@@ -469,6 +1008,12 @@ pub async fn test_connection(
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
             let ssl = config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
+            let tls_mode = parse_tls_mode(config)?;
+            let ca_cert_path = config.get("ca_cert_path").and_then(|v| v.as_str()).map(|s| s.to_string());
+            let (client_cert_path, client_key_path) = parse_client_cert(config)?;
+            let maintenance_database = config.get("maintenance_database").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            let extra_params = parse_extra_params(config)?;
+            let charset = parse_charset(config)?;
             ConnectionConfig::Mysql {
                 host,
                 port,
@@ -476,17 +1021,34 @@ pub async fn test_connection(
                 password,
                 database,
                 ssl,
+                tls_mode,
+                ca_cert_path,
+                client_cert_path,
+                client_key_path,
+                maintenance_database,
+                socket_path,
+                extra_params,
+                charset,
             }
         }
         "postgres" => {
+            let socket_path = config
+                .get("socket_path")
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+            // A socket directory replaces the host; the port still picks the
+            // socket file (`.s.PGSQL.<port>`), so it defaults to 5432
             let host = config
                 .get("host")
                 .and_then(|v| v.as_str())
+                .or(socket_path.as_ref().map(|_| "localhost"))
                 .ok_or("Missing host for PostgreSQL connection")?
                 .to_string();
             let port = config
                 .get("port")
                 .and_then(|v| v.as_u64())
+                .or(socket_path.as_ref().map(|_| 5432))
                 .ok_or("Missing port for PostgreSQL connection")? as u16;
             let user = config
                 .get("user")
@@ -503,6 +1065,12 @@ pub async fn test_connection(
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
             let ssl = config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
+            let tls_mode = parse_tls_mode(config)?;
+            let ca_cert_path = config.get("ca_cert_path").and_then(|v| v.as_str()).map(|s| s.to_string());
+            let (client_cert_path, client_key_path) = parse_client_cert(config)?;
+            let maintenance_database = config.get("maintenance_database").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            let schema = config.get("schema").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            let extra_params = parse_extra_params(config)?;
             ConnectionConfig::Postgres {
                 host,
                 port,
@@ -510,6 +1078,14 @@ pub async fn test_connection(
                 password,
                 database,
                 ssl,
+                tls_mode,
+                ca_cert_path,
+                client_cert_path,
+                client_key_path,
+                maintenance_database,
+                schema,
+                socket_path,
+                extra_params,
             }
         }
         "mssql" => {
@@ -522,21 +1098,29 @@ pub async fn test_connection(
                 .get("port")
                 .and_then(|v| v.as_u64())
                 .ok_or("Missing port for MSSQL connection")? as u16;
-            let user = config
-                .get("user")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing user for MSSQL connection")?
-                .to_string();
-            let password = config
-                .get("password")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing password for MSSQL connection")?
-                .to_string();
+            let auth_method = parse_mssql_auth_method(config)?;
+            // Integrated authentication uses the current Windows account
+            let integrated = auth_method == Some(MssqlAuthMethod::WindowsIntegrated);
+            let user = match config.get("user").and_then(|v| v.as_str()) {
+                Some(user) => user.to_string(),
+                None if integrated => String::new(),
+                None => return Err("Missing user for MSSQL connection".to_string()),
+            };
+            let password = match config.get("password").and_then(|v| v.as_str()) {
+                Some(password) => password.to_string(),
+                None if integrated => String::new(),
+                None => return Err("Missing password for MSSQL connection".to_string()),
+            };
             let database = config
                 .get("database")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
             let ssl = config.get("ssl").and_then(|v| v.as_bool()).unwrap_or(false);
+            let tls_mode = parse_tls_mode(config)?;
+            let ca_cert_path = config.get("ca_cert_path").and_then(|v| v.as_str()).map(|s| s.to_string());
+            let schema = config.get("schema").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            let domain = config.get("domain").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string());
+            let extra_params = parse_extra_params(config)?;
             ConnectionConfig::Mssql {
                 host,
                 port,
@@ -544,31 +1128,131 @@ pub async fn test_connection(
                 password,
                 database,
                 ssl,
+                tls_mode,
+                ca_cert_path,
+                schema,
+                auth_method,
+                domain,
+                extra_params,
             }
         }
         _ => return Err(format!("Unsupported database type: {}", db_type)),
     };
+    Ok(connection_config)
+}
+
+/// Connection tests in progress that were given a `test_id`, so a test
+/// stuck on an unreachable host can be abandoned with `cancel_test`
+pub struct ConnectionTests {
+    entries: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl ConnectionTests {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register a test and get the receiver that fires when it is cancelled.
+    /// A test already registered under the same id is cancelled.
+    fn register(&self, test_id: &str) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = entries.insert(test_id.to_string(), sender) {
+            let _ = previous.send(());
+        }
+        receiver
+    }
+
+    fn remove(&self, test_id: &str) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(test_id);
+    }
+
+    /// Returns false when no test with this id is running
+    fn cancel(&self, test_id: &str) -> bool {
+        let sender = self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(test_id);
+        sender.is_some_and(|sender| sender.send(()).is_ok())
+    }
+
+    /// Run a test, abandoning it when it is cancelled. Dropping the connect
+    /// future closes whatever socket it had opened.
+    async fn run<T>(
+        &self,
+        test_id: Option<&str>,
+        test: impl std::future::Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        let Some(test_id) = test_id else {
+            return test.await;
+        };
+        let cancel = self.register(test_id);
+        let result = tokio::select! {
+            result = test => result,
+            _ = cancel => Err("测试已取消".to_string()),
+        };
+        self.remove(test_id);
+        result
+    }
+}
 
+impl Default for ConnectionTests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Test an unsaved connection config. With a `test_id` the test can be
+/// stopped with `cancel_test`, which makes it fail with "测试已取消".
+#[tauri::command]
+pub async fn test_connection(
+    db_type: String,
+    config: serde_json::Value,
+    test_id: Option<String>,
+    tests: State<'_, ConnectionTests>,
+) -> Result<String, String> {
+    let connection_config = parse_connection_config(&db_type, &config)?;
+    tests.run(test_id.as_deref(), run_connection_test(db_type, connection_config)).await
+}
+
+/// Abandon a `test_connection` or `test_connection_detailed` started with
+/// this `test_id`. Returns false when it had already finished.
+#[tauri::command]
+pub async fn cancel_test(
+    test_id: String,
+    tests: State<'_, ConnectionTests>,
+) -> Result<bool, String> {
+    Ok(tests.cancel(&test_id))
+}
+
+async fn run_connection_test(db_type: String, connection_config: ConnectionConfig) -> Result<String, String> {
     // Test the connection
     match db_type.as_str() {
         "sqlite" => {
-            let connection_string = get_connection_string_for_test(&connection_config)?;
-            // For SQLite, we just check if the file exists (already done above)
+            let options = sqlite_connect_options(&connection_config)?;
             // Try to open the database to verify it's valid
             match sqlx::sqlite::SqlitePoolOptions::new()
                 .max_connections(1)
-                .connect(&connection_string)
+                .connect_with(options)
                 .await
             {
-                Ok(_) => Ok("SQLite 连接成功".to_string()),
+                Ok(pool) => {
+                    // A wrong key only shows once a page of the file is read
+                    if matches!(connection_config, ConnectionConfig::Sqlite { cipher_key: Some(_), .. }) {
+                        sqlx::query("SELECT COUNT(*) FROM sqlite_master")
+                            .execute(&pool)
+                            .await
+                            .map_err(|e| format!("SQLite 连接失败（密钥错误或 SQLite 未启用 SQLCipher）: {}", e))?;
+                    }
+                    Ok("SQLite 连接成功".to_string())
+                }
                 Err(e) => Err(format!("SQLite 连接失败: {}", e)),
             }
         }
         "mysql" => {
-            let connection_string = get_connection_string_for_test(&connection_config)?;
+            let options = mysql_connect_options(&get_connection_string_for_test(&connection_config)?, &connection_config)?;
             match sqlx::mysql::MySqlPoolOptions::new()
                 .max_connections(1)
-                .connect(&connection_string)
+                .connect_with(options)
                 .await
             {
                 Ok(pool) => {
@@ -588,10 +1272,10 @@ pub async fn test_connection(
             }
         }
         "postgres" => {
-            let connection_string = get_connection_string_for_test(&connection_config)?;
+            let options = postgres_connect_options(&get_connection_string_for_test(&connection_config)?, &connection_config)?;
             match sqlx::postgres::PgPoolOptions::new()
                 .max_connections(1)
-                .connect(&connection_string)
+                .connect_with(options)
                 .await
             {
                 Ok(pool) => {
@@ -611,57 +1295,123 @@ pub async fn test_connection(
             }
         }
         "mssql" => {
-            match &connection_config {
-                ConnectionConfig::Mssql {
-                    host,
-                    port,
-                    user,
-                    password,
-                    database,
-                    ssl: _,
-                } => {
-                    // Create tiberius config
-                    let mut config = Config::new();
-                    config.host(host);
-                    config.port(*port);
-                    config.authentication(AuthMethod::sql_server(user, password));
-                    config.trust_cert(); // Trust server certificate for testing
-                    if let Some(db) = database {
-                        config.database(db);
-                    }
-                    
-                    // Connect to SQL Server
-                    let tcp = TcpStream::connect(config.get_addr())
-                        .await
-                        .map_err(|e| format!("无法连接到服务器 {}:{} - {}", host, port, e))?;
-                    
-                    tcp.set_nodelay(true)
-                        .map_err(|e| format!("设置 TCP 选项失败: {}", e))?;
-                    
-                    // Create client (tiberius handles encryption internally if needed)
-                    let mut client = Client::connect(config, tcp.compat_write())
-                        .await
-                        .map_err(|e| format!("MSSQL 连接失败: {}", e))?;
-                    
-                    // Execute a simple query to verify the connection
-                    let mut stream = client.query("SELECT 1", &[]).await
-                        .map_err(|e| format!("MSSQL 查询失败: {}", e))?;
-                    
-                    // Consume the stream to verify the query executed
-                    while let Some(_row) = stream.try_next().await
-                        .map_err(|e| format!("MSSQL 读取结果失败: {}", e))? {
-                        // Just consume rows to verify connection works
-                    }
-                    
-                    Ok("MSSQL 连接成功".to_string())
-                }
-                _ => Err("无效的 MSSQL 配置".to_string()),
+            // Connect to SQL Server
+            let database = match &connection_config {
+                ConnectionConfig::Mssql { database, .. } => database.clone(),
+                _ => return Err("无效的 MSSQL 配置".to_string()),
+            };
+            let mut client = create_mssql_client(&connection_config, database.as_deref()).await?;
+            
+            // Execute a simple query to verify the connection
+            let mut stream = client.query("SELECT 1", &[]).await
+                .map_err(|e| format!("MSSQL 查询失败: {}", e))?;
+            
+            // Consume the stream to verify the query executed
+            while let Some(_row) = stream.try_next().await
+                .map_err(|e| format!("MSSQL 读取结果失败: {}", e))? {
+                // Just consume rows to verify connection works
             }
+            
+            Ok("MSSQL 连接成功".to_string())
         }
         _ => Err("Unsupported database type".to_string()),
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConnectionTestResult {
+    pub ok: bool,
+    /// Round trip of the version query, once connected
+    pub latency_ms: u64,
+    pub server_version: Option<String>,
+    pub message: String,
+}
+
+impl ConnectionTestResult {
+    fn from_probe(probe: Result<(u64, Option<String>), String>) -> Self {
+        match probe {
+            Ok((latency_ms, server_version)) => Self {
+                ok: true,
+                latency_ms,
+                server_version,
+                message: "连接成功".to_string(),
+            },
+            Err(message) => Self {
+                ok: false,
+                latency_ms: 0,
+                server_version: None,
+                message,
+            },
+        }
+    }
+}
+
+/// Connect with the given config and query the server version, returning the
+/// round-trip time of the query and the version
+async fn probe_connection(config: &ConnectionConfig) -> Result<(u64, Option<String>), String> {
+    let (version, started) = match config {
+        ConnectionConfig::Sqlite { .. } => {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect_with(sqlite_connect_options(config)?)
+                .await
+                .map_err(|e| format!("SQLite 连接失败: {}", e))?;
+            let started = Instant::now();
+            (sqlx::query("SELECT sqlite_version()").fetch_one(&pool).await.map(|r| r.get::<String, _>(0)), started)
+        }
+        ConnectionConfig::Mysql { .. } => {
+            let pool = sqlx::mysql::MySqlPoolOptions::new()
+                .max_connections(1)
+                .connect_with(mysql_connect_options(&get_connection_string_for_test(config)?, config)?)
+                .await
+                .map_err(|e| format!("MySQL 连接失败: {}", e))?;
+            let started = Instant::now();
+            (sqlx::query("SELECT VERSION()").fetch_one(&pool).await.map(|r| r.get::<String, _>(0)), started)
+        }
+        ConnectionConfig::Postgres { .. } => {
+            let pool = sqlx::postgres::PgPoolOptions::new()
+                .max_connections(1)
+                .connect_with(postgres_connect_options(&get_connection_string_for_test(config)?, config)?)
+                .await
+                .map_err(|e| format!("PostgreSQL 连接失败: {}", e))?;
+            let started = Instant::now();
+            (sqlx::query("SHOW server_version").fetch_one(&pool).await.map(|r| r.get::<String, _>(0)), started)
+        }
+        ConnectionConfig::Mssql { database, .. } => {
+            let mut client = create_mssql_client(config, database.as_deref()).await?;
+            let started = Instant::now();
+            let mut stream = client.query("SELECT CAST(SERVERPROPERTY('ProductVersion') AS NVARCHAR(128))", &[]).await
+                .map_err(|e| format!("MSSQL 查询失败: {}", e))?;
+            let mut version = None;
+            while let Some(item) = stream.try_next().await
+                .map_err(|e| format!("MSSQL 读取结果失败: {}", e))? {
+                if let QueryItem::Row(row) = item {
+                    version = row.try_get::<&str, _>(0).ok().flatten().map(|s| s.to_string());
+                }
+            }
+            return Ok((started.elapsed().as_millis() as u64, version));
+        }
+    };
+    let version = version.map_err(|e| format!("连接测试失败: {}", e))?;
+    Ok((started.elapsed().as_millis() as u64, Some(version)))
+}
+
+/// Test an unsaved connection config and report latency and server version.
+/// Connection failures are reported in the result rather than as an error;
+/// only an invalid config is an error.
+#[tauri::command]
+pub async fn test_connection_detailed(
+    db_type: String,
+    config: serde_json::Value,
+    test_id: Option<String>,
+    tests: State<'_, ConnectionTests>,
+) -> Result<ConnectionTestResult, String> {
+    let connection_config = parse_connection_config(&db_type, &config)?;
+
+    let probe = tests.run(test_id.as_deref(), probe_connection(&connection_config)).await;
+    Ok(ConnectionTestResult::from_probe(probe))
+}
+
 #[tauri::command]
 pub async fn list_databases(
     connection_id: String,
@@ -682,16 +1432,9 @@ pub async fn list_databases(
     // Handle MSSQL separately since it uses tiberius instead of sqlx
     if connection.db_type == "mssql" {
         match &connection.config {
-            ConnectionConfig::Mssql {
-                host,
-                port,
-                user,
-                password,
-                database: _,
-                ssl: _,
-            } => {
+            ConnectionConfig::Mssql { .. } => {
                 // Connect without specific database to list all databases
-                let mut client: Client<Compat<TcpStream>> = create_mssql_client(host, *port, user, password, None).await?;
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, None).await?;
                 
                 // Query databases (exclude system databases with database_id <= 4)
                 let mut stream: tiberius::QueryStream<'_> = client.query(
@@ -716,8 +1459,7 @@ pub async fn list_databases(
         }
     }
 
-    // Get or create pool (without database name)
-    let pool = pool_manager.get_pool_without_db(&connection).await?;
+    let pool = pool_manager.get_maintenance_pool(&connection).await?;
 
     // Query databases
     match pool {
@@ -751,44 +1493,143 @@ pub async fn list_databases(
     }
 }
 
+/// List schemas of a Postgres or MSSQL database. SQLite and MySQL have no
+/// schemas separate from databases, so they return an empty list.
+#[tauri::command]
+pub async fn list_schemas(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<String>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .into_iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    // Handle MSSQL separately since it uses tiberius instead of sqlx
+    if connection.db_type == "mssql" {
+        match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                let db_name = database.as_deref().or(config_db.as_deref());
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
+
+                // Skip system schemas and the fixed database role schemas (schema_id >= 16384)
+                let mut stream: tiberius::QueryStream<'_> = client.query(
+                    "SELECT name FROM sys.schemas WHERE schema_id < 16384 AND name NOT IN ('sys', 'INFORMATION_SCHEMA', 'guest') ORDER BY name",
+                    &[]
+                ).await
+                    .map_err(|e| format!("查询架构列表失败: {}", e))?;
+
+                let mut schemas = Vec::new();
+                while let Some(item) = stream.try_next().await
+                    .map_err(|e| format!("读取结果失败: {}", e))? {
+                    if let QueryItem::Row(row) = item {
+                        if let Some(name) = row.try_get::<&str, _>(0).ok().flatten() {
+                            schemas.push(name.to_string());
+                        }
+                    }
+                }
+
+                return Ok(schemas);
+            }
+            _ => return Err("无效的 MSSQL 配置".to_string()),
+        }
+    }
+
+    if connection.db_type != "postgres" {
+        return Ok(vec![]);
+    }
+
+    // Without a database this lists the maintenance database, which the
+    // connection can always reach
+    let database = database.as_deref().or(maintenance_database(&connection.config));
+    let pool = pool_manager.get_or_create_pool(&connection, database).await?;
+
+    match pool {
+        DatabasePool::Postgres(p) => {
+            let result = sqlx::query(
+                "SELECT schema_name FROM information_schema.schemata
+                 WHERE schema_name NOT LIKE 'pg\\_%' AND schema_name <> 'information_schema'
+                 ORDER BY schema_name"
+            )
+                .fetch_all(&p)
+                .await
+                .map_err(|e| format!("Failed to list schemas: {}", e))?;
+
+            let schemas: Vec<String> = result
+                .into_iter()
+                .map(|row| row.get::<String, _>(0))
+                .collect();
+
+            Ok(schemas)
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+/// Schema to browse: the explicit one, else the connection's default schema,
+/// else `public` (PostgreSQL) / `dbo` (MSSQL)
+pub(crate) fn resolve_schema(config: &ConnectionConfig, schema: Option<&str>) -> String {
+    let configured = match config {
+        ConnectionConfig::Postgres { schema, .. } | ConnectionConfig::Mssql { schema, .. } => schema.as_deref(),
+        _ => None,
+    };
+    match schema.filter(|s| !s.is_empty()).or(configured) {
+        Some(s) => s.to_string(),
+        None if matches!(config, ConnectionConfig::Mssql { .. }) => "dbo".to_string(),
+        None => "public".to_string(),
+    }
+}
+
 #[tauri::command]
 pub async fn list_tables(
     connection_id: String,
     database: Option<String>,
+    schema: Option<String>,
     app: tauri::AppHandle,
     pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
 ) -> Result<Vec<String>, String> {
     let connections = load_connections(&app);
     let connection = connections
         .into_iter()
         .find(|c| c.id == connection_id)
         .ok_or_else(|| "Connection not found".to_string())?;
+    let database = active_databases.resolve(&connection, database).await;
+    // The name is interpolated below (SHOW TABLES FROM, TABLE_CATALOG)
+    if let Some(db) = &database {
+        ensure_database_exists(&connection, db, &app, &pool_manager).await?;
+        if let Err(e) = record_recent(&app, &connection.id, RecentKind::Database, Some(db), db) {
+            eprintln!("Failed to save recent databases: {}", e);
+        }
+    }
 
     // Handle MSSQL separately since it uses tiberius instead of sqlx
     if connection.db_type == "mssql" {
         match &connection.config {
-            ConnectionConfig::Mssql {
-                host,
-                port,
-                user,
-                password,
-                database: config_db,
-                ssl: _,
-            } => {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
                 // Use specified database or config database
                 let db_name = database.as_deref().or(config_db.as_deref());
                 
                 // Create client connection
-                let mut client: Client<Compat<TcpStream>> = create_mssql_client(host, *port, user, password, db_name).await?;
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
                 
+                let schema_literal = quote_string("mssql", &resolve_schema(&connection.config, schema.as_deref()));
+
                 // Query tables from information_schema (optimized query)
                 let query = if let Some(db) = db_name {
                     format!(
-                        "SELECT TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_TYPE = 'BASE TABLE' AND TABLE_CATALOG = '{}' ORDER BY TABLE_NAME",
-                        db.replace("'", "''")
+                        "SELECT TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_TYPE = 'BASE TABLE' AND TABLE_CATALOG = {} AND TABLE_SCHEMA = {} ORDER BY TABLE_NAME",
+                        quote_string("mssql", db),
+                        schema_literal
                     )
                 } else {
-                    "SELECT TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_NAME".to_string()
+                    format!(
+                        "SELECT TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_TYPE = 'BASE TABLE' AND TABLE_SCHEMA = {} ORDER BY TABLE_NAME",
+                        schema_literal
+                    )
                 };
                 
                 let mut stream: tiberius::QueryStream<'_> = client.query(&query, &[])
@@ -832,7 +1673,7 @@ pub async fn list_tables(
         DatabasePool::Mysql(p) => {
             // Use SHOW TABLES query
             let query = if let Some(db) = database.as_ref() {
-                format!("SHOW TABLES FROM `{}`", db.replace("`", "``"))
+                format!("SHOW TABLES FROM {}", quote_ident("mysql", db))
             } else {
                 "SHOW TABLES".to_string()
             };
@@ -853,10 +1694,11 @@ pub async fn list_tables(
             // Query tables from information_schema
             let result = sqlx::query(
                 "SELECT table_name FROM information_schema.tables 
-                 WHERE table_schema = 'public' 
+                 WHERE table_schema = $1 
                  AND table_type = 'BASE TABLE'
                  ORDER BY table_name"
             )
+                .bind(resolve_schema(&connection.config, schema.as_deref()))
                 .fetch_all(&p)
                 .await
                 .map_err(|e| format!("Failed to list tables: {}", e))?;
@@ -871,7 +1713,65 @@ pub async fn list_tables(
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Columns of a MariaDB table declared as JSON, found through their json_valid() checks
+async fn mariadb_json_columns(pool: &sqlx::MySqlPool, database: Option<&str>, table_name: &str) -> Vec<String> {
+    let rows = sqlx::query(
+        "SELECT CHECK_CLAUSE FROM information_schema.CHECK_CONSTRAINTS
+         WHERE CONSTRAINT_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?"
+    )
+        .bind(database)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default();
+
+    rows.into_iter()
+        .filter_map(|row| row.try_get::<String, _>(0).ok())
+        .filter_map(|clause| {
+            let column = clause.trim().strip_prefix("json_valid(`")?.strip_suffix("`)")?;
+            Some(column.replace("``", "`"))
+        })
+        .collect()
+}
+
+/// Values of a MySQL `enum('a','b')` or `set(...)` column type; quotes inside
+/// a value are doubled in the type string
+fn parse_mysql_enum_values(column_type: &str) -> Option<Vec<String>> {
+    let lower = column_type.to_lowercase();
+    let start = if lower.starts_with("enum(") {
+        5
+    } else if lower.starts_with("set(") {
+        4
+    } else {
+        return None;
+    };
+    let body = column_type[start..].strip_suffix(')')?;
+
+    let mut values = Vec::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    value.push('\'');
+                } else {
+                    break;
+                }
+            } else {
+                value.push(c);
+            }
+        }
+        values.push(value);
+    }
+    Some(values)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
@@ -879,6 +1779,9 @@ pub struct ColumnInfo {
     pub default: Option<String>,
     pub primary_key: bool,
     pub auto_increment: bool,
+    /// Allowed values of a MySQL ENUM/SET or PostgreSQL enum column, in declaration order
+    #[serde(default)]
+    pub enum_values: Option<Vec<String>>,
 }
 
 #[tauri::command]
@@ -886,6 +1789,7 @@ pub async fn describe_table(
     connection_id: String,
     table_name: String,
     database: Option<String>,
+    schema: Option<String>,
     app: tauri::AppHandle,
     pool_manager: State<'_, PoolManager>,
 ) -> Result<Vec<ColumnInfo>, String> {
@@ -894,24 +1798,28 @@ pub async fn describe_table(
         .into_iter()
         .find(|c| c.id == connection_id)
         .ok_or_else(|| "Connection not found".to_string())?;
+    if let Some(db) = &database {
+        ensure_database_exists(&connection, db, &app, &pool_manager).await?;
+    }
 
     // Handle MSSQL separately since it uses tiberius instead of sqlx
     if connection.db_type == "mssql" {
         match &connection.config {
-            ConnectionConfig::Mssql {
-                host,
-                port,
-                user,
-                password,
-                database: config_db,
-                ssl: _,
-            } => {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
                 let db_name = database.as_deref().or(config_db.as_deref());
-                let mut client: Client<Compat<TcpStream>> = create_mssql_client(host, *port, user, password, db_name).await?;
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
                 
-                // Escape table name
-                let escaped_table = table_name.replace("'", "''");
-                let escaped_db = db_name.map(|d| d.replace("'", "''"));
+                // Names as string literals
+                let schema_name = resolve_schema(&connection.config, schema.as_deref());
+                let escaped_table = quote_string("mssql", &table_name);
+                let escaped_schema = quote_string("mssql", &schema_name);
+                let escaped_db = db_name.map(|d| quote_string("mssql", d));
+                // Schema-qualified name for OBJECT_ID
+                let object_name = quote_string("mssql", &format!(
+                    "{}.{}",
+                    quote_ident("mssql", &schema_name),
+                    quote_ident("mssql", &table_name)
+                ));
                 
                 // Query column information from information_schema
                 let query = if let Some(db) = &escaped_db {
@@ -922,7 +1830,7 @@ pub async fn describe_table(
                             IS_NULLABLE,
                             COLUMN_DEFAULT,
                             CASE WHEN pk.COLUMN_NAME IS NOT NULL THEN 1 ELSE 0 END AS IS_PRIMARY_KEY,
-                            CASE WHEN COLUMNPROPERTY(OBJECT_ID('{}'), COLUMN_NAME, 'IsIdentity') = 1 THEN 1 ELSE 0 END AS IS_IDENTITY
+                            CASE WHEN COLUMNPROPERTY(OBJECT_ID({}), COLUMN_NAME, 'IsIdentity') = 1 THEN 1 ELSE 0 END AS IS_IDENTITY
                         FROM INFORMATION_SCHEMA.COLUMNS c
                         LEFT JOIN (
                             SELECT ku.TABLE_CATALOG, ku.TABLE_SCHEMA, ku.TABLE_NAME, ku.COLUMN_NAME
@@ -934,10 +1842,11 @@ pub async fn describe_table(
                             AND c.TABLE_SCHEMA = pk.TABLE_SCHEMA 
                             AND c.TABLE_NAME = pk.TABLE_NAME 
                             AND c.COLUMN_NAME = pk.COLUMN_NAME
-                        WHERE c.TABLE_CATALOG = '{}' AND c.TABLE_NAME = '{}'
+                        WHERE c.TABLE_CATALOG = {} AND c.TABLE_SCHEMA = {} AND c.TABLE_NAME = {}
                         ORDER BY c.ORDINAL_POSITION",
-                        table_name.replace("'", "''"),
+                        object_name,
                         db,
+                        escaped_schema,
                         escaped_table
                     )
                 } else {
@@ -948,7 +1857,7 @@ pub async fn describe_table(
                             IS_NULLABLE,
                             COLUMN_DEFAULT,
                             CASE WHEN pk.COLUMN_NAME IS NOT NULL THEN 1 ELSE 0 END AS IS_PRIMARY_KEY,
-                            CASE WHEN COLUMNPROPERTY(OBJECT_ID('{}'), COLUMN_NAME, 'IsIdentity') = 1 THEN 1 ELSE 0 END AS IS_IDENTITY
+                            CASE WHEN COLUMNPROPERTY(OBJECT_ID({}), COLUMN_NAME, 'IsIdentity') = 1 THEN 1 ELSE 0 END AS IS_IDENTITY
                         FROM INFORMATION_SCHEMA.COLUMNS c
                         LEFT JOIN (
                             SELECT ku.TABLE_CATALOG, ku.TABLE_SCHEMA, ku.TABLE_NAME, ku.COLUMN_NAME
@@ -960,9 +1869,10 @@ pub async fn describe_table(
                             AND c.TABLE_SCHEMA = pk.TABLE_SCHEMA 
                             AND c.TABLE_NAME = pk.TABLE_NAME 
                             AND c.COLUMN_NAME = pk.COLUMN_NAME
-                        WHERE c.TABLE_NAME = '{}'
+                        WHERE c.TABLE_SCHEMA = {} AND c.TABLE_NAME = {}
                         ORDER BY c.ORDINAL_POSITION",
-                        table_name.replace("'", "''"),
+                        object_name,
+                        escaped_schema,
                         escaped_table
                     )
                 };
@@ -994,6 +1904,7 @@ pub async fn describe_table(
                             default,
                             primary_key: is_pk,
                             auto_increment: is_identity,
+                            enum_values: None,
                         });
                     }
                 }
@@ -1011,7 +1922,7 @@ pub async fn describe_table(
     match pool {
         DatabasePool::Sqlite(p) => {
             // Use PRAGMA table_info for SQLite
-            let query = format!("PRAGMA table_info(`{}`)", table_name.replace("`", "``"));
+            let query = format!("PRAGMA table_info({})", quote_ident("sqlite", &table_name));
             let result = sqlx::query(&query)
                 .fetch_all(&p)
                 .await
@@ -1034,6 +1945,7 @@ pub async fn describe_table(
                         default,
                         primary_key: pk == 1,
                         auto_increment: false, // SQLite doesn't have auto_increment in PRAGMA, would need separate check
+                        enum_values: None,
                     }
                 })
                 .collect();
@@ -1042,18 +1954,28 @@ pub async fn describe_table(
         }
         DatabasePool::Mysql(p) => {
             // Use SHOW COLUMNS for MySQL
-            let escaped_table = table_name.replace("`", "``");
+            let table = quote_ident("mysql", &table_name);
             let query = if let Some(db) = database.as_ref() {
-                let escaped_db = db.replace("`", "``");
-                format!("SHOW COLUMNS FROM `{}`.`{}`", escaped_db, escaped_table)
+                format!("SHOW COLUMNS FROM {}.{}", quote_ident("mysql", db), table)
             } else {
-                format!("SHOW COLUMNS FROM `{}`", escaped_table)
+                format!("SHOW COLUMNS FROM {}", table)
             };
             
             let result = sqlx::query(&query)
                 .fetch_all(&p)
                 .await
                 .map_err(|e| format!("查询表结构失败: {}", e))?;
+
+            let is_mariadb = server_info(&connection, database.as_deref(), &pool_manager, &app.state::<ServerInfoCache>())
+                .await
+                .map(|info| info.is_mariadb)
+                .unwrap_or(false);
+            // MariaDB stores JSON as LONGTEXT guarded by a json_valid() check
+            let json_columns = if is_mariadb {
+                mariadb_json_columns(&p, database.as_deref(), &table_name).await
+            } else {
+                vec![]
+            };
             
             let columns: Vec<ColumnInfo> = result
                 .into_iter()
@@ -1064,14 +1986,20 @@ pub async fn describe_table(
                     let key: String = row.get(3);
                     let default: Option<String> = row.get(4);
                     let extra: String = row.get(5);
+
+                    let enum_values = parse_mysql_enum_values(&type_str);
+                    let data_type = if json_columns.contains(&field) { "json".to_string() } else { type_str };
+                    // MariaDB reports an explicit NULL default as the text NULL
+                    let default = default.filter(|d| !(is_mariadb && d == "NULL"));
                     
                     ColumnInfo {
                         name: field,
-                        data_type: type_str,
+                        data_type,
                         nullable: null == "YES",
                         default,
                         primary_key: key == "PRI",
                         auto_increment: extra.contains("auto_increment"),
+                        enum_values,
                     }
                 })
                 .collect();
@@ -1080,7 +2008,6 @@ pub async fn describe_table(
         }
         DatabasePool::Postgres(p) => {
             // Use information_schema for PostgreSQL
-            let escaped_table = table_name.replace("'", "''");
             let query = format!(
                 "SELECT 
                     column_name,
@@ -1088,7 +2015,12 @@ pub async fn describe_table(
                     is_nullable,
                     column_default,
                     CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END AS is_primary_key,
-                    CASE WHEN column_default LIKE 'nextval%' THEN true ELSE false END AS is_auto_increment
+                    CASE WHEN column_default LIKE 'nextval%' THEN true ELSE false END AS is_auto_increment,
+                    (SELECT array_agg(e.enumlabel::text ORDER BY e.enumsortorder)
+                     FROM pg_type t
+                     JOIN pg_namespace tn ON tn.oid = t.typnamespace
+                     JOIN pg_enum e ON e.enumtypid = t.oid
+                     WHERE t.typname = c.udt_name AND tn.nspname = c.udt_schema) AS enum_values
                 FROM information_schema.columns c
                 LEFT JOIN (
                     SELECT ku.table_schema, ku.table_name, ku.column_name
@@ -1099,9 +2031,10 @@ pub async fn describe_table(
                 ) pk ON c.table_schema = pk.table_schema 
                     AND c.table_name = pk.table_name 
                     AND c.column_name = pk.column_name
-                WHERE c.table_schema = 'public' AND c.table_name = '{}'
+                WHERE c.table_schema = {} AND c.table_name = {}
                 ORDER BY c.ordinal_position",
-                escaped_table
+                quote_string("postgres", &resolve_schema(&connection.config, schema.as_deref())),
+                quote_string("postgres", &table_name)
             );
             
             let result = sqlx::query(&query)
@@ -1118,6 +2051,7 @@ pub async fn describe_table(
                     let default: Option<String> = row.get(3);
                     let is_pk: bool = row.get(4);
                     let is_auto: bool = row.get(5);
+                    let enum_values: Option<Vec<String>> = row.get(6);
                     
                     ColumnInfo {
                         name,
@@ -1126,6 +2060,7 @@ pub async fn describe_table(
                         default,
                         primary_key: is_pk,
                         auto_increment: is_auto,
+                        enum_values,
                     }
                 })
                 .collect();
@@ -1135,3 +2070,48 @@ pub async fn describe_table(
     }
 }
 
+
+/// SQLite connection to a new, empty database file in the temp directory.
+/// A busy timeout of 0 makes any unserialized write conflict fail at once.
+#[cfg(test)]
+pub(crate) fn sqlite_test_connection(name: &str) -> Connection {
+    let path = std::env::temp_dir().join(format!("feathersql-{}-{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    std::fs::File::create(&path).unwrap();
+    serde_json::from_value(serde_json::json!({
+        "id": name,
+        "name": name,
+        "type": "sqlite",
+        "config": {
+            "type": "sqlite",
+            "filepath": path.to_string_lossy(),
+            "busy_timeout_ms": 0,
+        },
+    }))
+    .unwrap()
+}
+
+/// Connection for the server integration tests, from the JSON connection
+/// config in the environment variable `var`, e.g. `{"type": "mssql", "host": ...}`.
+/// The tests using it are ignored; run them with `cargo test -- --ignored`.
+#[cfg(test)]
+fn server_test_connection(var: &str, db_type: &str) -> Connection {
+    let config = std::env::var(var).unwrap_or_else(|_| panic!("{} is not set", var));
+    serde_json::from_value(serde_json::json!({
+        "id": format!("{}-test", db_type),
+        "name": format!("{}-test", db_type),
+        "type": db_type,
+        "config": serde_json::from_str::<serde_json::Value>(&config).unwrap(),
+    }))
+    .unwrap()
+}
+
+#[cfg(test)]
+pub(crate) fn mssql_test_connection() -> Connection {
+    server_test_connection("FEATHERSQL_TEST_MSSQL", "mssql")
+}
+
+#[cfg(test)]
+pub(crate) fn postgres_test_connection() -> Connection {
+    server_test_connection("FEATHERSQL_TEST_POSTGRES", "postgres")
+}
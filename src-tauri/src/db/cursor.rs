@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::Serialize;
+use sqlx::pool::PoolConnection;
+use sqlx::{Executor, Postgres};
+use tauri::{Manager, State};
+use tokio::sync::Mutex;
+use crate::db::connections::load_connections;
+use crate::db::execute::{column_metadata, postgres_value_to_json};
+use crate::db::pool_manager::{DatabasePool, PoolManager};
+use crate::db::session::ActiveDatabases;
+use crate::db::sqlkind::{classify_statement, StatementKind};
+
+// Rows per FETCH when the caller gives no batch size, and the most allowed
+const DEFAULT_BATCH_SIZE: usize = 1000;
+const MAX_BATCH_SIZE: usize = 100_000;
+// Each cursor has a connection of its own, so one name is enough
+const CURSOR_NAME: &str = "feathersql_cursor";
+
+/// A declared cursor and the connection whose transaction keeps it open
+struct ServerCursor {
+    connection_id: String,
+    conn: PoolConnection<Postgres>,
+    batch_size: usize,
+}
+
+impl ServerCursor {
+    /// End the transaction, which closes the cursor, and give the connection
+    /// back; it is closed instead if the rollback fails
+    async fn close(mut self) {
+        if self.conn.execute("ROLLBACK").await.is_err() {
+            drop(self.conn.close().await);
+        }
+    }
+}
+
+/// Open server-side cursors by id. Each holds a pooled connection until it
+/// is read to the end or closed.
+pub struct OpenCursors {
+    cursors: Mutex<HashMap<String, Arc<Mutex<Option<ServerCursor>>>>>,
+}
+
+impl OpenCursors {
+    pub fn new() -> Self {
+        Self {
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn get(&self, cursor_id: &str) -> Option<Arc<Mutex<Option<ServerCursor>>>> {
+        self.cursors.lock().await.get(cursor_id).cloned()
+    }
+
+    async fn remove(&self, cursor_id: &str) -> Option<ServerCursor> {
+        let cursor = self.cursors.lock().await.remove(cursor_id)?;
+        let taken = cursor.lock().await.take();
+        taken
+    }
+
+    /// Close every cursor of a connection, e.g. when it is deleted
+    pub async fn close_connection(&self, connection_id: &str) {
+        let cursors: Vec<_> = self
+            .cursors
+            .lock()
+            .await
+            .iter()
+            .map(|(id, cursor)| (id.clone(), cursor.clone()))
+            .collect();
+        for (id, cursor) in cursors {
+            let matches = cursor.lock().await.as_ref().is_some_and(|c| c.connection_id == connection_id);
+            if !matches {
+                continue;
+            }
+            if let Some(cursor) = self.remove(&id).await {
+                cursor.close().await;
+            }
+        }
+    }
+
+    /// Close every cursor, e.g. when the app exits
+    pub async fn close_all(&self) {
+        let cursors: Vec<_> = self.cursors.lock().await.drain().map(|(_, cursor)| cursor).collect();
+        for cursor in cursors {
+            let taken = cursor.lock().await.take();
+            if let Some(cursor) = taken {
+                cursor.close().await;
+            }
+        }
+    }
+}
+
+impl Default for OpenCursors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CursorBatch {
+    pub columns: Vec<String>,
+    pub column_types: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// The cursor is exhausted and has been closed
+    pub done: bool,
+}
+
+/// Declare a server-side cursor for a SELECT (PostgreSQL), so its rows are
+/// read in batches of `batch_size` with `fetch_cursor` instead of being
+/// materialized at once. The cursor lives in a transaction on a connection
+/// of its own; `close_cursor` releases it early. Returns the cursor id.
+#[tauri::command]
+pub async fn open_cursor(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    batch_size: Option<usize>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    cursors: State<'_, OpenCursors>,
+) -> Result<String, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if connection.db_type != "postgres" {
+        return Err("Server-side cursors are only supported for PostgreSQL".to_string());
+    }
+    if classify_statement(&sql) != StatementKind::Query {
+        return Err("A cursor can only be opened for a SELECT".to_string());
+    }
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).clamp(1, MAX_BATCH_SIZE);
+
+    let database = app.state::<ActiveDatabases>().resolve(connection, database).await;
+    let pool = match pool_manager.get_or_create_pool(connection, database.as_deref()).await? {
+        DatabasePool::Postgres(p) => p,
+        _ => return Err("Server-side cursors are only supported for PostgreSQL".to_string()),
+    };
+    let mut conn = pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?;
+    conn.execute("BEGIN").await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let declare = format!("DECLARE {} NO SCROLL CURSOR FOR {}", CURSOR_NAME, sql.trim().trim_end_matches(';'));
+    let mut cursor = ServerCursor { connection_id, conn, batch_size };
+    if let Err(e) = cursor.conn.execute(declare.as_str()).await {
+        cursor.close().await;
+        return Err(format!("Failed to open cursor: {}", e));
+    }
+
+    let cursor_id = uuid::Uuid::new_v4().to_string();
+    cursors
+        .cursors
+        .lock()
+        .await
+        .insert(cursor_id.clone(), Arc::new(Mutex::new(Some(cursor))));
+    Ok(cursor_id)
+}
+
+/// Next batch of rows from a cursor. A batch shorter than the batch size
+/// is the last one: the cursor is closed and `done` is set.
+#[tauri::command]
+pub async fn fetch_cursor(
+    cursor_id: String,
+    cursors: State<'_, OpenCursors>,
+) -> Result<CursorBatch, String> {
+    let not_open = || format!("Cursor {} is not open", cursor_id);
+    let entry = cursors.get(&cursor_id).await.ok_or_else(not_open)?;
+    let mut guard = entry.lock().await;
+    let cursor = guard.as_mut().ok_or_else(not_open)?;
+
+    let fetch = format!("FETCH FORWARD {} FROM {}", cursor.batch_size, CURSOR_NAME);
+    let fetched = sqlx::query(&fetch).fetch_all(&mut *cursor.conn).await;
+    let rows = match fetched {
+        Ok(rows) => rows,
+        Err(e) => {
+            // The transaction is aborted, so the cursor cannot be read any further
+            drop(guard);
+            if let Some(cursor) = cursors.remove(&cursor_id).await {
+                cursor.close().await;
+            }
+            return Err(format!("Failed to fetch from cursor: {}", e));
+        }
+    };
+
+    let done = rows.len() < cursor.batch_size;
+    let (columns, column_types) = rows.first().map(column_metadata).unwrap_or_default();
+    let rows = rows
+        .iter()
+        .map(|row| (0..columns.len()).map(|i| postgres_value_to_json(row, i)).collect())
+        .collect();
+    if done {
+        drop(guard);
+        if let Some(cursor) = cursors.remove(&cursor_id).await {
+            cursor.close().await;
+        }
+    }
+    Ok(CursorBatch { columns, column_types, rows, done })
+}
+
+/// Close a cursor before it is read to the end. Returns false when it was
+/// not open.
+#[tauri::command]
+pub async fn close_cursor(
+    cursor_id: String,
+    cursors: State<'_, OpenCursors>,
+) -> Result<bool, String> {
+    match cursors.remove(&cursor_id).await {
+        Some(cursor) => {
+            cursor.close().await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,1085 @@
+use std::time::Instant;
+use sqlx::{Executor, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use crate::db::column_source::{tokenize_spans, Token};
+use crate::db::connections::{describe_table, load_connections, resolve_schema, ColumnInfo, Connection, ConnectionConfig};
+use crate::db::quote::{quote_ident, quote_literal, quote_string};
+use crate::db::execute::{execute_statement, run_query_with_limit};
+use crate::db::history;
+use crate::db::pool_manager::{DatabasePool, PoolManager};
+use crate::db::query_cache::QueryCache;
+use crate::error::AppError;
+
+/// Prefix of the work tables the app creates, e.g. while rebuilding a SQLite
+/// table; `cleanup_temp_objects` only drops tables with this prefix
+pub(crate) const TEMP_TABLE_PREFIX: &str = "__feathersql_";
+
+/// Record DDL in the SQL history
+async fn record_ddl(
+    connection: &Connection,
+    sql: String,
+    result: Result<Option<u64>, &String>,
+    elapsed_ms: u64,
+    app: &tauri::AppHandle,
+) {
+    if let Err(e) = history::add_sql_history(
+        connection.id.clone(),
+        connection.name.clone(),
+        sql,
+        result.is_ok(),
+        result.err().cloned(),
+        result.ok().flatten(),
+        Some(elapsed_ms),
+        app.clone(),
+    ).await {
+        eprintln!("Failed to save SQL history: {}", e);
+    }
+}
+
+/// Run a DDL statement and record it in the SQL history. Cached query
+/// results of the connection are dropped, as they may no longer hold.
+async fn run_ddl(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: String,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+) -> Result<u64, String> {
+    let started = Instant::now();
+    let result = execute_statement(connection, database, &sql, pool_manager).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    app.state::<QueryCache>().invalidate(&connection.id).await;
+    record_ddl(connection, sql, result.as_ref().map(|n| Some(*n)), elapsed_ms, app).await;
+    result
+}
+
+/// Refuse changes on a connection opened read-only
+pub(crate) fn ensure_writable(connection: &Connection) -> Result<(), String> {
+    match &connection.config {
+        ConnectionConfig::Sqlite { read_only: true, .. } => Err(AppError::ReadOnly(connection.name.clone()).into()),
+        _ => Ok(()),
+    }
+}
+
+/// INFORMATION_SCHEMA reports MSSQL string and binary types without a length,
+/// which would mean a length of 1; widen them to the MAX variants instead
+pub(crate) fn mssql_column_type(data_type: &str) -> String {
+    match data_type.to_lowercase().as_str() {
+        "char" | "varchar" => "varchar(max)".to_string(),
+        "nchar" | "nvarchar" => "nvarchar(max)".to_string(),
+        "binary" | "varbinary" => "varbinary(max)".to_string(),
+        _ => data_type.to_string(),
+    }
+}
+
+/// CREATE TABLE statement for an existing table, without a trailing semicolon.
+///
+/// SQLite and MySQL return the server's own definition. Postgres and MSSQL
+/// rebuild it from the column list, so only columns, defaults, identity and
+/// the primary key are covered.
+pub(crate) async fn table_ddl(
+    connection: &Connection,
+    database: Option<&str>,
+    table_name: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<String, String> {
+    let db_type = connection.db_type.as_str();
+    let stored_sql = match db_type {
+        "sqlite" => Some((
+            format!(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = {}",
+                quote_literal(db_type, &serde_json::Value::String(table_name.to_string()))
+            ),
+            0,
+        )),
+        "mysql" => Some((format!("SHOW CREATE TABLE {}", quote_ident(db_type, table_name)), 1)),
+        _ => None,
+    };
+    if let Some((sql, col)) = stored_sql {
+        let result = run_query_with_limit(connection, database, &sql, app, pool_manager, 1).await?;
+        return result
+            .rows
+            .first()
+            .and_then(|row| row.get(col))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| format!("Table not found: {}", table_name));
+    }
+
+    let columns = describe_table(
+        connection.id.clone(),
+        table_name.to_string(),
+        database.map(|s| s.to_string()),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+    ).await?;
+    if columns.is_empty() {
+        return Err(format!("Table not found: {}", table_name));
+    }
+    Ok(create_table_sql(db_type, table_name, &columns))
+}
+
+/// Definition of one column (name, type, identity or default, NOT NULL) in
+/// the syntax of `db_type`, as used in CREATE TABLE and ADD COLUMN
+pub(crate) fn column_definition_sql(db_type: &str, column: &ColumnInfo) -> String {
+    let data_type = if db_type == "mssql" { mssql_column_type(&column.data_type) } else { column.data_type.clone() };
+    let mut definition = format!("{} {}", quote_ident(db_type, &column.name), data_type);
+    if column.auto_increment {
+        // The identity replaces the sequence default (nextval(...) on Postgres)
+        definition.push_str(match db_type {
+            "mssql" => " IDENTITY(1,1)",
+            "mysql" => " AUTO_INCREMENT",
+            // An INTEGER PRIMARY KEY already numbers new rows
+            "sqlite" => "",
+            _ => " GENERATED BY DEFAULT AS IDENTITY",
+        });
+    } else if let Some(default) = &column.default {
+        definition.push_str(&format!(" DEFAULT {}", default));
+    }
+    if !column.nullable {
+        definition.push_str(" NOT NULL");
+    }
+    definition
+}
+
+/// CREATE TABLE statement built from a column list: columns, defaults,
+/// identity and the primary key, in the syntax of `db_type`
+pub(crate) fn create_table_sql(db_type: &str, table_name: &str, columns: &[ColumnInfo]) -> String {
+    let mut lines: Vec<String> = columns
+        .iter()
+        .map(|c| format!("  {}", column_definition_sql(db_type, c)))
+        .collect();
+
+    let primary_key: Vec<String> = columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| quote_ident(db_type, &c.name))
+        .collect();
+    if !primary_key.is_empty() {
+        lines.push(format!("  PRIMARY KEY ({})", primary_key.join(", ")));
+    }
+
+    format!(
+        "CREATE TABLE {} (\n{}\n)",
+        quote_ident(db_type, table_name),
+        lines.join(",\n")
+    )
+}
+
+#[tauri::command]
+pub async fn rename_table(
+    connection_id: String,
+    table_name: String,
+    new_name: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    if new_name.trim().is_empty() {
+        return Err("New table name is required".to_string());
+    }
+
+    let db_type = connection.db_type.as_str();
+    let sql = match db_type {
+        "mssql" => {
+            // sp_rename takes the qualified old name and the bare new name
+            let old_name = format!(
+                "{}.{}",
+                quote_ident(db_type, &resolve_schema(&connection.config, None)),
+                quote_ident(db_type, &table_name)
+            );
+            format!(
+                "EXEC sp_rename {}, {}",
+                quote_string(db_type, &old_name),
+                quote_string(db_type, &new_name)
+            )
+        }
+        "mysql" => format!(
+            "RENAME TABLE {} TO {}",
+            quote_ident(db_type, &table_name),
+            quote_ident(db_type, &new_name)
+        ),
+        _ => format!(
+            "ALTER TABLE {} RENAME TO {}",
+            quote_ident(db_type, &table_name),
+            quote_ident(db_type, &new_name)
+        ),
+    };
+
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+/// Remove all rows from a table. SQLite has no TRUNCATE, so it deletes the rows
+/// and resets the AUTOINCREMENT counter instead.
+#[tauri::command]
+pub async fn truncate_table(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let db_type = connection.db_type.as_str();
+    let table = quote_ident(db_type, &table_name);
+    if db_type != "sqlite" {
+        return run_ddl(connection, database.as_deref(), format!("TRUNCATE TABLE {}", table), &app, &pool_manager).await;
+    }
+
+    let deleted = run_ddl(connection, database.as_deref(), format!("DELETE FROM {}", table), &app, &pool_manager).await?;
+
+    // sqlite_sequence only exists once a table with AUTOINCREMENT has been created
+    let has_sequence = execute_statement(
+        connection,
+        database.as_deref(),
+        "SELECT 1 FROM sqlite_sequence LIMIT 0",
+        &pool_manager,
+    ).await.is_ok();
+    if has_sequence {
+        let reset_sql = format!(
+            "DELETE FROM sqlite_sequence WHERE name = {}",
+            quote_string(db_type, &table_name)
+        );
+        run_ddl(connection, database.as_deref(), reset_sql, &app, &pool_manager).await?;
+    }
+
+    Ok(deleted)
+}
+
+/// Create an empty table with the same columns as an existing one
+#[tauri::command]
+pub async fn duplicate_table_structure(
+    connection_id: String,
+    table_name: String,
+    new_name: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    if new_name.trim().is_empty() {
+        return Err("New table name is required".to_string());
+    }
+
+    let db_type = connection.db_type.as_str();
+    let source = quote_ident(db_type, &table_name);
+    let target = quote_ident(db_type, &new_name);
+    let sql = match db_type {
+        // LIKE keeps indexes and defaults as well as the columns
+        "mysql" => format!("CREATE TABLE {} LIKE {}", target, source),
+        "postgres" => format!("CREATE TABLE {} (LIKE {} INCLUDING ALL)", target, source),
+        "mssql" => format!("SELECT * INTO {} FROM {} WHERE 1 = 0", target, source),
+        _ => format!("CREATE TABLE {} AS SELECT * FROM {} WHERE 1 = 0", target, source),
+    };
+
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+/// Drop a table. `confirm_token` must repeat the table name, so a stray click
+/// cannot drop anything.
+#[tauri::command]
+pub async fn drop_table(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    confirm_token: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    if confirm_token != table_name {
+        return Err(AppError::ConfirmationMismatch(table_name).into());
+    }
+
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let sql = format!("DROP TABLE IF EXISTS {}", quote_ident(&connection.db_type, &table_name));
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+/// Drop an index. `confirm_token` must repeat the index name. MySQL and MSSQL
+/// scope index names to a table, so `table_name` is required there.
+#[tauri::command]
+pub async fn drop_index(
+    connection_id: String,
+    index_name: String,
+    table_name: Option<String>,
+    database: Option<String>,
+    confirm_token: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    if confirm_token != index_name {
+        return Err(AppError::ConfirmationMismatch(index_name).into());
+    }
+
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let db_type = connection.db_type.as_str();
+    let index = quote_ident(db_type, &index_name);
+    let sql = match db_type {
+        "mysql" | "mssql" => {
+            let table = table_name
+                .as_deref()
+                .ok_or_else(|| "Table name is required to drop an index".to_string())?;
+            // MySQL has no IF EXISTS for DROP INDEX
+            let if_exists = if db_type == "mssql" { "IF EXISTS " } else { "" };
+            format!("DROP INDEX {}{} ON {}", if_exists, index, quote_ident(db_type, table))
+        }
+        _ => format!("DROP INDEX IF EXISTS {}", index),
+    };
+
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+/// Find a column of a table, for statements that must restate its definition
+async fn find_column(
+    connection: &Connection,
+    table_name: &str,
+    column_name: &str,
+    database: Option<&str>,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<ColumnInfo, String> {
+    describe_table(
+        connection.id.clone(),
+        table_name.to_string(),
+        database.map(|s| s.to_string()),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+    )
+    .await?
+    .into_iter()
+    .find(|c| c.name == column_name)
+    .ok_or_else(|| format!("Column not found: {}", column_name))
+}
+
+/// A MySQL default as SHOW COLUMNS reports it (unquoted), as SQL
+fn mysql_default_sql(default: &str) -> String {
+    let upper = default.to_uppercase();
+    if default.parse::<f64>().is_ok() || upper == "NULL" || upper.starts_with("CURRENT_TIMESTAMP") {
+        default.to_string()
+    } else {
+        quote_string("mysql", default)
+    }
+}
+
+/// The schema-qualified table name sp_rename expects for MSSQL
+fn mssql_object_name(connection: &Connection, table_name: &str) -> String {
+    format!(
+        "{}.{}",
+        quote_ident("mssql", &resolve_schema(&connection.config, None)),
+        quote_ident("mssql", table_name)
+    )
+}
+
+/// How a SQLite table rebuild changes one column
+enum SqliteColumnEdit<'a> {
+    Drop,
+    /// New type, and the expression to convert existing values with
+    Retype { data_type: &'a str, using: Option<&'a str> },
+}
+
+/// Split at commas outside parentheses and quotes
+fn split_definitions(body: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' | '`' => quote = Some(c),
+                '[' => quote = Some(']'),
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    parts.push(&body[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            },
+        }
+    }
+    parts.push(&body[start..]);
+    parts
+}
+
+/// The leading name of a column definition and the text after it
+fn split_leading_name(definition: &str) -> (&str, &str) {
+    let close = match definition.chars().next() {
+        Some('"') => Some('"'),
+        Some('`') => Some('`'),
+        Some('[') => Some(']'),
+        _ => None,
+    };
+    let end = match close {
+        Some(close) => definition[1..].find(close).map(|i| i + 2).unwrap_or(definition.len()),
+        None => definition.find(char::is_whitespace).unwrap_or(definition.len()),
+    };
+    definition.split_at(end)
+}
+
+fn unquote_name(name: &str) -> &str {
+    name.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+}
+
+// Words that end the type in a SQLite column definition
+const SQLITE_CONSTRAINT_KEYWORDS: &[&str] = &[
+    "CONSTRAINT", "PRIMARY", "NOT", "NULL", "UNIQUE", "CHECK", "DEFAULT", "COLLATE", "REFERENCES", "GENERATED", "AS",
+];
+
+/// Byte offset where the column constraints start, after the type
+fn type_end(after_name: &str) -> usize {
+    let mut depth = 0usize;
+    let mut previous = ' ';
+    for (i, c) in after_name.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && c.is_alphabetic() && !(previous.is_alphanumeric() || previous == '_') => {
+                let word: String = after_name[i..].chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                if SQLITE_CONSTRAINT_KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k)) {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    after_name.len()
+}
+
+/// The CREATE TABLE statement of the rebuilt table: `create_sql` renamed to
+/// `new_table`, with one column dropped or retyped and all else kept
+fn rebuilt_create_sql(create_sql: &str, new_table: &str, column_name: &str, edit: &SqliteColumnEdit) -> Result<String, String> {
+    let (Some(open), Some(close)) = (create_sql.find('('), create_sql.rfind(')')) else {
+        return Err("Could not parse the table definition".to_string());
+    };
+
+    let mut found = false;
+    let mut definitions = Vec::new();
+    for definition in split_definitions(&create_sql[open + 1..close]) {
+        let definition = definition.trim();
+        let (name, rest) = split_leading_name(definition);
+        if found || !unquote_name(name).eq_ignore_ascii_case(column_name) {
+            definitions.push(definition.to_string());
+            continue;
+        }
+        found = true;
+        if let SqliteColumnEdit::Retype { data_type, .. } = edit {
+            let constraints = rest[type_end(rest)..].trim();
+            definitions.push(format!("{} {} {}", name, data_type, constraints).trim_end().to_string());
+        }
+    }
+    if !found {
+        return Err(format!("Column not found: {}", column_name));
+    }
+
+    Ok(format!(
+        "CREATE TABLE {} (\n  {}\n){}",
+        new_table,
+        definitions.join(",\n  "),
+        &create_sql[close + 1..]
+    ))
+}
+
+async fn run_statements(conn: &mut sqlx::SqliteConnection, statements: &[String]) -> Result<(), String> {
+    conn.execute("BEGIN").await.map_err(|e| format!("SQL execution failed: {}", e))?;
+    for sql in statements {
+        conn.execute(sql.as_str()).await.map_err(|e| format!("SQL execution failed: {}", e))?;
+    }
+    let violations = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("SQL execution failed: {}", e))?;
+    if !violations.is_empty() {
+        return Err(format!("The rebuilt table breaks {} foreign key reference(s)", violations.len()));
+    }
+    conn.execute("COMMIT").await.map_err(|e| format!("SQL execution failed: {}", e))?;
+    Ok(())
+}
+
+/// Whether an index or trigger definition mentions `column`, apart from its
+/// own name
+fn references_column(sql: &str, object_name: &str, column: &str) -> bool {
+    tokenize_spans("sqlite", sql).into_iter().any(|(token, _)| match token {
+        Token::Word(word) | Token::Quoted(word) => {
+            word.eq_ignore_ascii_case(column) && !word.eq_ignore_ascii_case(object_name)
+        }
+        _ => false,
+    })
+}
+
+/// DROP COLUMN errors a table rebuild gets around: the statement missing
+/// before SQLite 3.35, key columns, and columns an index or trigger uses
+fn sqlite_drop_refused(message: &str) -> bool {
+    message.contains("near \"DROP\": syntax error")
+        || message.contains("cannot drop PRIMARY KEY column")
+        || message.contains("cannot drop UNIQUE column")
+        || message.contains("error in index ")
+        || message.contains("error in trigger ")
+}
+
+/// Change a column SQLite cannot alter in place by rebuilding the table, as
+/// described in https://www.sqlite.org/lang_altertable.html: create the new
+/// table, copy the rows, drop the old one, rename, then recreate its indexes
+/// and triggers, all in one transaction with foreign keys off. Indexes and
+/// triggers that use a dropped column go with it; their names are returned.
+async fn rebuild_sqlite_table(
+    connection: &Connection,
+    table_name: &str,
+    column_name: &str,
+    edit: SqliteColumnEdit<'_>,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+) -> Result<Vec<String>, String> {
+    let ConnectionConfig::Sqlite { filepath, .. } = &connection.config else {
+        return Err("Invalid SQLite config".to_string());
+    };
+    let _write_guard = pool_manager.sqlite_write_lock(filepath).lock_owned().await;
+    let DatabasePool::Sqlite(pool) = pool_manager.get_or_create_pool(connection, None).await? else {
+        return Err("Invalid SQLite config".to_string());
+    };
+    let mut conn = pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+    let create_sql: String = sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(table_name)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to read table definition: {}", e))?
+        .ok_or_else(|| format!("Table not found: {}", table_name))?;
+    let columns: Vec<String> = sqlx::query(&format!("PRAGMA table_info({})", quote_ident("sqlite", table_name)))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to read table columns: {}", e))?
+        .iter()
+        .map(|row| row.get::<String, _>(1))
+        .collect();
+    // Indexes and triggers go with the old table; sql is NULL for automatic indexes
+    let companions: Vec<(String, String)> = sqlx::query_as(
+        "SELECT name, sql FROM sqlite_master WHERE tbl_name = ? AND type IN ('index', 'trigger') AND sql IS NOT NULL",
+    )
+    .bind(table_name)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to read indexes: {}", e))?;
+    let (dropped, companions): (Vec<_>, Vec<_>) = companions
+        .into_iter()
+        .partition(|(name, sql)| matches!(edit, SqliteColumnEdit::Drop) && references_column(sql, name, column_name));
+    let dropped: Vec<String> = dropped.into_iter().map(|(name, _)| name).collect();
+
+    let table = quote_ident("sqlite", table_name);
+    let new_table = quote_ident("sqlite", &format!("{}rebuild_{}", TEMP_TABLE_PREFIX, table_name));
+    let mut targets = Vec::new();
+    let mut values = Vec::new();
+    for column in &columns {
+        let quoted = quote_ident("sqlite", column);
+        if column.eq_ignore_ascii_case(column_name) {
+            match &edit {
+                SqliteColumnEdit::Drop => continue,
+                SqliteColumnEdit::Retype { using: Some(using), .. } => values.push(using.to_string()),
+                SqliteColumnEdit::Retype { using: None, .. } => values.push(quoted.clone()),
+            }
+        } else {
+            values.push(quoted.clone());
+        }
+        targets.push(quoted);
+    }
+
+    let mut statements = vec![
+        rebuilt_create_sql(&create_sql, &new_table, column_name, &edit)?,
+        format!("INSERT INTO {} ({}) SELECT {} FROM {}", new_table, targets.join(", "), values.join(", "), table),
+        format!("DROP TABLE {}", table),
+        format!("ALTER TABLE {} RENAME TO {}", new_table, table),
+    ];
+    statements.extend(companions.into_iter().map(|(_, sql)| sql));
+
+    let started = Instant::now();
+    // foreign_keys cannot change inside a transaction, so it is switched around it
+    let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap_or(0);
+    if foreign_keys != 0 {
+        conn.execute("PRAGMA foreign_keys = OFF").await.map_err(|e| format!("SQL execution failed: {}", e))?;
+    }
+    let result = run_statements(&mut conn, &statements).await;
+    if result.is_err() {
+        let _ = conn.execute("ROLLBACK").await;
+    }
+    if foreign_keys != 0 {
+        let _ = conn.execute("PRAGMA foreign_keys = ON").await;
+    }
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    app.state::<QueryCache>().invalidate(&connection.id).await;
+    record_ddl(connection, statements.join(";\n"), result.as_ref().map(|_| None), elapsed_ms, app).await;
+    result.map(|_| dropped)
+}
+
+/// Add a column to a table. `default` is a SQL expression, e.g. `0` or `'n/a'`.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn add_column(
+    connection_id: String,
+    table_name: String,
+    column_name: String,
+    data_type: String,
+    nullable: Option<bool>,
+    default: Option<String>,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_writable(connection)?;
+
+    if column_name.trim().is_empty() || data_type.trim().is_empty() {
+        return Err("Column name and type are required".to_string());
+    }
+
+    let db_type = connection.db_type.as_str();
+    let mut definition = format!("{} {}", quote_ident(db_type, &column_name), data_type.trim());
+    if let Some(default) = default.as_deref().filter(|d| !d.trim().is_empty()) {
+        definition.push_str(&format!(" DEFAULT {}", default.trim()));
+    }
+    if !nullable.unwrap_or(true) {
+        definition.push_str(" NOT NULL");
+    }
+    // MSSQL has no COLUMN keyword in ADD
+    let add = if db_type == "mssql" { "ADD" } else { "ADD COLUMN" };
+    let sql = format!("ALTER TABLE {} {} {}", quote_ident(db_type, &table_name), add, definition);
+
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+/// Drop a column. `confirm_token` must repeat the column name. SQLite falls
+/// back to rebuilding the table when DROP COLUMN is refused (before 3.35, or
+/// for a key column or one an index or trigger uses). Returns the indexes and
+/// triggers the rebuild dropped along with the column.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn drop_column(
+    connection_id: String,
+    table_name: String,
+    column_name: String,
+    database: Option<String>,
+    confirm_token: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<String>, String> {
+    if confirm_token != column_name {
+        return Err(AppError::ConfirmationMismatch(column_name).into());
+    }
+
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_writable(connection)?;
+
+    let db_type = connection.db_type.as_str();
+    let sql = format!(
+        "ALTER TABLE {} DROP COLUMN {}",
+        quote_ident(db_type, &table_name),
+        quote_ident(db_type, &column_name)
+    );
+    let dropped = run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await;
+    match dropped {
+        Err(e) if db_type == "sqlite" && sqlite_drop_refused(&e) => {
+            rebuild_sqlite_table(connection, &table_name, &column_name, SqliteColumnEdit::Drop, &app, &pool_manager).await
+        }
+        result => result.map(|_| Vec::new()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn rename_column(
+    connection_id: String,
+    table_name: String,
+    column_name: String,
+    new_name: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_writable(connection)?;
+
+    if new_name.trim().is_empty() {
+        return Err("New column name is required".to_string());
+    }
+
+    let db_type = connection.db_type.as_str();
+    let table = quote_ident(db_type, &table_name);
+    let sql = match db_type {
+        "mssql" => {
+            let old_name = format!("{}.{}", mssql_object_name(connection, &table_name), quote_ident(db_type, &column_name));
+            format!(
+                "EXEC sp_rename {}, {}, 'COLUMN'",
+                quote_string(db_type, &old_name),
+                quote_string(db_type, &new_name)
+            )
+        }
+        // CHANGE restates the definition, so it also works before MySQL 8's RENAME COLUMN
+        "mysql" => {
+            let column = find_column(connection, &table_name, &column_name, database.as_deref(), &app, &pool_manager).await?;
+            format!(
+                "ALTER TABLE {} CHANGE {} {} {}",
+                table,
+                quote_ident(db_type, &column_name),
+                quote_ident(db_type, &new_name),
+                mysql_column_definition(&column, &column.data_type)
+            )
+        }
+        _ => format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {}",
+            table,
+            quote_ident(db_type, &column_name),
+            quote_ident(db_type, &new_name)
+        ),
+    };
+
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+/// Type, nullability, default and AUTO_INCREMENT of a MySQL column, which
+/// CHANGE and MODIFY reset unless they are restated
+pub(crate) fn mysql_column_definition(column: &ColumnInfo, data_type: &str) -> String {
+    let mut definition = data_type.to_string();
+    definition.push_str(if column.nullable { " NULL" } else { " NOT NULL" });
+    if let Some(default) = &column.default {
+        definition.push_str(&format!(" DEFAULT {}", mysql_default_sql(default)));
+    }
+    if column.auto_increment {
+        definition.push_str(" AUTO_INCREMENT");
+    }
+    definition
+}
+
+/// Change the type of a column. `using` converts existing values on
+/// PostgreSQL (`USING expr`) and SQLite; SQLite rebuilds the table, since it
+/// cannot alter a column type in place.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn change_column_type(
+    connection_id: String,
+    table_name: String,
+    column_name: String,
+    new_type: String,
+    using: Option<String>,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_writable(connection)?;
+
+    let new_type = new_type.trim();
+    if new_type.is_empty() {
+        return Err("New column type is required".to_string());
+    }
+    let using = using.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    let db_type = connection.db_type.as_str();
+    if using.is_some() && !matches!(db_type, "postgres" | "sqlite") {
+        return Err("A USING expression is only supported on PostgreSQL and SQLite".to_string());
+    }
+
+    let table = quote_ident(db_type, &table_name);
+    let column = quote_ident(db_type, &column_name);
+    let sql = match db_type {
+        "sqlite" => {
+            let edit = SqliteColumnEdit::Retype { data_type: new_type, using };
+            return rebuild_sqlite_table(connection, &table_name, &column_name, edit, &app, &pool_manager)
+                .await
+                .map(|_| ());
+        }
+        "mysql" => {
+            let info = find_column(connection, &table_name, &column_name, database.as_deref(), &app, &pool_manager).await?;
+            format!("ALTER TABLE {} MODIFY {} {}", table, column, mysql_column_definition(&info, new_type))
+        }
+        "mssql" => {
+            // ALTER COLUMN makes the column nullable unless NOT NULL is restated
+            let info = find_column(connection, &table_name, &column_name, database.as_deref(), &app, &pool_manager).await?;
+            let null = if info.nullable { "NULL" } else { "NOT NULL" };
+            format!("ALTER TABLE {} ALTER COLUMN {} {} {}", table, column, new_type, null)
+        }
+        _ => {
+            let using = using.map(|u| format!(" USING {}", u)).unwrap_or_default();
+            format!("ALTER TABLE {} ALTER COLUMN {} TYPE {}{}", table, column, new_type, using)
+        }
+    };
+
+    run_ddl(connection, database.as_deref(), sql, &app, &pool_manager).await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectType {
+    View,
+    Procedure,
+    Function,
+}
+
+impl ObjectType {
+    fn label(self) -> &'static str {
+        match self {
+            ObjectType::View => "View",
+            ObjectType::Procedure => "Procedure",
+            ObjectType::Function => "Function",
+        }
+    }
+}
+
+/// Source of a view, procedure or function as a CREATE statement, for
+/// display and editing. Postgres only stores the query of a view, so the
+/// CREATE VIEW header is added; overloaded functions are all returned.
+#[tauri::command]
+pub async fn get_object_definition(
+    connection_id: String,
+    object_name: String,
+    object_type: ObjectType,
+    database: Option<String>,
+    schema: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<String, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let db_type = connection.db_type.as_str();
+    let name = quote_string(db_type, &object_name);
+    let schema = resolve_schema(&connection.config, schema.as_deref());
+    let not_found = || String::from(AppError::NotFound(format!("{} {}", object_type.label(), object_name)));
+
+    // Query for the definition and the column that holds it
+    let (sql, col) = match (db_type, object_type) {
+        ("sqlite", ObjectType::View) => (
+            format!("SELECT sql FROM sqlite_master WHERE type = 'view' AND name = {}", name),
+            0,
+        ),
+        ("sqlite", _) => return Err("SQLite has no stored procedures or functions".to_string()),
+        ("mysql", _) => {
+            let keyword = object_type.label().to_uppercase();
+            // SHOW CREATE VIEW puts the definition in the second column, the others in the third
+            let col = if matches!(object_type, ObjectType::View) { 1 } else { 2 };
+            (format!("SHOW CREATE {} {}", keyword, quote_ident(db_type, &object_name)), col)
+        }
+        ("mssql", _) => {
+            let types = match object_type {
+                ObjectType::View => "'V'",
+                ObjectType::Procedure => "'P'",
+                ObjectType::Function => "'FN', 'IF', 'TF'",
+            };
+            let qualified = format!("{}.{}", quote_ident(db_type, &schema), quote_ident(db_type, &object_name));
+            (
+                format!(
+                    "SELECT OBJECT_DEFINITION(o.object_id) FROM sys.objects o \
+                     WHERE o.object_id = OBJECT_ID({}) AND o.type IN ({})",
+                    quote_string(db_type, &qualified),
+                    types
+                ),
+                0,
+            )
+        }
+        (_, ObjectType::View) => (
+            format!(
+                "SELECT 'CREATE OR REPLACE ' || CASE c.relkind WHEN 'm' THEN 'MATERIALIZED VIEW ' ELSE 'VIEW ' END \
+                 || quote_ident(n.nspname) || '.' || quote_ident(c.relname) || ' AS' || E'\\n' || pg_get_viewdef(c.oid, true) \
+                 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE c.relkind IN ('v', 'm') AND c.relname = {} AND n.nspname = {}",
+                name,
+                quote_string(db_type, &schema)
+            ),
+            0,
+        ),
+        (_, _) => {
+            let kind = if matches!(object_type, ObjectType::Procedure) { "p" } else { "f" };
+            (
+                format!(
+                    "SELECT pg_get_functiondef(p.oid) FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace \
+                     WHERE p.prokind = '{}' AND p.proname = {} AND n.nspname = {} ORDER BY p.oid",
+                    kind,
+                    name,
+                    quote_string(db_type, &schema)
+                ),
+                0,
+            )
+        }
+    };
+
+    let result = match run_query_with_limit(connection, database.as_deref(), &sql, &app, &pool_manager, usize::MAX).await {
+        Ok(result) => result,
+        // SHOW CREATE fails instead of returning no rows
+        Err(e) if db_type == "mysql" && (e.contains("doesn't exist") || e.contains("does not exist")) => {
+            return Err(not_found());
+        }
+        Err(e) => return Err(e),
+    };
+    if result.rows.is_empty() {
+        return Err(not_found());
+    }
+    let definitions: Vec<&str> = result
+        .rows
+        .iter()
+        .filter_map(|row| row.get(col).and_then(|v| v.as_str()))
+        .collect();
+    if definitions.is_empty() {
+        // MSSQL hides encrypted modules, MySQL hides routine bodies without privileges
+        return Err(format!("The definition of {} is not available to this user", object_name));
+    }
+    Ok(definitions.join(";\n\n"))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TempObject {
+    /// None on SQLite and MySQL, where tables are listed in the current database only
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+/// Work tables with `prefix` in the database, left behind by interrupted operations
+async fn find_temp_objects(
+    connection: &Connection,
+    database: Option<&str>,
+    prefix: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+) -> Result<Vec<TempObject>, String> {
+    let (sql, has_schema) = match connection.db_type.as_str() {
+        "sqlite" => ("SELECT name FROM sqlite_master WHERE type = 'table'", false),
+        "mysql" => (
+            "SELECT TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE'",
+            false,
+        ),
+        "mssql" => ("SELECT SCHEMA_NAME(schema_id), name FROM sys.tables", true),
+        _ => (
+            "SELECT schemaname, tablename FROM pg_catalog.pg_tables \
+             WHERE schemaname NOT IN ('pg_catalog', 'information_schema')",
+            true,
+        ),
+    };
+    let result = run_query_with_limit(connection, database, sql, app, pool_manager, usize::MAX).await?;
+    let text = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).map(|s| s.to_string());
+    let mut objects: Vec<TempObject> = result
+        .rows
+        .iter()
+        .filter_map(|row| {
+            let (schema, name) = if has_schema {
+                (text(row.first()), text(row.get(1))?)
+            } else {
+                (None, text(row.first())?)
+            };
+            name.starts_with(prefix).then_some(TempObject { schema, name })
+        })
+        .collect();
+    objects.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+    Ok(objects)
+}
+
+/// List work tables the app left behind (names starting with `__feathersql_`)
+#[tauri::command]
+pub async fn list_temp_objects(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<TempObject>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    find_temp_objects(connection, database.as_deref(), TEMP_TABLE_PREFIX, &app, &pool_manager).await
+}
+
+/// Drop the work tables the app left behind and return the ones dropped.
+/// `prefix` narrows the match but must start with `__feathersql_`, so no
+/// other table can be dropped; nothing happens unless `confirm` is set.
+#[tauri::command]
+pub async fn cleanup_temp_objects(
+    connection_id: String,
+    database: Option<String>,
+    prefix: Option<String>,
+    confirm: bool,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<TempObject>, String> {
+    if !confirm {
+        return Err("Confirm the cleanup to drop temporary tables".to_string());
+    }
+    let prefix = prefix.unwrap_or_else(|| TEMP_TABLE_PREFIX.to_string());
+    if !prefix.starts_with(TEMP_TABLE_PREFIX) {
+        return Err(format!("Prefix must start with {}", TEMP_TABLE_PREFIX));
+    }
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_writable(connection)?;
+
+    let db_type = connection.db_type.as_str();
+    let objects = find_temp_objects(connection, database.as_deref(), &prefix, &app, &pool_manager).await?;
+    for object in &objects {
+        let table = match &object.schema {
+            Some(schema) => format!("{}.{}", quote_ident(db_type, schema), quote_ident(db_type, &object.name)),
+            None => quote_ident(db_type, &object.name),
+        };
+        run_ddl(connection, database.as_deref(), format!("DROP TABLE IF EXISTS {}", table), &app, &pool_manager).await?;
+    }
+    Ok(objects)
+}
@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use crate::db::connections::{describe_table, list_tables, load_connections, resolve_schema, ColumnInfo, Connection};
+use crate::db::quote::quote_string;
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::PoolManager;
+use crate::db::session::ActiveDatabases;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKey {
+    pub name: String,
+    /// Referencing columns, in key order
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    /// Referenced columns, matching `columns` position by position
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagramFormat {
+    Mermaid,
+    Dbml,
+}
+
+/// Query returning one row per foreign key column of `table_name`:
+/// constraint name, column, referenced table, referenced column
+fn foreign_key_sql(connection: &Connection, table_name: &str, schema: Option<&str>) -> String {
+    let db_type = connection.db_type.as_str();
+    let text = |s: &str| quote_string(db_type, s);
+    match db_type {
+        "mysql" => format!(
+            "SELECT CONSTRAINT_NAME, COLUMN_NAME, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME \
+             FROM information_schema.KEY_COLUMN_USAGE \
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = {} AND REFERENCED_TABLE_NAME IS NOT NULL \
+             ORDER BY CONSTRAINT_NAME, ORDINAL_POSITION",
+            text(table_name)
+        ),
+        "postgres" => format!(
+            "SELECT c.conname::text, a.attname::text, rt.relname::text, ra.attname::text \
+             FROM pg_constraint c \
+             JOIN pg_class t ON t.oid = c.conrelid \
+             JOIN pg_namespace n ON n.oid = t.relnamespace \
+             JOIN pg_class rt ON rt.oid = c.confrelid \
+             CROSS JOIN LATERAL unnest(c.conkey, c.confkey) WITH ORDINALITY AS k(attnum, refnum, pos) \
+             JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum \
+             JOIN pg_attribute ra ON ra.attrelid = c.confrelid AND ra.attnum = k.refnum \
+             WHERE c.contype = 'f' AND n.nspname = {} AND t.relname = {} \
+             ORDER BY c.conname, k.pos",
+            text(&resolve_schema(&connection.config, schema)),
+            text(table_name)
+        ),
+        "mssql" => format!(
+            "SELECT fk.name, pc.name, rt.name, rc.name \
+             FROM sys.foreign_keys fk \
+             JOIN sys.foreign_key_columns fkc ON fkc.constraint_object_id = fk.object_id \
+             JOIN sys.tables t ON t.object_id = fk.parent_object_id \
+             JOIN sys.columns pc ON pc.object_id = fkc.parent_object_id AND pc.column_id = fkc.parent_column_id \
+             JOIN sys.tables rt ON rt.object_id = fkc.referenced_object_id \
+             JOIN sys.columns rc ON rc.object_id = fkc.referenced_object_id AND rc.column_id = fkc.referenced_column_id \
+             WHERE SCHEMA_NAME(t.schema_id) = {} AND t.name = {} \
+             ORDER BY fk.name, fkc.constraint_column_id",
+            text(&resolve_schema(&connection.config, schema)),
+            text(table_name)
+        ),
+        // "to" is NULL when the key references the parent's primary key implicitly
+        _ => format!(
+            "SELECT 'fk_' || id, \"from\", \"table\", \"to\" FROM pragma_foreign_key_list({}) ORDER BY id, seq",
+            text(table_name)
+        ),
+    }
+}
+
+/// Foreign keys of a table, with composite keys grouped into one entry
+async fn table_foreign_keys(
+    connection: &Connection,
+    database: Option<&str>,
+    schema: Option<&str>,
+    table_name: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<Vec<ForeignKey>, String> {
+    let sql = foreign_key_sql(connection, table_name, schema);
+    let result = run_query_with_limit(connection, database, &sql, app, pool_manager, usize::MAX).await?;
+
+    let text = |v: &serde_json::Value| v.as_str().map(|s| s.to_string()).unwrap_or_default();
+    let mut keys: Vec<ForeignKey> = Vec::new();
+    for row in &result.rows {
+        let [name, column, referenced_table, referenced_column] = match row.as_slice() {
+            [a, b, c, d, ..] => [text(a), text(b), text(c), text(d)],
+            _ => continue,
+        };
+        match keys.last_mut() {
+            Some(key) if key.name == name => {
+                key.columns.push(column);
+                key.referenced_columns.push(referenced_column);
+            }
+            _ => keys.push(ForeignKey {
+                name,
+                columns: vec![column],
+                referenced_table,
+                referenced_columns: vec![referenced_column],
+            }),
+        }
+    }
+
+    // SQLite leaves the referenced columns out when they are the parent's primary key
+    for key in keys.iter_mut().filter(|k| k.referenced_columns.iter().any(|c| c.is_empty())) {
+        let parent = describe_table(
+            connection.id.clone(),
+            key.referenced_table.clone(),
+            database.map(|s| s.to_string()),
+            schema.map(|s| s.to_string()),
+            app.clone(),
+            pool_manager.clone(),
+        ).await?;
+        key.referenced_columns = parent.into_iter().filter(|c| c.primary_key).map(|c| c.name).collect();
+    }
+
+    Ok(keys)
+}
+
+/// Foreign keys declared on a table
+#[tauri::command]
+pub async fn list_foreign_keys(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<ForeignKey>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    table_foreign_keys(connection, database.as_deref(), schema.as_deref(), &table_name, &app, &pool_manager).await
+}
+
+struct DiagramTable {
+    name: String,
+    columns: Vec<ColumnInfo>,
+    foreign_keys: Vec<ForeignKey>,
+}
+
+/// Mermaid only accepts plain words for entity, attribute and type names
+fn mermaid_word(s: &str) -> String {
+    let word: String = s
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if word.is_empty() { "_".to_string() } else { word }
+}
+
+fn render_mermaid(tables: &[DiagramTable]) -> String {
+    let mut out = String::from("erDiagram\n");
+    for table in tables {
+        let fk_columns: HashSet<&str> = table
+            .foreign_keys
+            .iter()
+            .flat_map(|k| k.columns.iter().map(|c| c.as_str()))
+            .collect();
+        out.push_str(&format!("    {} {{\n", mermaid_word(&table.name)));
+        for column in &table.columns {
+            let keys: Vec<&str> = [
+                Some("PK").filter(|_| column.primary_key),
+                Some("FK").filter(|_| fk_columns.contains(column.name.as_str())),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            let mut line = format!("        {} {}", mermaid_word(&column.data_type), mermaid_word(&column.name));
+            if !keys.is_empty() {
+                line.push(' ');
+                line.push_str(&keys.join(", "));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out.push_str("    }\n");
+    }
+    for table in tables {
+        for key in &table.foreign_keys {
+            out.push_str(&format!(
+                "    {} }}o--|| {} : \"{}\"\n",
+                mermaid_word(&table.name),
+                mermaid_word(&key.referenced_table),
+                key.name.replace('"', "'")
+            ));
+        }
+    }
+    out
+}
+
+fn dbml_name(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+/// Column list of a reference: `"col"` or `("a", "b")` for composite keys
+fn dbml_columns(columns: &[String]) -> String {
+    match columns {
+        [single] => dbml_name(single),
+        _ => format!("({})", columns.iter().map(|c| dbml_name(c)).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+fn render_dbml(tables: &[DiagramTable]) -> String {
+    let mut out = String::new();
+    for table in tables {
+        let primary_key: Vec<String> = table.columns.iter().filter(|c| c.primary_key).map(|c| c.name.clone()).collect();
+        out.push_str(&format!("Table {} {{\n", dbml_name(&table.name)));
+        for column in &table.columns {
+            let mut settings = Vec::new();
+            // A composite key goes in the indexes block instead
+            if column.primary_key && primary_key.len() == 1 {
+                settings.push("pk");
+            }
+            if column.auto_increment {
+                settings.push("increment");
+            }
+            if !column.nullable && !column.primary_key {
+                settings.push("not null");
+            }
+            let data_type = if column.data_type.contains(char::is_whitespace) {
+                dbml_name(&column.data_type)
+            } else {
+                column.data_type.clone()
+            };
+            let settings = if settings.is_empty() { String::new() } else { format!(" [{}]", settings.join(", ")) };
+            out.push_str(&format!("  {} {}{}\n", dbml_name(&column.name), data_type, settings));
+        }
+        if primary_key.len() > 1 {
+            out.push_str(&format!("\n  indexes {{\n    {} [pk]\n  }}\n", dbml_columns(&primary_key)));
+        }
+        out.push_str("}\n\n");
+    }
+    for table in tables {
+        for key in &table.foreign_keys {
+            out.push_str(&format!(
+                "Ref: {}.{} > {}.{}\n",
+                dbml_name(&table.name),
+                dbml_columns(&key.columns),
+                dbml_name(&key.referenced_table),
+                dbml_columns(&key.referenced_columns)
+            ));
+        }
+    }
+    out
+}
+
+/// Describe every table of a database with its columns, primary keys and
+/// foreign key relationships, as Mermaid `erDiagram` or DBML text
+#[tauri::command]
+pub async fn export_schema_diagram(
+    connection_id: String,
+    database: Option<String>,
+    format: DiagramFormat,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<String, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    // Resolve here so the table list and the table details come from the same database
+    let active_databases = app.state::<ActiveDatabases>();
+    let database = active_databases.resolve(connection, database).await;
+
+    let table_names = list_tables(
+        connection_id.clone(),
+        database.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases,
+    ).await?;
+
+    let mut tables = Vec::with_capacity(table_names.len());
+    for name in table_names {
+        let columns = describe_table(
+            connection_id.clone(),
+            name.clone(),
+            database.clone(),
+            None,
+            app.clone(),
+            pool_manager.clone(),
+        ).await?;
+        let foreign_keys = table_foreign_keys(connection, database.as_deref(), None, &name, &app, &pool_manager).await?;
+        tables.push(DiagramTable { name, columns, foreign_keys });
+    }
+
+    Ok(match format {
+        DiagramFormat::Mermaid => render_mermaid(&tables),
+        DiagramFormat::Dbml => render_dbml(&tables),
+    })
+}
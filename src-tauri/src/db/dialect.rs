@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+// Keywords shared by every supported dialect
+const COMMON_KEYWORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CHECK", "COLUMN",
+    "CONSTRAINT", "CREATE", "CROSS", "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP", "ELSE",
+    "END", "EXISTS", "FOREIGN", "FROM", "FULL", "GROUP", "HAVING", "IN", "INDEX", "INNER",
+    "INSERT", "INTO", "IS", "JOIN", "KEY", "LEFT", "LIKE", "NOT", "NULL", "ON", "OR", "ORDER",
+    "OUTER", "PRIMARY", "REFERENCES", "RIGHT", "SELECT", "SET", "TABLE", "THEN", "UNION",
+    "UNIQUE", "UPDATE", "VALUES", "VIEW", "WHEN", "WHERE", "WITH",
+];
+
+// Functions shared by every supported dialect
+const COMMON_FUNCTIONS: &[&str] = &[
+    "ABS", "AVG", "CAST", "COALESCE", "COUNT", "LOWER", "MAX", "MIN", "NULLIF", "ROUND",
+    "SUM", "UPPER",
+];
+
+const SQLITE_KEYWORDS: &[&str] = &[
+    "ATTACH", "AUTOINCREMENT", "DETACH", "GLOB", "LIMIT", "OFFSET", "PRAGMA", "REINDEX",
+    "RETURNING", "ROWID", "VACUUM", "WITHOUT",
+];
+
+const SQLITE_FUNCTIONS: &[&str] = &[
+    "changes", "date", "datetime", "group_concat", "ifnull", "instr", "json_extract",
+    "json_object", "julianday", "last_insert_rowid", "length", "printf", "random", "replace",
+    "strftime", "substr", "time", "total", "trim", "typeof",
+];
+
+const MYSQL_KEYWORDS: &[&str] = &[
+    "AUTO_INCREMENT", "CHARSET", "DATABASE", "DATABASES", "DESCRIBE", "DUPLICATE", "ENGINE",
+    "EXPLAIN", "IGNORE", "LIMIT", "LOCK", "OFFSET", "REPLACE", "SHOW", "STRAIGHT_JOIN",
+    "TABLES", "UNLOCK", "UNSIGNED", "USE", "ZEROFILL",
+];
+
+const MYSQL_FUNCTIONS: &[&str] = &[
+    "CONCAT", "CONCAT_WS", "CURDATE", "DATE_ADD", "DATE_FORMAT", "DATE_SUB", "DATEDIFF",
+    "FIND_IN_SET", "FROM_UNIXTIME", "GROUP_CONCAT", "IF", "IFNULL", "JSON_ARRAYAGG",
+    "JSON_EXTRACT", "JSON_OBJECT", "LAST_INSERT_ID", "NOW", "STR_TO_DATE", "SUBSTRING_INDEX",
+    "UNIX_TIMESTAMP",
+];
+
+const POSTGRES_KEYWORDS: &[&str] = &[
+    "ANALYZE", "ARRAY", "CONFLICT", "DO", "EXPLAIN", "ILIKE", "LATERAL", "LIMIT", "NOTHING",
+    "OFFSET", "ONLY", "OVER", "PARTITION", "RETURNING", "SCHEMA", "SEQUENCE", "SERIAL",
+    "SIMILAR", "TRUNCATE", "WINDOW",
+];
+
+const POSTGRES_FUNCTIONS: &[&str] = &[
+    "array_agg", "array_length", "coalesce", "date_trunc", "extract", "generate_series",
+    "json_agg", "json_build_object", "jsonb_agg", "jsonb_build_object", "jsonb_set", "now",
+    "regexp_replace", "row_number", "string_agg", "to_char", "to_date", "to_timestamp",
+    "unnest", "uuid_generate_v4",
+];
+
+const MSSQL_KEYWORDS: &[&str] = &[
+    "APPLY", "BEGIN", "CLUSTERED", "DECLARE", "EXEC", "FETCH", "GO", "IDENTITY", "MERGE",
+    "NOCOUNT", "NOLOCK", "NONCLUSTERED", "OFFSET", "OUTPUT", "PIVOT", "PROCEDURE", "ROWS",
+    "TOP", "TRAN", "UNPIVOT",
+];
+
+const MSSQL_FUNCTIONS: &[&str] = &[
+    "CHARINDEX", "CONVERT", "DATEADD", "DATEDIFF", "DATEPART", "FORMAT", "GETDATE",
+    "GETUTCDATE", "ISNULL", "IIF", "JSON_VALUE", "LEN", "NEWID", "OPENJSON", "ROW_NUMBER",
+    "SCOPE_IDENTITY", "STRING_AGG", "STRING_SPLIT", "STUFF", "TRY_CONVERT",
+];
+
+/// Keywords and functions of a database type, without the common ones
+fn dialect_lists(db_type: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    match db_type {
+        "sqlite" => Some((SQLITE_KEYWORDS, SQLITE_FUNCTIONS)),
+        "mysql" => Some((MYSQL_KEYWORDS, MYSQL_FUNCTIONS)),
+        "postgres" => Some((POSTGRES_KEYWORDS, POSTGRES_FUNCTIONS)),
+        "mssql" => Some((MSSQL_KEYWORDS, MSSQL_FUNCTIONS)),
+        _ => None,
+    }
+}
+
+/// Whether `word` is a reserved word of the database type, in any case.
+/// Unknown types only know the common keywords.
+pub(crate) fn is_sql_keyword(db_type: &str, word: &str) -> bool {
+    let own = dialect_lists(db_type).map_or(&[][..], |(keywords, _)| keywords);
+    COMMON_KEYWORDS.iter().chain(own).any(|k| k.eq_ignore_ascii_case(word))
+}
+
+#[derive(Debug, Serialize)]
+pub struct SqlKeywords {
+    pub keywords: Vec<&'static str>,
+    pub functions: Vec<&'static str>,
+}
+
+/// Reserved words and built-in function names for a database type, used by the
+/// editor for completion and highlighting
+#[tauri::command]
+pub async fn get_sql_keywords(db_type: String) -> Result<SqlKeywords, String> {
+    let (keywords, functions) =
+        dialect_lists(&db_type).ok_or_else(|| format!("Unsupported database type: {}", db_type))?;
+
+    let mut keywords: Vec<&'static str> = COMMON_KEYWORDS.iter().chain(keywords).copied().collect();
+    keywords.sort_unstable();
+    keywords.dedup();
+    let mut functions: Vec<&'static str> = COMMON_FUNCTIONS.iter().chain(functions).copied().collect();
+    functions.sort_unstable_by_key(|f| f.to_lowercase());
+    functions.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    Ok(SqlKeywords { keywords, functions })
+}
@@ -0,0 +1,434 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use futures_util::TryStreamExt;
+use sqlx::mysql::MySqlRow;
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Executor, Row, TypeInfo, ValueRef};
+use tauri::{Manager, State};
+use tiberius::{ColumnData, QueryItem};
+use crate::db::connections::{create_mssql_client, describe_table, list_tables, load_connections, Connection, ConnectionConfig};
+use crate::db::quote::{quote_ident, quote_literal, quote_string};
+use crate::db::ddl::table_ddl;
+use crate::db::execute::mssql_value_to_json;
+use crate::db::pool_manager::{DatabasePool, PoolManager};
+use crate::db::session::ActiveDatabases;
+use crate::error::AppError;
+
+// Rows per INSERT statement; MSSQL rejects more than 1000 in one VALUES list
+const DUMP_ROWS_PER_INSERT: usize = 100;
+
+/// Writes rows as INSERT statements of up to `DUMP_ROWS_PER_INSERT` rows
+/// each, as they are read, so a table never has to fit in memory
+struct InsertWriter<'a> {
+    out: &'a mut BufWriter<File>,
+    table: String,
+    in_statement: usize,
+    written: u64,
+}
+
+impl<'a> InsertWriter<'a> {
+    fn new(out: &'a mut BufWriter<File>, table: String) -> Self {
+        Self { out, table, in_statement: 0, written: 0 }
+    }
+
+    fn push(&mut self, columns: &str, values: &[String]) -> Result<(), String> {
+        if self.in_statement == 0 {
+            writeln!(self.out, "INSERT INTO {} ({}) VALUES", self.table, columns).map_err(AppError::from)?;
+        } else {
+            writeln!(self.out, ",").map_err(AppError::from)?;
+        }
+        write!(self.out, "  ({})", values.join(", ")).map_err(AppError::from)?;
+        self.in_statement += 1;
+        self.written += 1;
+        if self.in_statement == DUMP_ROWS_PER_INSERT {
+            self.finish_statement()?;
+        }
+        Ok(())
+    }
+
+    fn finish_statement(&mut self) -> Result<(), String> {
+        if self.in_statement > 0 {
+            writeln!(self.out, ";").map_err(AppError::from)?;
+            self.in_statement = 0;
+        }
+        Ok(())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// A number as the server printed it, unquoted when SQL can read it back as
+/// one; NaN and infinities stay quoted strings
+fn number_literal(db_type: &str, text: &str) -> String {
+    if text.parse::<f64>().is_ok_and(f64::is_finite) {
+        text.to_string()
+    } else {
+        quote_string(db_type, text)
+    }
+}
+
+/// SQLite values are typed per value rather than per column, so the
+/// storage class of each one decides how it is written
+fn sqlite_literal(row: &SqliteRow, index: usize) -> Result<String, String> {
+    let raw = row.try_get_raw(index).map_err(|e| e.to_string())?;
+    if raw.is_null() {
+        return Ok("NULL".to_string());
+    }
+    let storage_class = raw.type_info().name().to_string();
+    let decode_error = |e: sqlx::Error| e.to_string();
+    Ok(match storage_class.as_str() {
+        "INTEGER" => row.try_get_unchecked::<i64, _>(index).map_err(decode_error)?.to_string(),
+        "REAL" => {
+            let v = row.try_get_unchecked::<f64, _>(index).map_err(decode_error)?;
+            // SQLite reads an out-of-range literal as infinity; it has no NaN
+            if v.is_finite() { format!("{:?}", v) } else if v > 0.0 { "9e999".to_string() } else { "-9e999".to_string() }
+        }
+        "BLOB" => format!("X'{}'", hex(&row.try_get_unchecked::<Vec<u8>, _>(index).map_err(decode_error)?)),
+        _ => quote_string("sqlite", &row.try_get_unchecked::<String, _>(index).map_err(decode_error)?),
+    })
+}
+
+/// MySQL rows are read with the text protocol, so every value arrives as the
+/// server formats it; binary columns are written as hex literals
+fn mysql_literal(row: &MySqlRow, index: usize) -> Result<String, String> {
+    if row.try_get_raw(index).map_err(|e| e.to_string())?.is_null() {
+        return Ok("NULL".to_string());
+    }
+    let type_name = row.column(index).type_info().name();
+    let base_type = type_name.trim_end_matches(" UNSIGNED");
+    if matches!(base_type, "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BINARY" | "VARBINARY" | "BIT" | "GEOMETRY") {
+        let bytes = row.try_get_unchecked::<Vec<u8>, _>(index).map_err(|e| e.to_string())?;
+        return Ok(format!("X'{}'", hex(&bytes)));
+    }
+    let text = row
+        .try_get_unchecked::<String, _>(index)
+        .map_err(|e| format!("Cannot dump column {} ({}): {}", row.column(index).name(), type_name, e))?;
+    Ok(match base_type {
+        "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" | "DECIMAL" | "FLOAT" | "DOUBLE" | "YEAR" | "BOOLEAN" => {
+            number_literal("mysql", &text)
+        }
+        _ => quote_string("mysql", &text),
+    })
+}
+
+/// PostgreSQL rows are read with the simple query protocol, which sends every
+/// value as text. Quoted text is read back as the column's type (bytea `\x..`,
+/// dates, arrays, ...), so only plain numbers are left unquoted.
+fn postgres_literal(row: &PgRow, index: usize) -> Result<String, String> {
+    if row.try_get_raw(index).map_err(|e| e.to_string())?.is_null() {
+        return Ok("NULL".to_string());
+    }
+    let type_name = row.column(index).type_info().name();
+    let text = row
+        .try_get_unchecked::<String, _>(index)
+        .map_err(|e| format!("Cannot dump column {} ({}): {}", row.column(index).name(), type_name, e))?;
+    Ok(match type_name {
+        "INT2" | "INT4" | "INT8" | "OID" | "FLOAT4" | "FLOAT8" | "NUMERIC" => number_literal("postgres", &text),
+        _ => quote_string("postgres", &text),
+    })
+}
+
+fn mssql_is_null(data: &ColumnData<'_>) -> bool {
+    matches!(
+        data,
+        ColumnData::U8(None)
+            | ColumnData::I16(None)
+            | ColumnData::I32(None)
+            | ColumnData::I64(None)
+            | ColumnData::F32(None)
+            | ColumnData::F64(None)
+            | ColumnData::Bit(None)
+            | ColumnData::String(None)
+            | ColumnData::Guid(None)
+            | ColumnData::Binary(None)
+            | ColumnData::Numeric(None)
+            | ColumnData::Xml(None)
+            | ColumnData::DateTime(None)
+            | ColumnData::SmallDateTime(None)
+            | ColumnData::Time(None)
+            | ColumnData::Date(None)
+            | ColumnData::DateTime2(None)
+            | ColumnData::DateTimeOffset(None)
+    )
+}
+
+/// MSSQL values go through the grid conversion, except binary and xml,
+/// which it does not read; anything else it cannot read fails the dump
+fn mssql_literal(row: &tiberius::Row, index: usize) -> Result<String, String> {
+    let Some((column, data)) = row.cells().nth(index) else {
+        return Err(format!("Column {} is missing from the row", index));
+    };
+    Ok(match data {
+        data if mssql_is_null(data) => "NULL".to_string(),
+        ColumnData::Binary(Some(bytes)) => format!("0x{}", hex(bytes)),
+        ColumnData::Xml(Some(xml)) => quote_string("mssql", &xml.to_string()),
+        _ => match mssql_value_to_json(row, index) {
+            serde_json::Value::Null => {
+                return Err(format!("Cannot dump column {} ({:?})", column.name(), column.column_type()));
+            }
+            value => quote_literal("mssql", &value),
+        },
+    })
+}
+
+/// Stream every row of `select` into INSERT statements, converting each
+/// value from its native type so nothing is lost to the grid's JSON
+/// conversion. Columns the dump cannot represent fail it instead of being
+/// written as NULL.
+async fn dump_rows(
+    writer: &mut InsertWriter<'_>,
+    connection: &Connection,
+    database: Option<&str>,
+    select: &str,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<(), String> {
+    let db_type = connection.db_type.as_str();
+    let column_list = |names: Vec<&str>| names.into_iter().map(|c| quote_ident(db_type, c)).collect::<Vec<_>>().join(", ");
+    let read_error = |e: sqlx::Error| format!("Failed to read rows: {}", e);
+
+    if db_type == "mssql" {
+        let mut client = create_mssql_client(&connection.config, database).await?;
+        let mut stream = client.query(select, &[]).await.map_err(|e| format!("查询数据失败: {}", e))?;
+        let mut columns = None;
+        while let Some(item) = stream.try_next().await.map_err(|e| format!("读取结果失败: {}", e))? {
+            if let QueryItem::Row(row) = item {
+                let columns = columns.get_or_insert_with(|| column_list(row.columns().iter().map(|c| c.name()).collect()));
+                let values = (0..row.len()).map(|i| mssql_literal(&row, i)).collect::<Result<Vec<_>, _>>()?;
+                writer.push(columns, &values)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // A plain &str runs unprepared, which is what selects the text protocols
+    match pool_manager.get_or_create_pool(connection, database).await? {
+        DatabasePool::Sqlite(pool) => {
+            let mut rows = pool.fetch(select);
+            let mut columns = None;
+            while let Some(row) = rows.try_next().await.map_err(read_error)? {
+                let columns = columns.get_or_insert_with(|| column_list(row.columns().iter().map(|c| c.name()).collect()));
+                let values = (0..row.len()).map(|i| sqlite_literal(&row, i)).collect::<Result<Vec<_>, _>>()?;
+                writer.push(columns, &values)?;
+            }
+        }
+        DatabasePool::Mysql(pool) => {
+            let mut rows = pool.fetch(select);
+            let mut columns = None;
+            while let Some(row) = rows.try_next().await.map_err(read_error)? {
+                let columns = columns.get_or_insert_with(|| column_list(row.columns().iter().map(|c| c.name()).collect()));
+                let values = (0..row.len()).map(|i| mysql_literal(&row, i)).collect::<Result<Vec<_>, _>>()?;
+                writer.push(columns, &values)?;
+            }
+        }
+        DatabasePool::Postgres(pool) => {
+            let mut rows = pool.fetch(select);
+            let mut columns = None;
+            while let Some(row) = rows.try_next().await.map_err(read_error)? {
+                let columns = columns.get_or_insert_with(|| column_list(row.columns().iter().map(|c| c.name()).collect()));
+                let values = (0..row.len()).map(|i| postgres_literal(&row, i)).collect::<Result<Vec<_>, _>>()?;
+                writer.push(columns, &values)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write one table's CREATE TABLE and/or INSERT statements. Returns the number
+/// of rows written.
+#[allow(clippy::too_many_arguments)]
+async fn dump_table_to(
+    out: &mut BufWriter<File>,
+    connection: &Connection,
+    database: Option<&str>,
+    table_name: &str,
+    include_schema: bool,
+    include_data: bool,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let db_type = connection.db_type.as_str();
+    let table = quote_ident(db_type, table_name);
+
+    writeln!(out, "-- Table: {}", table_name).map_err(AppError::from)?;
+    if include_schema {
+        let ddl = table_ddl(connection, database, table_name, app, pool_manager).await?;
+        writeln!(out, "{};\n", ddl).map_err(AppError::from)?;
+    }
+    if !include_data {
+        return Ok(0);
+    }
+
+    let columns = describe_table(
+        connection.id.clone(),
+        table_name.to_string(),
+        database.map(|s| s.to_string()),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+    ).await?;
+    let primary_key: Vec<String> = columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| quote_ident(db_type, &c.name))
+        .collect();
+    let identity_insert = db_type == "mssql" && columns.iter().any(|c| c.auto_increment);
+
+    if identity_insert {
+        writeln!(out, "SET IDENTITY_INSERT {} ON;", table).map_err(AppError::from)?;
+    }
+
+    // Rows are streamed, so the table is read with one query; ordering by
+    // the primary key keeps dumps of unchanged data identical
+    let select = if primary_key.is_empty() {
+        format!("SELECT * FROM {}", table)
+    } else {
+        format!("SELECT * FROM {} ORDER BY {}", table, primary_key.join(", "))
+    };
+    let mut writer = InsertWriter::new(out, table.clone());
+    dump_rows(&mut writer, connection, database, &select, pool_manager).await?;
+    writer.finish_statement()?;
+    let written = writer.written;
+
+    if identity_insert {
+        writeln!(out, "SET IDENTITY_INSERT {} OFF;", table).map_err(AppError::from)?;
+    }
+    writeln!(out).map_err(AppError::from)?;
+    Ok(written)
+}
+
+fn create_dump_file(file_path: &str, connection: &Connection) -> Result<BufWriter<File>, String> {
+    let file = File::create(file_path)
+        .map_err(|e| AppError::Export(format!("Failed to create {}: {}", file_path, e)))?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, "-- FeatherSQL dump of {} ({})\n", connection.name, connection.db_type).map_err(AppError::from)?;
+    Ok(out)
+}
+
+/// Write a SQL script that recreates every table of a database: CREATE TABLE
+/// statements and/or batched INSERTs. Returns the number of rows written.
+#[tauri::command]
+pub async fn dump_database(
+    connection_id: String,
+    database: Option<String>,
+    file_path: String,
+    include_schema: bool,
+    include_data: bool,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    // Resolve here so the table list and the dumped data come from the same database
+    let active_databases = app.state::<ActiveDatabases>();
+    let database = active_databases.resolve(connection, database).await;
+
+    let tables = list_tables(
+        connection_id.clone(),
+        database.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases,
+    ).await?;
+
+    let mut out = create_dump_file(&file_path, connection)?;
+    let mut written = 0;
+    for table in &tables {
+        written += dump_table_to(
+            &mut out,
+            connection,
+            database.as_deref(),
+            table,
+            include_schema,
+            include_data,
+            &app,
+            &pool_manager,
+        ).await?;
+    }
+    out.flush().map_err(AppError::from)?;
+
+    Ok(written)
+}
+
+/// Same as `dump_database` for a single table
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn dump_table(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    file_path: String,
+    include_schema: bool,
+    include_data: bool,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let mut out = create_dump_file(&file_path, connection)?;
+    let written = dump_table_to(
+        &mut out,
+        connection,
+        database.as_deref(),
+        &table_name,
+        include_schema,
+        include_data,
+        &app,
+        &pool_manager,
+    ).await?;
+    out.flush().map_err(AppError::from)?;
+
+    Ok(written)
+}
+
+/// Copy a SQLite database to `dest_path` while it stays in use. sqlx does not
+/// expose SQLite's backup API, so this uses `VACUUM INTO`, which writes a
+/// consistent snapshot from inside a read transaction the same way. In WAL
+/// mode committed pages are checkpointed first, so the main file is current
+/// as well. Returns the destination path.
+#[tauri::command]
+pub async fn backup_sqlite(
+    connection_id: String,
+    dest_path: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<String, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if !matches!(connection.config, ConnectionConfig::Sqlite { .. }) {
+        return Err("Backups are only supported for SQLite".to_string());
+    }
+
+    // VACUUM INTO refuses to overwrite, which also protects the source file
+    if Path::new(&dest_path).exists() {
+        return Err(format!("{} already exists", dest_path));
+    }
+
+    let DatabasePool::Sqlite(pool) = pool_manager.get_or_create_pool(connection, None).await? else {
+        return Err("Backups are only supported for SQLite".to_string());
+    };
+    let mut conn = pool.acquire().await.map_err(|e| format!("Backup failed: {}", e))?;
+    // Not an error outside WAL mode, and only best effort on read-only files
+    let _ = sqlx::query("PRAGMA wal_checkpoint(PASSIVE)").execute(&mut *conn).await;
+    sqlx::query("VACUUM INTO ?")
+        .bind(&dest_path)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Backup failed: {}", e))?;
+
+    Ok(dest_path)
+}
@@ -1,31 +1,84 @@
 use serde::{Deserialize, Serialize};
-use crate::db::connections::{load_connections, ConnectionConfig};
-use crate::db::pool_manager::{PoolManager, DatabasePool};
+use crate::db::column_source::{is_keyword, mssql_column_sources, single_table_sources, tokenize_spans, ColumnSource};
+use crate::db::connections::{load_connections, create_mssql_client, Connection, ConnectionConfig, FetchStrategy};
+use crate::db::pool_manager::{PoolManager, DatabasePool, POOL_MAX_CONNECTIONS};
+use crate::db::query_cache::QueryCache;
+use crate::db::recent::{record_recent, RecentKind};
+use crate::db::quote::quote_ident;
 use crate::db::history;
-use tauri::State;
-use sqlx::{Row, Column};
-use tiberius::{Config, AuthMethod, Client, QueryItem};
+use crate::db::session::{ActiveDatabases, DatabaseListCache};
+use crate::db::settings;
+use crate::db::sqlkind::{classify_statement, leading_keyword, StatementKind};
+use crate::db::transaction::{transaction_end, HeldConnection, OpenTransactions};
+use crate::error::AppError;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, State};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, RwLock, Semaphore};
+use sqlx::{Row, Column, TypeInfo, Executor};
+use tiberius::{Client, QueryItem};
 use tokio::net::TcpStream;
-use tokio_util::compat::{TokioAsyncWriteCompatExt, Compat};
+use tokio_util::compat::Compat;
 use futures_util::TryStreamExt;
 
-/// Convert a tiberius row value to JSON value
-fn mssql_value_to_json(row: &tiberius::Row, index: usize) -> serde_json::Value {
-    if let Some(v) = row.try_get::<&str, _>(index).ok().flatten() {
+/// Convert a tiberius row value to JSON value.
+pub(crate) fn mssql_value_to_json(row: &tiberius::Row, index: usize) -> serde_json::Value {
+    row.cells()
+        .nth(index)
+        .map(|(_, data)| mssql_data_to_json(data))
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Convert a tiberius column value to JSON value. Types are tried from the most
+/// specific: decimals become exact strings and dates/times ISO 8601 strings.
+fn mssql_data_to_json(data: &tiberius::ColumnData<'static>) -> serde_json::Value {
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+    use tiberius::FromSql;
+
+    if let Some(v) = tiberius::Uuid::from_sql(data).ok().flatten() {
+        // uniqueidentifier
+        uuid_to_json(v)
+    } else if let Some(v) = <&str as FromSql>::from_sql(data).ok().flatten() {
         serde_json::Value::String(v.to_string())
-    } else if let Some(v) = row.try_get::<i32, _>(index).ok().flatten() {
+    } else if let Some(v) = u8::from_sql(data).ok().flatten() {
+        // tinyint
         serde_json::Value::Number(v.into())
-    } else if let Some(v) = row.try_get::<i64, _>(index).ok().flatten() {
+    } else if let Some(v) = i16::from_sql(data).ok().flatten() {
+        // smallint
         serde_json::Value::Number(v.into())
-    } else if let Some(v) = row.try_get::<f64, _>(index).ok().flatten() {
+    } else if let Some(v) = i32::from_sql(data).ok().flatten() {
+        serde_json::Value::Number(v.into())
+    } else if let Some(v) = i64::from_sql(data).ok().flatten() {
+        serde_json::Value::Number(v.into())
+    } else if let Some(v) = f32::from_sql(data).ok().flatten() {
+        // real
+        serde_json::Number::from_f64(v as f64)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null)
+    } else if let Some(v) = f64::from_sql(data).ok().flatten() {
+        // float, and money/smallmoney, which tiberius decodes as f64
         serde_json::Value::Number(
             serde_json::Number::from_f64(v).unwrap_or(serde_json::Number::from(0))
         )
-    } else if let Some(v) = row.try_get::<bool, _>(index).ok().flatten() {
+    } else if let Some(v) = bool::from_sql(data).ok().flatten() {
         serde_json::Value::Bool(v)
+    } else if let Some(v) = tiberius::numeric::Numeric::from_sql(data).ok().flatten() {
+        // decimal/numeric as a string, so no digits are lost to f64
+        serde_json::Value::String(numeric_to_string(v.value(), v.scale()))
+    } else if let Some(v) = DateTime::<FixedOffset>::from_sql(data).ok().flatten() {
+        // datetimeoffset
+        serde_json::Value::String(v.to_rfc3339())
+    } else if let Some(v) = NaiveDateTime::from_sql(data).ok().flatten() {
+        // datetime, datetime2, smalldatetime
+        serde_json::Value::String(v.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+    } else if let Some(v) = NaiveDate::from_sql(data).ok().flatten() {
+        serde_json::Value::String(v.format("%Y-%m-%d").to_string())
+    } else if let Some(v) = NaiveTime::from_sql(data).ok().flatten() {
+        serde_json::Value::String(v.format("%H:%M:%S%.f").to_string())
     } else {
         // Try to get as string as fallback
-        row.try_get::<&str, _>(index)
+        <&str as FromSql>::from_sql(data)
             .ok()
             .flatten()
             .map(|s| serde_json::Value::String(s.to_string()))
@@ -33,49 +86,288 @@ fn mssql_value_to_json(row: &tiberius::Row, index: usize) -> serde_json::Value {
     }
 }
 
-/// Helper function to create MSSQL client connection
-async fn create_mssql_client(
-    host: &str,
-    port: u16,
-    user: &str,
-    password: &str,
-    database: Option<&str>,
-) -> Result<Client<Compat<TcpStream>>, String> {
-    let mut config = Config::new();
-    config.host(host);
-    config.port(port);
-    config.authentication(AuthMethod::sql_server(user, password));
-    config.trust_cert();
-    
-    if let Some(db) = database {
-        config.database(db);
+/// Decimal text of a scaled integer. tiberius' own `Display` puts the sign on
+/// both parts of a negative value ("-1.-5")
+fn numeric_to_string(value: i128, scale: u8) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let scale = scale as usize;
+    if scale == 0 {
+        return format!("{}{}", sign, value.unsigned_abs());
+    }
+    let digits = format!("{:0>width$}", value.unsigned_abs(), width = scale + 1);
+    let (int_part, dec_part) = digits.split_at(digits.len() - scale);
+    format!("{}{}.{}", sign, int_part, dec_part)
+}
+
+/// Map a tiberius column type to a lowercase SQL Server type name
+fn mssql_column_type_name(column_type: tiberius::ColumnType) -> &'static str {
+    use tiberius::ColumnType;
+    match column_type {
+        ColumnType::Null => "null",
+        ColumnType::Bit | ColumnType::Bitn => "bit",
+        ColumnType::Int1 => "tinyint",
+        ColumnType::Int2 => "smallint",
+        ColumnType::Int4 | ColumnType::Intn => "int",
+        ColumnType::Int8 => "bigint",
+        ColumnType::Float4 => "real",
+        ColumnType::Float8 | ColumnType::Floatn => "float",
+        ColumnType::Money | ColumnType::Money4 => "money",
+        ColumnType::Decimaln | ColumnType::Numericn => "decimal",
+        ColumnType::Datetime4 => "smalldatetime",
+        ColumnType::Datetime | ColumnType::Datetimen => "datetime",
+        ColumnType::Daten => "date",
+        ColumnType::Timen => "time",
+        ColumnType::Datetime2 => "datetime2",
+        ColumnType::DatetimeOffsetn => "datetimeoffset",
+        ColumnType::Guid => "uuid",
+        ColumnType::BigVarChar => "varchar",
+        ColumnType::BigChar => "char",
+        ColumnType::NVarchar => "nvarchar",
+        ColumnType::NChar => "nchar",
+        ColumnType::Text => "text",
+        ColumnType::NText => "ntext",
+        ColumnType::BigVarBin => "varbinary",
+        ColumnType::BigBinary => "binary",
+        ColumnType::Image => "image",
+        ColumnType::Xml => "xml",
+        ColumnType::Udt => "udt",
+        ColumnType::SSVariant => "sql_variant",
     }
-    
-    let tcp = TcpStream::connect(config.get_addr())
-        .await
-        .map_err(|e| format!("无法连接到服务器 {}:{} - {}", host, port, e))?;
-    
-    tcp.set_nodelay(true)
-        .map_err(|e| format!("设置 TCP 选项失败: {}", e))?;
-    
-    Client::connect(config, tcp.compat_write())
-        .await
-        .map_err(|e| format!("MSSQL 连接失败: {}", e))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
     pub rows: Vec<Vec<serde_json::Value>>,
+    /// Lowercase database type name for each column (e.g. `int4`, `uuid`)
+    #[serde(default)]
+    pub column_types: Vec<String>,
+    /// True when rows were dropped because the result hit `max_result_rows`
+    #[serde(default)]
+    pub truncated: bool,
+    /// Wall-clock execution time in milliseconds
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// Cells shortened to `max_cell_chars`; fetch them with `fetch_full_cell`
+    #[serde(default)]
+    pub truncated_cells: Vec<TruncatedCell>,
+    /// Column names made unique (`id`, `id_2`, ...) for keyed access, since
+    /// joins can return several columns with the same name
+    #[serde(default)]
+    pub column_keys: Vec<String>,
+    /// Served from the query cache instead of running on the server
+    #[serde(default)]
+    pub cached: bool,
+    /// Ran inside an open manual-mode transaction, so its changes are not
+    /// committed yet
+    #[serde(default)]
+    pub in_transaction: bool,
+    /// Table column behind each result column, for editing in the grid.
+    /// None for expressions and for queries over several tables.
+    #[serde(default)]
+    pub column_source: Vec<Option<ColumnSource>>,
+    /// The rows went out as `execute_sql_stream` events on the `query_id`
+    /// channel instead of in `rows`, per the connection's fetch strategy
+    #[serde(default)]
+    pub streamed: bool,
+    /// Key generated by an INSERT: the rowid on SQLite, AUTO_INCREMENT on
+    /// MySQL, an `id` column on Postgres and the identity on MSSQL
+    #[serde(default)]
+    pub last_insert_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TruncatedCell {
+    pub row: usize,
+    pub column: usize,
+    /// Length of the full value in characters
+    pub full_len: usize,
+}
+
+/// Whether the statement is an INSERT (or MySQL/SQLite REPLACE)
+fn is_insert(sql: &str) -> bool {
+    matches!(leading_keyword(sql).as_str(), "INSERT" | "REPLACE")
+}
+
+/// Whether the statement is a read that returns rows
+pub(crate) fn is_row_query(sql: &str) -> bool {
+    classify_statement(sql).returns_rows()
+}
+
+/// Unique keys for a list of column names: repeated names get `_2`, `_3`, ...
+/// skipping any suffix that is itself taken by another column
+pub(crate) fn unique_column_keys(columns: &[String]) -> Vec<String> {
+    let mut taken: HashSet<String> = columns.iter().cloned().collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+    columns
+        .iter()
+        .map(|name| {
+            if seen.insert(name.as_str()) {
+                return name.clone();
+            }
+            let key = (2..)
+                .map(|n| format!("{}_{}", name, n))
+                .find(|k| !taken.contains(k))
+                .unwrap_or_default();
+            taken.insert(key.clone());
+            key
+        })
+        .collect()
+}
+
+/// Shorten string cells longer than `max_chars` (appending an ellipsis) and
+/// record them in `truncated_cells`
+fn truncate_long_cells(result: &mut QueryResult, max_chars: usize) {
+    for (row_index, row) in result.rows.iter_mut().enumerate() {
+        for (column_index, value) in row.iter_mut().enumerate() {
+            if let serde_json::Value::String(text) = value {
+                let full_len = text.chars().count();
+                if full_len > max_chars {
+                    let mut short: String = text.chars().take(max_chars).collect();
+                    short.push('…');
+                    *text = short;
+                    result.truncated_cells.push(TruncatedCell {
+                        row: row_index,
+                        column: column_index,
+                        full_len,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Columns whose type is boolean: Postgres `bool`, `boolean` (MySQL reports
+/// TINYINT(1) as BOOLEAN, SQLite keeps the declared type) and MSSQL `bit`
+fn boolean_columns(column_types: &[String]) -> Vec<usize> {
+    column_types
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| matches!(t.as_str(), "bool" | "boolean" | "bit"))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Show 0/1 in the given boolean columns as false/true, so every engine's
+/// booleans reach the grid the same way; other values are left alone
+fn normalize_row_booleans(row: &mut [serde_json::Value], columns: &[usize]) {
+    for &i in columns {
+        if let Some(value) = row.get_mut(i) {
+            match value.as_i64() {
+                Some(0) => *value = serde_json::Value::Bool(false),
+                Some(1) => *value = serde_json::Value::Bool(true),
+                _ => {}
+            }
+        }
+    }
 }
 
+/// `normalize_row_booleans` for every row, driven by the column types
+fn normalize_booleans(result: &mut QueryResult) {
+    let columns = boolean_columns(&result.column_types);
+    if !columns.is_empty() {
+        for row in &mut result.rows {
+            normalize_row_booleans(row, &columns);
+        }
+    }
+}
+
+/// Collect at most `max_rows` rows from a query stream, reporting whether more were left
+async fn fetch_limited<R, S>(mut stream: S, max_rows: usize) -> Result<(Vec<R>, bool), sqlx::Error>
+where
+    S: futures_util::Stream<Item = Result<R, sqlx::Error>> + Unpin,
+{
+    let mut rows = Vec::new();
+    while let Some(row) = stream.try_next().await? {
+        if rows.len() >= max_rows {
+            return Ok((rows, true));
+        }
+        rows.push(row);
+    }
+    Ok((rows, false))
+}
+
+/// Run a statement that is not a read once, keeping the rows it may return
+/// (INSERT ... RETURNING, CALL) and its completion
+async fn fetch_with_done<R, Q, S>(mut stream: S, max_rows: usize) -> Result<(Vec<R>, bool, Q), sqlx::Error>
+where
+    Q: Default,
+    S: futures_util::Stream<Item = Result<sqlx::Either<Q, R>, sqlx::Error>> + Unpin,
+{
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut done = Q::default();
+    // Drained to the end, since the completion comes last
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            sqlx::Either::Right(_) if rows.len() >= max_rows => truncated = true,
+            sqlx::Either::Right(row) => rows.push(row),
+            sqlx::Either::Left(result) => done = result,
+        }
+    }
+    Ok((rows, truncated, done))
+}
+
+/// Result of a statement without rows: its affected row count
+fn affected_rows_result(rows_affected: u64, last_insert_id: Option<i64>) -> QueryResult {
+    QueryResult {
+        columns: vec!["affected_rows".to_string()],
+        rows: vec![vec![serde_json::Value::Number(serde_json::Number::from(rows_affected))]],
+        last_insert_id,
+        ..Default::default()
+    }
+}
+
+// Copies lastval() into a session setting, leaving it empty when no sequence
+// was used yet. The exception block keeps the error from aborting an open
+// transaction, which a plain `SELECT lastval()` would do.
+const POSTGRES_LASTVAL: &str = "DO $$ BEGIN \
+    PERFORM set_config('feathersql.lastval', lastval()::text, false); \
+    EXCEPTION WHEN object_not_in_prerequisite_state THEN \
+    PERFORM set_config('feathersql.lastval', '', false); END $$; \
+    SELECT NULLIF(current_setting('feathersql.lastval', true), '')::bigint";
+
+/// Value most recently generated by a sequence on this connection, which
+/// after an INSERT into a serial or identity column is the new row's key.
+/// None when no sequence has been used or the lookup fails.
+async fn postgres_lastval(conn: &mut sqlx::PgConnection) -> Option<i64> {
+    let row = (&mut *conn).fetch_optional(POSTGRES_LASTVAL).await.ok()??;
+    row.try_get::<Option<i64>, _>(0).ok().flatten()
+}
+
+/// Extract column names and lowercase type names from a sqlx row
+pub(crate) fn column_metadata<R: Row>(row: &R) -> (Vec<String>, Vec<String>) {
+    row.columns()
+        .iter()
+        .map(|col| (col.name().to_string(), col.type_info().name().to_lowercase()))
+        .unzip()
+}
+
+/// Run a statement and record it in the SQL history. Passing a `query_id`
+/// and/or `tab_id` lets `cancel_query` and `cancel_tab_queries` stop it.
+///
+/// Reads collect rows per the connection's `fetch_strategy`. A streamed
+/// strategy needs a `query_id` to name the channel; without one, or inside
+/// a manual-mode transaction, rows are capped at `max_result_rows` instead.
+/// Explicit paged calls (`execute_sql_stream`, exports, previews) ignore it.
+/// `limit` caps the rows of this call alone, overriding the strategy.
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn execute_sql(
     connection_id: String,
     sql: String,
     database: Option<String>,
+    query_id: Option<String>,
+    tab_id: Option<String>,
+    limit: Option<usize>,
     app: tauri::AppHandle,
     pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
+    query_cache: State<'_, QueryCache>,
+    running_queries: State<'_, RunningQueries>,
+    database_list_cache: State<'_, DatabaseListCache>,
+    open_transactions: State<'_, OpenTransactions>,
+    query_queue: State<'_, QueryQueue>,
 ) -> Result<QueryResult, String> {
     // Get connection from store
     let connections = load_connections(&app);
@@ -83,52 +375,136 @@ pub async fn execute_sql(
         .iter()
         .find(|c| c.id == connection_id)
         .ok_or_else(|| "Connection not found".to_string())?;
+    let database = active_databases.resolve(connection, database).await;
+    let settings = settings::load_settings(&app);
 
     let connection_name = connection.name.clone();
 
-    // Handle MSSQL separately since it uses tiberius instead of sqlx
-    let result = if connection.db_type == "mssql" {
-        match &connection.config {
-            ConnectionConfig::Mssql {
-                host,
-                port,
-                user,
-                password,
-                database: config_db,
-                ssl: _,
-            } => {
-                execute_sql_mssql(
-                    host,
-                    *port,
-                    user,
-                    password,
-                    database.as_deref().or(config_db.as_deref()),
-                    &sql,
-                ).await
-            }
-            _ => Err("无效的 MSSQL 配置".to_string()),
+    // With autocommit off, a write opens a transaction and everything after
+    // it runs there until it is committed or rolled back
+    let transactional = (!connection.autocommit && !is_row_query(&sql))
+        || open_transactions.get(&connection_id).await.is_some();
+
+    // Only reads are cached; any other statement may change what they return.
+    // Reads inside a transaction see its uncommitted changes, so they bypass the cache.
+    let cache_ttl = Duration::from_secs(settings.query_cache_ttl_secs);
+    let strategy = match limit {
+        Some(max_rows) => FetchStrategy::Capped { max_rows: max_rows.max(1) },
+        None => connection
+            .fetch_strategy
+            .unwrap_or(FetchStrategy::Capped { max_rows: settings.max_result_rows }),
+    };
+    let stream_batch = match strategy {
+        FetchStrategy::Streamed { batch_size } if is_row_query(&sql) && !transactional && query_id.is_some() => {
+            Some(batch_size)
         }
+        _ => None,
+    };
+    let max_rows = match strategy {
+        FetchStrategy::AllRows => usize::MAX,
+        FetchStrategy::Capped { max_rows } => max_rows,
+        FetchStrategy::Streamed { .. } => settings.max_result_rows,
+    };
+
+    // Streamed rows are not kept, so there is nothing to cache, and cached
+    // results were collected under the connection's own cap
+    let cacheable = settings.query_cache_ttl_secs > 0
+        && is_row_query(&sql)
+        && !transactional
+        && stream_batch.is_none()
+        && limit.is_none();
+    if !is_row_query(&sql) {
+        query_cache.invalidate(&connection_id).await;
+        // CREATE/DROP DATABASE changes what the existence check should accept
+        database_list_cache.invalidate(&connection_id).await;
+    }
+    let cached = if cacheable {
+        query_cache.get(&connection_id, database.as_deref(), &sql, cache_ttl).await
     } else {
-        // Get or create pool (with database if specified)
-        let pool = pool_manager.get_or_create_pool(connection, database.as_deref()).await?;
+        None
+    };
 
-        // Execute SQL based on database type
-        match pool {
-            DatabasePool::Sqlite(p) => {
-                execute_sql_sqlite(&p, &sql).await
-            }
-            DatabasePool::Mysql(p) => {
-                execute_sql_mysql(&p, &sql).await
+    let started = Instant::now();
+    let mut result = match cached {
+        Some(mut hit) => {
+            hit.cached = true;
+            Ok(hit)
+        }
+        None => {
+            // Queries of a tab are tracked even without an id of their own
+            let query_id = query_id.or_else(|| tab_id.as_ref().map(|_| uuid::Uuid::new_v4().to_string()));
+            let mut cancel = match &query_id {
+                Some(id) => Some(running_queries.register(id, tab_id.as_deref()).await),
+                None => None,
+            };
+            let result = if transactional {
+                let run = run_in_transaction(connection, database.as_deref(), &sql, max_rows, &pool_manager, &open_transactions);
+                cancellable(run, cancel.as_mut()).await
+            } else if let (Some(batch_size), Some(channel), Some(cancel)) = (stream_batch, &query_id, cancel.as_mut()) {
+                let run = async {
+                    let _slot = if settings.queue_concurrent_queries {
+                        let wait = async { Ok(query_queue.acquire(&connection_id).await) };
+                        cancellable(wait, Some(&mut *cancel)).await?
+                    } else {
+                        None
+                    };
+                    let summary = stream_to_channel(
+                        connection,
+                        database.as_deref(),
+                        &sql,
+                        channel,
+                        batch_size,
+                        &app,
+                        &pool_manager,
+                        cancel,
+                    ).await?;
+                    if summary.cancelled {
+                        return Err("Query cancelled".to_string());
+                    }
+                    Ok(QueryResult { streamed: true, ..Default::default() })
+                };
+                run.await
+            } else {
+                // Waiting in the queue can be cancelled like the query itself
+                let run = async {
+                    let _slot = if settings.queue_concurrent_queries {
+                        query_queue.acquire(&connection_id).await
+                    } else {
+                        None
+                    };
+                    run_query_with_limit(connection, database.as_deref(), &sql, &app, &pool_manager, max_rows).await
+                };
+                cancellable(run, cancel.as_mut()).await
+            };
+            if let Some(id) = &query_id {
+                running_queries.remove(id).await;
             }
-            DatabasePool::Postgres(p) => {
-                execute_sql_postgres(&p, &sql).await
+            if let (true, Ok(query_result)) = (cacheable, &result) {
+                query_cache.insert(&connection_id, database.as_deref(), &sql, query_result.clone(), cache_ttl).await;
             }
+            result
         }
     };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    if let Ok(query_result) = &mut result {
+        query_result.elapsed_ms = elapsed_ms;
+        // Only the grid gets shortened cells; exports go through run_query directly
+        if let Some(max_chars) = settings.max_cell_chars {
+            truncate_long_cells(query_result, max_chars);
+        }
+    }
+
+    // A read of a single table puts it in the recent objects
+    let table = result.as_ref().ok().and_then(|r| r.column_source.iter().flatten().next());
+    if let Some(source) = table {
+        if let Err(e) = record_recent(&app, &connection_id, RecentKind::Table, database.as_deref(), &source.table) {
+            eprintln!("Failed to save recent tables: {}", e);
+        }
+    }
 
     // Save to history
-    let rows_affected = result.as_ref().ok().and_then(|qr| extract_rows_affected(qr));
-    let error_msg = result.as_ref().err().map(|e| e.clone());
+    let rows_affected = result.as_ref().ok().and_then(extract_rows_affected);
+    let error_msg = result.as_ref().err().cloned();
     
     if let Err(e) = history::add_sql_history(
         connection_id.clone(),
@@ -137,6 +513,7 @@ pub async fn execute_sql(
         result.is_ok(),
         error_msg,
         rows_affected,
+        Some(elapsed_ms),
         app.clone(),
     ).await {
         eprintln!("Failed to save SQL history: {}", e);
@@ -145,15 +522,434 @@ pub async fn execute_sql(
     result
 }
 
+/// Run a batch or procedure call and return every result set it produces,
+/// in order, where `execute_sql` keeps only the first. Each set is capped at
+/// `max_result_rows`. On sqlx engines a result set with no rows is left out,
+/// since its columns are only known from a row. A batch that returns no rows
+/// gives a single `affected_rows` result.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn execute_sql_multi(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
+    query_cache: State<'_, QueryCache>,
+    database_list_cache: State<'_, DatabaseListCache>,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<Vec<QueryResult>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let database = active_databases.resolve(connection, database).await;
+    let settings = settings::load_settings(&app);
+
+    // Result sets are read outside the manual-mode transaction
+    if !connection.autocommit || open_transactions.get(&connection_id).await.is_some() {
+        return Err("Autocommit is off on this connection; run the batch with execute_sql".to_string());
+    }
+    if !is_row_query(&sql) {
+        query_cache.invalidate(&connection_id).await;
+        database_list_cache.invalidate(&connection_id).await;
+    }
+
+    let started = Instant::now();
+    let result = attempt_query_multi(
+        connection,
+        database.as_deref(),
+        &sql,
+        &settings,
+        &pool_manager,
+        settings.max_result_rows,
+    ).await.map_err(String::from);
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let rows_affected = result
+        .as_ref()
+        .ok()
+        .map(|sets| sets.iter().filter_map(extract_rows_affected).sum());
+    if let Err(e) = history::add_sql_history(
+        connection_id.clone(),
+        connection.name.clone(),
+        sql,
+        result.is_ok(),
+        result.as_ref().err().cloned(),
+        rows_affected,
+        Some(elapsed_ms),
+        app.clone(),
+    ).await {
+        eprintln!("Failed to save SQL history: {}", e);
+    }
+
+    result.map(|sets| {
+        sets.into_iter()
+            .map(|mut set| {
+                normalize_booleans(&mut set);
+                QueryResult {
+                    column_keys: unique_column_keys(&set.columns),
+                    elapsed_ms,
+                    ..set
+                }
+            })
+            .collect()
+    })
+}
+
+/// Re-run a query and return one untruncated cell, for values shortened by
+/// `max_cell_chars`. Only read statements are accepted since the SQL runs again.
+#[tauri::command]
+pub async fn fetch_full_cell(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    row: usize,
+    col: usize,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<serde_json::Value, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    // EXPLAIN ANALYZE and the like run the statement they inspect, so only
+    // plain queries are safe to run again
+    if classify_statement(&sql) != StatementKind::Query {
+        return Err("Only SELECT queries can be re-read".to_string());
+    }
+
+    let mut result = run_query(connection, database.as_deref(), &sql, &app, &pool_manager).await?;
+    result
+        .rows
+        .get_mut(row)
+        .and_then(|r| r.get_mut(col))
+        .map(serde_json::Value::take)
+        .ok_or_else(|| format!("Cell ({}, {}) is out of range", row, col))
+}
+
+/// Run SQL against a connection and collect the result, without recording history.
+///
+/// Shared by `execute_sql` and the commands that export query results.
+pub(crate) async fn run_query(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+) -> Result<QueryResult, String> {
+    let max_rows = settings::load_settings(app).max_result_rows;
+    run_query_with_limit(connection, database, sql, app, pool_manager, max_rows).await
+}
+
+/// Run SQL in the connection's open manual-mode transaction, beginning one
+/// if there is none. A bare COMMIT or ROLLBACK ends the transaction instead.
+/// Connection failures are not retried, since the transaction would be lost.
+async fn run_in_transaction(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    max_rows: usize,
+    pool_manager: &PoolManager,
+    open_transactions: &OpenTransactions,
+) -> Result<QueryResult, String> {
+    match transaction_end(sql) {
+        Some(true) => {
+            open_transactions.commit(&connection.id).await?;
+            return Ok(QueryResult::default());
+        }
+        Some(false) => {
+            open_transactions.rollback(&connection.id).await;
+            return Ok(QueryResult::default());
+        }
+        None => {}
+    }
+
+    let transaction = open_transactions.get_or_begin(connection, database, pool_manager).await?;
+    let mut transaction = transaction.lock().await;
+    if transaction.database.as_deref() != database {
+        return Err(format!(
+            "A transaction is open on {}; commit or roll back before switching databases",
+            transaction.database.as_deref().unwrap_or("the default database")
+        ));
+    }
+
+    // The held connection writes alongside the pool, so it takes the file's write lock too
+    let write_guard = if is_row_query(sql) {
+        None
+    } else {
+        pool_manager.sqlite_write_guard(connection).await
+    };
+    let result = execute_on_held(&mut transaction.conn, sql, max_rows).await?;
+    drop(write_guard);
+    transaction.statements += 1;
+
+    let mut result = QueryResult {
+        column_keys: unique_column_keys(&result.columns),
+        in_transaction: true,
+        ..result
+    };
+    normalize_booleans(&mut result);
+    fill_column_source(&mut result, connection, sql);
+    Ok(result)
+}
+
+/// Like `run_query`, but with an explicit row cap instead of the configured one.
+///
+/// Connection failures are retried up to `query_retry_count` times after
+/// dropping the connection's pools; errors in the SQL are returned at once.
+/// A connection lost while the statement ran is only retried for reads, as
+/// a write may already have been applied.
+pub(crate) async fn run_query_with_limit(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+    max_rows: usize,
+) -> Result<QueryResult, String> {
+    let settings = settings::load_settings(app);
+
+    let read_only = is_row_query(sql);
+    let mut attempt = 0;
+    loop {
+        match attempt_query(connection, database, sql, &settings, pool_manager, max_rows).await {
+            Err(e) if attempt < settings.query_retry_count && e.is_retryable(read_only) => {
+                eprintln!("Retrying after connection failure: {}", String::from(e));
+                // The pool may still hold the broken sockets; start from fresh ones
+                pool_manager.remove_pool(&connection.id).await;
+                attempt += 1;
+            }
+            result => return result.map(|r| finish_result(r, connection, sql)).map_err(String::from),
+        }
+    }
+}
+
+/// Fill in what the grid needs beyond the raw rows: unique column keys,
+/// booleans and the column sources
+fn finish_result(mut result: QueryResult, connection: &Connection, sql: &str) -> QueryResult {
+    result.column_keys = unique_column_keys(&result.columns);
+    normalize_booleans(&mut result);
+    fill_column_source(&mut result, connection, sql);
+    result
+}
+
+/// Run a statement on a connection held outside the pool, e.g. the one of
+/// an open transaction
+pub(crate) async fn execute_on_held(conn: &mut HeldConnection, sql: &str, max_rows: usize) -> Result<QueryResult, String> {
+    let result = match conn {
+        HeldConnection::Sqlite(conn) => execute_sql_sqlite(conn, sql, max_rows).await,
+        HeldConnection::Mysql(conn) => execute_sql_mysql(conn, sql, None, max_rows).await,
+        HeldConnection::Postgres(conn) => execute_sql_postgres(conn, sql, max_rows).await,
+        HeldConnection::Mssql(client) => execute_mssql_batch(client, sql, max_rows).await,
+    };
+    result.map_err(String::from)
+}
+
+/// Work out the column sources of a read on a sqlx connection. MSSQL
+/// results already carry them from the server.
+fn fill_column_source(result: &mut QueryResult, connection: &Connection, sql: &str) {
+    if connection.db_type != "mssql" && is_row_query(sql) {
+        result.column_source = single_table_sources(&connection.db_type, sql, &result.columns);
+    }
+}
+
+async fn attempt_query(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    settings: &settings::AppSettings,
+    pool_manager: &PoolManager,
+    max_rows: usize,
+) -> Result<QueryResult, ExecError> {
+    // Handle MSSQL separately since it uses tiberius instead of sqlx
+    if connection.db_type == "mssql" {
+        return match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                execute_sql_mssql(&connection.config, database.or(config_db.as_deref()), sql, max_rows).await
+            }
+            _ => Err("无效的 MSSQL 配置".to_string().into()),
+        };
+    }
+
+    // Get or create pool (with database if specified)
+    let (pool, use_database) = pool_manager
+        .get_pool_for_database(connection, database, settings.reuse_pool_across_databases)
+        .await
+        .map_err(ExecError::Connect)?;
+
+    // Execute SQL based on database type
+    match pool {
+        DatabasePool::Sqlite(p) => {
+            let _write_guard = if is_row_query(sql) {
+                None
+            } else {
+                pool_manager.sqlite_write_guard(connection).await
+            };
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            execute_sql_sqlite(&mut conn, sql, max_rows).await
+        }
+        DatabasePool::Mysql(p) => {
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            execute_sql_mysql(&mut conn, sql, use_database.as_deref(), max_rows).await
+        }
+        DatabasePool::Postgres(p) => {
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            execute_sql_postgres(&mut conn, sql, max_rows).await
+        }
+    }
+}
+
+/// Check SQL syntax without executing it.
+///
+/// sqlx backends only prepare the statement (`describe`), and MSSQL parses it
+/// under `SET PARSEONLY ON`, so DML is never applied.
+#[tauri::command]
+pub async fn validate_sql(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    if connection.db_type == "mssql" {
+        return match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                validate_sql_mssql(
+                    &connection.config,
+                    database.as_deref().or(config_db.as_deref()),
+                    &sql,
+                ).await
+            }
+            _ => Err("无效的 MSSQL 配置".to_string()),
+        };
+    }
+
+    let pool = pool_manager.get_or_create_pool(connection, database.as_deref()).await?;
+    let described = match &pool {
+        DatabasePool::Sqlite(p) => p.describe(&sql).await.map(|_| ()),
+        DatabasePool::Mysql(p) => p.describe(&sql).await.map(|_| ()),
+        DatabasePool::Postgres(p) => p.describe(&sql).await.map(|_| ()),
+    };
+
+    match described {
+        Ok(()) => Ok(()),
+        Err(sqlx::Error::Database(db_err)) => {
+            if let Some(mysql_err) = db_err.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+                // ER_UNSUPPORTED_PS: statement parsed fine but can't be prepared
+                if mysql_err.number() == 1295 {
+                    return Ok(());
+                }
+            }
+            let line = syntax_error_line(&sql, db_err.as_ref());
+            Err(AppError::SyntaxError {
+                line,
+                message: db_err.message().to_string(),
+            }.into())
+        }
+        Err(e) => Err(format!("SQL validation failed: {}", e)),
+    }
+}
+
+/// Best-effort 1-based line number of a syntax error reported by a sqlx backend
+fn syntax_error_line(sql: &str, db_err: &dyn sqlx::error::DatabaseError) -> Option<u32> {
+    if let Some(pg_err) = db_err.try_downcast_ref::<sqlx::postgres::PgDatabaseError>() {
+        if let Some(sqlx::postgres::PgErrorPosition::Original(position)) = pg_err.position() {
+            // Postgres reports a 1-based character offset into the statement
+            let newlines = sql
+                .chars()
+                .take(position.saturating_sub(1))
+                .filter(|c| *c == '\n')
+                .count();
+            return Some(newlines as u32 + 1);
+        }
+        return None;
+    }
+
+    // MySQL: "... near 'xxx' at line N"
+    let message = db_err.message();
+    message
+        .rfind("at line ")
+        .and_then(|pos| message[pos + 8..].split_whitespace().next())
+        .and_then(|n| n.parse().ok())
+}
+
+async fn validate_sql_mssql(
+    config: &ConnectionConfig,
+    database: Option<&str>,
+    sql: &str,
+) -> Result<(), String> {
+    let converted_sql = convert_limit_to_top(sql);
+    let mut client: Client<Compat<TcpStream>> = create_mssql_client(config, database).await?;
+
+    // The user's SQL starts on line 2 of the batch
+    let batch = format!("SET PARSEONLY ON;\n{}\n;SET PARSEONLY OFF;", converted_sql);
+    let result = match client.simple_query(batch).await {
+        Ok(stream) => stream.into_results().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(tiberius::error::Error::Server(token_err)) => Err(AppError::SyntaxError {
+            line: Some(token_err.line().saturating_sub(1).max(1)),
+            message: token_err.message().to_string(),
+        }.into()),
+        Err(e) => Err(format!("SQL 校验失败: {}", e)),
+    }
+}
+
+/// Run a single non-query statement (DDL or DML) and return the affected row count
+pub(crate) async fn execute_statement(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    pool_manager: &PoolManager,
+) -> Result<u64, String> {
+    if connection.db_type == "mssql" {
+        let db_name = match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => database.or(config_db.as_deref()),
+            _ => return Err("无效的 MSSQL 配置".to_string()),
+        };
+        let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
+        let result = client
+            .execute(sql, &[])
+            .await
+            .map_err(|e| format!("SQL 执行失败: {}", e))?;
+        return Ok(result.total());
+    }
+
+    let pool = pool_manager.get_or_create_pool(connection, database).await?;
+    let result = match pool {
+        DatabasePool::Sqlite(p) => {
+            let _write_guard = pool_manager.sqlite_write_guard(connection).await;
+            sqlx::query(sql).execute(&p).await.map(|r| r.rows_affected())
+        }
+        DatabasePool::Mysql(p) => sqlx::query(sql).execute(&p).await.map(|r| r.rows_affected()),
+        DatabasePool::Postgres(p) => sqlx::query(sql).execute(&p).await.map(|r| r.rows_affected()),
+    };
+    result.map_err(|e| execution_error(e).into())
+}
+
 /// Extract rows_affected from query result
-fn extract_rows_affected(query_result: &QueryResult) -> Option<u64> {
+pub(crate) fn extract_rows_affected(query_result: &QueryResult) -> Option<u64> {
     if query_result.rows.is_empty() {
         return None;
     }
     
     if query_result.columns.len() == 1 && query_result.columns[0] == "affected_rows" {
         // Try to extract the number from the first row
-        query_result.rows[0].get(0).and_then(|val| {
+        query_result.rows[0].first().and_then(|val| {
             match val {
                 serde_json::Value::Number(n) => {
                     n.as_u64().or_else(|| n.as_i64().map(|i| i as u64))
@@ -166,78 +962,320 @@ fn extract_rows_affected(query_result: &QueryResult) -> Option<u64> {
     }
 }
 
+/// Convert a single database column value to a JSON value (generic helper)
+macro_rules! value_to_json {
+    ($row:expr, $i:expr) => {{
+        let i = $i;
+        // Try to get value as different types. NULL comes first, as
+        // SQLite would decode it as an empty string.
+        if $row.try_get_raw(i).map_or(true, |v| sqlx::ValueRef::is_null(&v)) {
+            serde_json::Value::Null
+        } else if let Ok(v) = $row.try_get::<String, _>(i) {
+            serde_json::Value::String(v)
+        } else if let Ok(v) = $row.try_get::<i64, _>(i) {
+            serde_json::Value::Number(v.into())
+        } else if let Ok(v) = $row.try_get::<f64, _>(i) {
+            serde_json::Value::Number(
+                serde_json::Number::from_f64(v).unwrap_or(serde_json::Number::from(0))
+            )
+        } else if let Ok(v) = $row.try_get::<bool, _>(i) {
+            serde_json::Value::Bool(v)
+        } else if let Ok(v) = $row.try_get::<chrono::NaiveDateTime, _>(i) {
+            serde_json::Value::String(v.to_string())
+        } else if let Ok(v) = $row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
+            serde_json::Value::String(v.to_string())
+        } else {
+            // Fallback: try to get as string
+            $row.try_get::<String, _>(i)
+                .map(serde_json::Value::String)
+                .unwrap_or(serde_json::Value::Null)
+        }
+    }};
+}
+
 /// Convert a database row to a vector of JSON values (generic helper)
 macro_rules! row_to_json_values {
     ($row:expr, $column_count:expr) => {{
         (0..$column_count)
-            .map(|i| {
-                // Try to get value as different types
-                if let Ok(v) = $row.try_get::<String, _>(i) {
-                    serde_json::Value::String(v)
-                } else if let Ok(v) = $row.try_get::<i64, _>(i) {
-                    serde_json::Value::Number(v.into())
-                } else if let Ok(v) = $row.try_get::<f64, _>(i) {
-                    serde_json::Value::Number(
-                        serde_json::Number::from_f64(v).unwrap_or(serde_json::Number::from(0))
-                    )
-                } else if let Ok(v) = $row.try_get::<bool, _>(i) {
-                    serde_json::Value::Bool(v)
-                } else if let Ok(v) = $row.try_get::<chrono::NaiveDateTime, _>(i) {
-                    serde_json::Value::String(v.to_string())
-                } else if let Ok(v) = $row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
-                    serde_json::Value::String(v.to_string())
-                } else {
-                    // Fallback: try to get as string
-                    $row.try_get::<String, _>(i)
-                        .map(serde_json::Value::String)
-                        .unwrap_or(serde_json::Value::Null)
-                }
-            })
+            .map(|i| value_to_json!($row, i))
             .collect()
     }};
 }
 
+/// Convert a PostgreSQL column value to JSON, handling uuid, array and json/jsonb
+/// columns before falling back to the generic conversion
+pub(crate) fn postgres_value_to_json(row: &sqlx::postgres::PgRow, index: usize) -> serde_json::Value {
+    if let Ok(v) = row.try_get::<sqlx::types::Uuid, _>(index) {
+        uuid_to_json(v)
+    } else if let Ok(v) = row.try_get::<serde_json::Value, _>(index) {
+        // json/jsonb keep their structure instead of being flattened to a string
+        v
+    } else if let Ok(v) = row.try_get::<Vec<i64>, _>(index) {
+        array_to_json(v)
+    } else if let Ok(v) = row.try_get::<Vec<i32>, _>(index) {
+        array_to_json(v)
+    } else if let Ok(v) = row.try_get::<Vec<f64>, _>(index) {
+        array_to_json(v)
+    } else if let Ok(v) = row.try_get::<Vec<bool>, _>(index) {
+        array_to_json(v)
+    } else if let Ok(v) = row.try_get::<Vec<String>, _>(index) {
+        array_to_json(v)
+    } else {
+        value_to_json!(row, index)
+    }
+}
+
+/// A uuid in its canonical lower-case hyphenated form, whatever the driver's
+/// wire order (MSSQL stores the first three groups byte-swapped)
+fn uuid_to_json(v: uuid::Uuid) -> serde_json::Value {
+    serde_json::Value::String(v.hyphenated().to_string())
+}
+
+/// A decoded PostgreSQL array as a JSON array. Non-finite floats have no
+/// JSON number and become null.
+fn array_to_json<T: Into<serde_json::Value>>(values: Vec<T>) -> serde_json::Value {
+    serde_json::Value::Array(values.into_iter().map(Into::into).collect())
+}
+
+/// JSON for an unsigned integer: a number while it fits i64, else its text,
+/// so `BIGINT UNSIGNED` values past that are kept exactly
+fn unsigned_to_json(v: u64) -> serde_json::Value {
+    i64::try_from(v)
+        .map(serde_json::Value::from)
+        .unwrap_or_else(|_| serde_json::Value::String(v.to_string()))
+}
+
+/// Convert a MySQL column value to JSON. Unsigned integers are read as u64,
+/// which sqlx requires for them (`BIGINT UNSIGNED` overflows i64), and
+/// DECIMAL values, which no numeric type here reads, are kept as their text
+/// so neither comes back as null
+pub(crate) fn mysql_value_to_json(row: &sqlx::mysql::MySqlRow, index: usize) -> serde_json::Value {
+    if let Ok(v) = row.try_get::<u64, _>(index) {
+        return unsigned_to_json(v);
+    }
+    match value_to_json!(row, index) {
+        serde_json::Value::Null if row.column(index).type_info().name() == "DECIMAL" => row
+            .try_get_unchecked::<String, _>(index)
+            .map(serde_json::Value::String)
+            .unwrap_or(serde_json::Value::Null),
+        value => value,
+    }
+}
+
+/// Failure of one execution attempt. Connection failures are kept apart from
+/// errors in the SQL itself, so only they are retried on a fresh connection.
+enum ExecError {
+    /// No connection could be made, so the statement was never sent
+    Connect(String),
+    /// The connection broke while the statement ran; it may have been applied
+    Connection(String),
+    Query(String),
+}
+
+impl ExecError {
+    /// Whether running the statement again on a fresh connection is safe
+    fn is_retryable(&self, read_only: bool) -> bool {
+        match self {
+            ExecError::Connect(_) => true,
+            ExecError::Connection(_) => read_only,
+            ExecError::Query(_) => false,
+        }
+    }
+}
+
+impl From<String> for ExecError {
+    fn from(message: String) -> Self {
+        ExecError::Query(message)
+    }
+}
+
+impl From<ExecError> for String {
+    fn from(error: ExecError) -> Self {
+        match error {
+            ExecError::Connect(message) | ExecError::Connection(message) | ExecError::Query(message) => message,
+        }
+    }
+}
+
+/// Classify a failed sqlx statement; a pool timeout means every connection
+/// was busy rather than a problem with the SQL
+fn execution_error(e: sqlx::Error) -> ExecError {
+    match e {
+        sqlx::Error::PoolTimedOut => ExecError::Query(AppError::PoolExhausted(POOL_MAX_CONNECTIONS).into()),
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed => {
+            ExecError::Connection(format!("SQL execution failed: {}", e))
+        }
+        e => ExecError::Query(format!("SQL execution failed: {}", e)),
+    }
+}
+
+/// Classify a failed pool checkout: nothing has been sent on it yet
+fn acquire_error(e: sqlx::Error) -> ExecError {
+    match execution_error(e) {
+        ExecError::Connection(message) => ExecError::Connect(message),
+        other => other,
+    }
+}
+
+/// Classify a failed tiberius call the same way
+fn mssql_error(context: &str, e: tiberius::error::Error) -> ExecError {
+    let message = format!("{}: {}", context, e);
+    match e {
+        tiberius::error::Error::Io { .. } | tiberius::error::Error::Tls(_) => ExecError::Connection(message),
+        _ => ExecError::Query(message),
+    }
+}
+
+/// `attempt_query` for `execute_sql_multi`, keeping every result set
+async fn attempt_query_multi(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    settings: &settings::AppSettings,
+    pool_manager: &PoolManager,
+    max_rows: usize,
+) -> Result<Vec<QueryResult>, ExecError> {
+    if connection.db_type == "mssql" {
+        let db_name = match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => database.or(config_db.as_deref()),
+            _ => return Err("无效的 MSSQL 配置".to_string().into()),
+        };
+        let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name)
+            .await
+            .map_err(ExecError::Connect)?;
+        return execute_mssql_batch_multi(&mut client, sql, max_rows).await;
+    }
+
+    let (pool, use_database) = pool_manager
+        .get_pool_for_database(connection, database, settings.reuse_pool_across_databases)
+        .await
+        .map_err(ExecError::Connect)?;
+    let result = match pool {
+        DatabasePool::Sqlite(p) => {
+            let _write_guard = if is_row_query(sql) {
+                None
+            } else {
+                pool_manager.sqlite_write_guard(connection).await
+            };
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            let to_json = |row: &sqlx::sqlite::SqliteRow| row_to_json_values!(row, row.len());
+            collect_result_sets((&mut *conn).fetch_many(sql), to_json, |done| done.rows_affected(), max_rows).await
+        }
+        DatabasePool::Mysql(p) => {
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            // Shared pools switch database per checkout
+            if let Some(db) = use_database {
+                sqlx::query(&format!("USE {}", quote_ident("mysql", &db)))
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| format!("Failed to switch database: {}", e))?;
+            }
+            let to_json = |row: &sqlx::mysql::MySqlRow| (0..row.len()).map(|i| mysql_value_to_json(row, i)).collect();
+            collect_result_sets((&mut *conn).fetch_many(sql), to_json, |done| done.rows_affected(), max_rows).await
+        }
+        DatabasePool::Postgres(p) => {
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            let to_json = |row: &sqlx::postgres::PgRow| (0..row.len()).map(|i| postgres_value_to_json(row, i)).collect();
+            collect_result_sets((&mut *conn).fetch_many(sql), to_json, |done| done.rows_affected(), max_rows).await
+        }
+    };
+    result.map_err(execution_error)
+}
+
+/// Split the items of a `fetch_many` stream into result sets; each
+/// statement's completion ends the set its rows belong to
+async fn collect_result_sets<R, Q, S>(
+    mut stream: S,
+    to_json: impl Fn(&R) -> Vec<serde_json::Value>,
+    rows_affected: impl Fn(&Q) -> u64,
+    max_rows: usize,
+) -> Result<Vec<QueryResult>, sqlx::Error>
+where
+    R: Row,
+    S: futures_util::Stream<Item = Result<sqlx::Either<Q, R>, sqlx::Error>> + Unpin,
+{
+    let mut sets = Vec::new();
+    let mut current: Option<QueryResult> = None;
+    let mut affected = 0;
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            sqlx::Either::Right(row) => {
+                let set = current.get_or_insert_with(|| {
+                    let (columns, column_types) = column_metadata(&row);
+                    QueryResult { columns, column_types, ..Default::default() }
+                });
+                if set.rows.len() >= max_rows {
+                    set.truncated = true;
+                } else {
+                    set.rows.push(to_json(&row));
+                }
+            }
+            sqlx::Either::Left(done) => {
+                affected += rows_affected(&done);
+                sets.extend(current.take());
+            }
+        }
+    }
+    sets.extend(current.take());
+
+    if sets.is_empty() {
+        sets.push(QueryResult {
+            columns: vec!["affected_rows".to_string()],
+            rows: vec![vec![serde_json::Value::Number(serde_json::Number::from(affected))]],
+            ..Default::default()
+        });
+    }
+    Ok(sets)
+}
+
 async fn execute_sql_sqlite(
-    pool: &sqlx::Pool<sqlx::Sqlite>,
+    conn: &mut sqlx::SqliteConnection,
     sql: &str,
-) -> Result<QueryResult, String> {
+    max_rows: usize,
+) -> Result<QueryResult, ExecError> {
+    if !is_row_query(sql) {
+        let (rows, truncated, done) = fetch_with_done((&mut *conn).fetch_many(sqlx::query(sql)), max_rows)
+            .await
+            .map_err(execution_error)?;
+        if rows.is_empty() {
+            // The rowid is left over from an earlier insert when nothing was inserted
+            let last_insert_id = Some(done.last_insert_rowid()).filter(|_| is_insert(sql) && done.rows_affected() > 0);
+            return Ok(affected_rows_result(done.rows_affected(), last_insert_id));
+        }
+        let (columns, column_types) = column_metadata(&rows[0]);
+        let rows = rows.iter().map(|row| row_to_json_values!(row, columns.len())).collect();
+        return Ok(QueryResult { columns, rows, column_types, truncated, ..Default::default() });
+    }
+
     // Try to execute as a query first (SELECT statements)
-    let query_result = sqlx::query(sql).fetch_all(pool).await;
+    let query_result = fetch_limited(sqlx::query(sql).fetch(&mut *conn), max_rows).await;
     
     match query_result {
-        Ok(rows) => {
+        Ok((rows, truncated)) => {
             // Get column names - try from first row if available, otherwise try to get from a LIMIT 0 query
-            let columns: Vec<String> = if rows.is_empty() {
+            let (columns, column_types): (Vec<String>, Vec<String>) = if rows.is_empty() {
                 // If no rows, try to get column info by executing a LIMIT 0 query
-                let limit_query = if sql.trim().to_uppercase().starts_with("SELECT") {
-                    format!("{} LIMIT 0", sql.trim_end_matches(';').trim())
+                let limit_query = if classify_statement(sql) == StatementKind::Query {
+                    format!("{}\nLIMIT 0", sql.trim().trim_end_matches(';'))
                 } else {
                     sql.to_string()
                 };
                 
-                match sqlx::query(&limit_query).fetch_all(pool).await {
+                match sqlx::query(&limit_query).fetch_all(&mut *conn).await {
                     Ok(limit_rows) => {
                         if !limit_rows.is_empty() {
-                            limit_rows[0]
-                                .columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect()
+                            column_metadata(&limit_rows[0])
                         } else {
                             // Try to get from the original query's row structure
                             // This might work if the query structure is preserved
-                            vec![]
+                            (vec![], vec![])
                         }
                     }
-                    Err(_) => vec![],
+                    Err(_) => (vec![], vec![]),
                 }
             } else {
                 // Get column names from the first row
-                rows[0]
-                    .columns()
-                    .iter()
-                    .map(|col| col.name().to_string())
-                    .collect()
+                column_metadata(&rows[0])
             };
 
             // Convert rows to JSON values
@@ -249,160 +1287,210 @@ async fn execute_sql_sqlite(
             Ok(QueryResult {
                 columns,
                 rows: json_rows,
+                column_types,
+                truncated,
+                ..Default::default()
             })
         }
+        // Retrying as a command would only wait for the pool again
+        Err(sqlx::Error::PoolTimedOut) => Err(execution_error(sqlx::Error::PoolTimedOut)),
         Err(_) => {
             // If query fails, try to execute as a command (INSERT, UPDATE, DELETE, etc.)
-            match sqlx::query(sql).execute(pool).await {
+            match sqlx::query(sql).execute(&mut *conn).await {
                 Ok(result) => {
                     Ok(QueryResult {
                         columns: vec!["affected_rows".to_string()],
                         rows: vec![vec![serde_json::Value::Number(
                             serde_json::Number::from(result.rows_affected())
                         )]],
+                        ..Default::default()
                     })
                 }
-                Err(e) => Err(format!("SQL execution failed: {}", e)),
+                Err(e) => Err(execution_error(e)),
             }
         }
     }
-}
+}
+
+async fn execute_sql_mysql(
+    conn: &mut sqlx::MySqlConnection,
+    sql: &str,
+    use_database: Option<&str>,
+    max_rows: usize,
+) -> Result<QueryResult, ExecError> {
+    // Shared pools switch database per checkout
+    if let Some(db) = use_database {
+        sqlx::query(&format!("USE {}", quote_ident("mysql", db)))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to switch database: {}", e))?;
+    }
+
+    if !is_row_query(sql) {
+        let (rows, truncated, done) = fetch_with_done((&mut *conn).fetch_many(sqlx::query(sql)), max_rows)
+            .await
+            .map_err(execution_error)?;
+        if rows.is_empty() {
+            // LAST_INSERT_ID() is 0 when no AUTO_INCREMENT value was generated
+            let last_insert_id = Some(done.last_insert_id() as i64).filter(|id| is_insert(sql) && *id > 0);
+            return Ok(affected_rows_result(done.rows_affected(), last_insert_id));
+        }
+        let (columns, column_types) = column_metadata(&rows[0]);
+        let rows = rows.iter().map(|row| (0..columns.len()).map(|i| mysql_value_to_json(row, i)).collect()).collect();
+        return Ok(QueryResult { columns, rows, column_types, truncated, ..Default::default() });
+    }
 
-async fn execute_sql_mysql(
-    pool: &sqlx::Pool<sqlx::MySql>,
-    sql: &str,
-) -> Result<QueryResult, String> {
     // Try to execute as a query first (SELECT statements)
-    let query_result = sqlx::query(sql).fetch_all(pool).await;
+    let query_result = fetch_limited(sqlx::query(sql).fetch(&mut *conn), max_rows).await;
     
     match query_result {
-        Ok(rows) => {
+        Ok((rows, truncated)) => {
             // Get column names - try from first row if available, otherwise try to get from a LIMIT 0 query
-            let columns: Vec<String> = if rows.is_empty() {
+            let (columns, column_types): (Vec<String>, Vec<String>) = if rows.is_empty() {
                 // If no rows, try to get column info by executing a LIMIT 0 query
-                let limit_query = if sql.trim().to_uppercase().starts_with("SELECT") {
-                    format!("{} LIMIT 0", sql.trim_end_matches(';').trim())
+                let limit_query = if classify_statement(sql) == StatementKind::Query {
+                    format!("{}\nLIMIT 0", sql.trim().trim_end_matches(';'))
                 } else {
                     sql.to_string()
                 };
                 
-                match sqlx::query(&limit_query).fetch_all(pool).await {
+                match sqlx::query(&limit_query).fetch_all(&mut *conn).await {
                     Ok(limit_rows) => {
                         if !limit_rows.is_empty() {
-                            limit_rows[0]
-                                .columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect()
+                            column_metadata(&limit_rows[0])
                         } else {
-                            vec![]
+                            (vec![], vec![])
                         }
                     }
-                    Err(_) => vec![],
+                    Err(_) => (vec![], vec![]),
                 }
             } else {
                 // Get column names from the first row
-                rows[0]
-                    .columns()
-                    .iter()
-                    .map(|col| col.name().to_string())
-                    .collect()
+                column_metadata(&rows[0])
             };
 
             // Convert rows to JSON values
             let json_rows: Vec<Vec<serde_json::Value>> = rows
                 .iter()
-                .map(|row| row_to_json_values!(row, columns.len()))
+                .map(|row| (0..columns.len()).map(|i| mysql_value_to_json(row, i)).collect())
                 .collect();
 
             Ok(QueryResult {
                 columns,
                 rows: json_rows,
+                column_types,
+                truncated,
+                ..Default::default()
             })
         }
+        // Retrying as a command would only wait for the pool again
+        Err(sqlx::Error::PoolTimedOut) => Err(execution_error(sqlx::Error::PoolTimedOut)),
         Err(_) => {
             // If query fails, try to execute as a command (INSERT, UPDATE, DELETE, etc.)
-            match sqlx::query(sql).execute(pool).await {
+            match sqlx::query(sql).execute(&mut *conn).await {
                 Ok(result) => {
                     Ok(QueryResult {
                         columns: vec!["affected_rows".to_string()],
                         rows: vec![vec![serde_json::Value::Number(
                             serde_json::Number::from(result.rows_affected())
                         )]],
+                        ..Default::default()
                     })
                 }
-                Err(e) => Err(format!("SQL execution failed: {}", e)),
+                Err(e) => Err(execution_error(e)),
             }
         }
     }
 }
 
 async fn execute_sql_postgres(
-    pool: &sqlx::Pool<sqlx::Postgres>,
+    conn: &mut sqlx::PgConnection,
     sql: &str,
-) -> Result<QueryResult, String> {
+    max_rows: usize,
+) -> Result<QueryResult, ExecError> {
+    if !is_row_query(sql) {
+        let (rows, truncated, done) = fetch_with_done((&mut *conn).fetch_many(sqlx::query(sql)), max_rows)
+            .await
+            .map_err(execution_error)?;
+        if rows.is_empty() {
+            let last_insert_id = if is_insert(sql) && done.rows_affected() > 0 {
+                postgres_lastval(conn).await
+            } else {
+                None
+            };
+            return Ok(affected_rows_result(done.rows_affected(), last_insert_id));
+        }
+        let (columns, column_types) = column_metadata(&rows[0]);
+        let rows = rows
+            .iter()
+            .map(|row| (0..columns.len()).map(|i| postgres_value_to_json(row, i)).collect())
+            .collect();
+        return Ok(QueryResult { columns, rows, column_types, truncated, ..Default::default() });
+    }
+
     // Try to execute as a query first (SELECT statements)
-    let query_result = sqlx::query(sql).fetch_all(pool).await;
+    let query_result = fetch_limited(sqlx::query(sql).fetch(&mut *conn), max_rows).await;
     
     match query_result {
-        Ok(rows) => {
+        Ok((rows, truncated)) => {
             // Get column names - try from first row if available, otherwise try to get from a LIMIT 0 query
-            let columns: Vec<String> = if rows.is_empty() {
+            let (columns, column_types): (Vec<String>, Vec<String>) = if rows.is_empty() {
                 // If no rows, try to get column info by executing a LIMIT 0 query
                 // Check if SQL already has LIMIT clause
-                let sql_upper = sql.trim().to_uppercase();
-                let limit_query = if sql_upper.starts_with("SELECT") && !sql_upper.contains("LIMIT") {
-                    format!("{} LIMIT 0", sql.trim_end_matches(';').trim())
+                let limit_query = if classify_statement(sql) == StatementKind::Query && !sql.to_uppercase().contains("LIMIT") {
+                    format!("{}\nLIMIT 0", sql.trim().trim_end_matches(';'))
                 } else {
                     sql.to_string()
                 };
                 
-                match sqlx::query(&limit_query).fetch_all(pool).await {
+                match sqlx::query(&limit_query).fetch_all(&mut *conn).await {
                     Ok(limit_rows) => {
                         if !limit_rows.is_empty() {
-                            limit_rows[0]
-                                .columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect()
+                            column_metadata(&limit_rows[0])
                         } else {
-                            vec![]
+                            (vec![], vec![])
                         }
                     }
-                    Err(_) => vec![],
+                    Err(_) => (vec![], vec![]),
                 }
             } else {
                 // Get column names from the first row
-                rows[0]
-                    .columns()
-                    .iter()
-                    .map(|col| col.name().to_string())
-                    .collect()
+                column_metadata(&rows[0])
             };
 
             // Convert rows to JSON values
             let json_rows: Vec<Vec<serde_json::Value>> = rows
                 .iter()
-                .map(|row| row_to_json_values!(row, columns.len()))
+                .map(|row| {
+                    (0..columns.len())
+                        .map(|i| postgres_value_to_json(row, i))
+                        .collect()
+                })
                 .collect();
 
             Ok(QueryResult {
                 columns,
                 rows: json_rows,
+                column_types,
+                truncated,
+                ..Default::default()
             })
         }
+        // Retrying as a command would only wait for the pool again
+        Err(sqlx::Error::PoolTimedOut) => Err(execution_error(sqlx::Error::PoolTimedOut)),
         Err(_) => {
             // If query fails, try to execute as a command (INSERT, UPDATE, DELETE, etc.)
-            match sqlx::query(sql).execute(pool).await {
+            match sqlx::query(sql).execute(&mut *conn).await {
                 Ok(result) => {
                     Ok(QueryResult {
                         columns: vec!["affected_rows".to_string()],
                         rows: vec![vec![serde_json::Value::Number(
                             serde_json::Number::from(result.rows_affected())
                         )]],
+                        ..Default::default()
                     })
                 }
-                Err(e) => Err(format!("SQL execution failed: {}", e)),
+                Err(e) => Err(execution_error(e)),
             }
         }
     }
@@ -410,7 +1498,7 @@ async fn execute_sql_postgres(
 
 /// Convert LIMIT clause to TOP clause for MSSQL compatibility
 /// Handles patterns like: SELECT ... LIMIT n or SELECT ... LIMIT offset, n
-fn convert_limit_to_top(sql: &str) -> String {
+pub(crate) fn convert_limit_to_top(sql: &str) -> String {
     let sql_upper = sql.to_uppercase();
     
     // Find LIMIT keyword (case-insensitive)
@@ -425,7 +1513,7 @@ fn convert_limit_to_top(sql: &str) -> String {
             // Parse LIMIT value(s)
             let limit_value = if let Some(comma_pos) = limit_clause.find(',') {
                 // LIMIT offset, count -> use count as TOP value
-                limit_clause[comma_pos + 1..].trim().split_whitespace().next().unwrap_or("100")
+                limit_clause[comma_pos + 1..].split_whitespace().next().unwrap_or("100")
             } else {
                 // LIMIT count -> use count as TOP value
                 limit_clause.split_whitespace().next().unwrap_or("100")
@@ -462,67 +1550,918 @@ fn convert_limit_to_top(sql: &str) -> String {
 }
 
 async fn execute_sql_mssql(
-    host: &str,
-    port: u16,
-    user: &str,
-    password: &str,
+    config: &ConnectionConfig,
     database: Option<&str>,
     sql: &str,
-) -> Result<QueryResult, String> {
+    max_rows: usize,
+) -> Result<QueryResult, ExecError> {
+    // Create client connection using helper function
+    let mut client: Client<Compat<TcpStream>> = create_mssql_client(config, database)
+        .await
+        .map_err(ExecError::Connect)?;
+    execute_mssql_batch(&mut client, sql, max_rows).await
+}
+
+// Objects whose CREATE or ALTER must be the only statement of its batch
+const MSSQL_BATCH_OBJECTS: &[&str] = &["VIEW", "PROC", "PROCEDURE", "FUNCTION", "TRIGGER"];
+
+/// `sql` with a query for its row count appended (and for the new identity
+/// when `identity` is set), so DML reports what it changed. The newline keeps
+/// a trailing line comment from swallowing it. None for CREATE or ALTER of a
+/// view, procedure, function or trigger: those must be alone in their batch,
+/// and a procedure would take the appended query into its body.
+fn mssql_counted_batch(sql: &str, identity: bool) -> Option<String> {
+    let tokens = tokenize_spans("mssql", sql);
+    let mut words = tokens.iter().map(|(token, _)| token);
+    if let Some(first) = words.next().filter(|t| is_keyword(t, &["CREATE", "ALTER"])) {
+        let mut next = words.next();
+        if is_keyword(first, &["CREATE"]) && next.is_some_and(|t| is_keyword(t, &["OR"])) {
+            next = words.nth(1);
+        }
+        if next.is_some_and(|t| is_keyword(t, MSSQL_BATCH_OBJECTS)) {
+            return None;
+        }
+    }
+    Some(if identity {
+        format!("{}\n;SELECT @@ROWCOUNT AS affected_rows, CAST(SCOPE_IDENTITY() AS BIGINT) AS last_insert_id", sql)
+    } else {
+        format!("{}\n;SELECT @@ROWCOUNT AS affected_rows", sql)
+    })
+}
+
+/// Run a batch as written, for those `mssql_counted_batch` cannot extend,
+/// and report the rows it changed
+async fn execute_mssql_uncounted(client: &mut Client<Compat<TcpStream>>, sql: &str) -> Result<u64, ExecError> {
+    client
+        .execute(sql, &[])
+        .await
+        .map(|result| result.total())
+        .map_err(|e| mssql_error("SQL 执行失败", e))
+}
+
+async fn execute_mssql_batch(
+    client: &mut Client<Compat<TcpStream>>,
+    sql: &str,
+    max_rows: usize,
+) -> Result<QueryResult, ExecError> {
     // Convert LIMIT to TOP for MSSQL compatibility
     let converted_sql = convert_limit_to_top(sql);
+
+    // Described before the batch runs, since its stream holds the client
+    let column_source = if is_row_query(sql) {
+        mssql_column_sources(client, &converted_sql).await
+    } else {
+        None
+    };
     
-    // Create client connection using helper function
-    let mut client: Client<Compat<TcpStream>> = create_mssql_client(host, port, user, password, database).await?;
-    
+    // Count the affected rows and the new identity for INSERTs
+    let Some(batch) = mssql_counted_batch(&converted_sql, true) else {
+        let affected = execute_mssql_uncounted(client, &converted_sql).await?;
+        return Ok(affected_rows_result(affected, None));
+    };
+
     // Execute query
-    let mut stream: tiberius::QueryStream<'_> = client.query(&converted_sql, &[])
+    let mut stream: tiberius::QueryStream<'_> = client.query(&batch, &[])
         .await
-        .map_err(|e| format!("SQL 执行失败: {}", e))?;
+        .map_err(|e| mssql_error("SQL 执行失败", e))?;
     
-    // Collect metadata and rows
+    // Collect metadata and rows of the first result set
     let mut columns = Vec::new();
+    let mut column_types = Vec::new();
     let mut rows = Vec::new();
+    let mut result_sets = 0;
+    let mut affected_rows: Option<i32> = None;
+    let mut last_insert_id: Option<i64> = None;
+    let mut truncated = false;
     
     while let Some(item) = stream.try_next().await
-        .map_err(|e| format!("读取结果失败: {}", e))? {
+        .map_err(|e| mssql_error("读取结果失败", e))? {
         match item {
             QueryItem::Metadata(meta) => {
+                result_sets += 1;
                 // Extract column names from metadata
-                if columns.is_empty() {
+                if result_sets == 1 {
                     columns = meta.columns()
                         .iter()
                         .map(|col| col.name().to_string())
                         .collect();
+                    column_types = meta.columns()
+                        .iter()
+                        .map(|col| mssql_column_type_name(col.column_type()).to_string())
+                        .collect();
                 }
             }
             QueryItem::Row(row) => {
-                if columns.is_empty() {
-                    // If we haven't received metadata yet, we can't process the row
-                    continue;
+                // The last row seen always belongs to the trailing @@ROWCOUNT query
+                affected_rows = row.try_get::<i32, _>(0).ok().flatten();
+                last_insert_id = row.try_get::<i64, _>(1).ok().flatten();
+                if result_sets == 1 {
+                    // The @@ROWCOUNT result has a single row, so hitting the cap
+                    // means this is a real result set
+                    if rows.len() >= max_rows.max(1) {
+                        truncated = true;
+                        break;
+                    }
+                    let row_data: Vec<serde_json::Value> = (0..columns.len())
+                        .map(|i| mssql_value_to_json(&row, i))
+                        .collect();
+                    rows.push(row_data);
                 }
-                
-                let row_data: Vec<serde_json::Value> = (0..columns.len())
-                    .map(|i| mssql_value_to_json(&row, i))
-                    .collect();
-                rows.push(row_data);
             }
         }
     }
     
-    // If no columns found, this might be a non-query statement (INSERT, UPDATE, DELETE)
-    if columns.is_empty() {
-        // For non-query statements, we can't get affected rows easily with tiberius
-        // Return a simple success message
-        Ok(QueryResult {
-            columns: vec!["status".to_string()],
-            rows: vec![vec![serde_json::Value::String("执行成功".to_string())]],
-        })
+    // Only the @@ROWCOUNT result came back: a non-query statement (INSERT, UPDATE, DELETE)
+    if result_sets <= 1 && !truncated {
+        // SCOPE_IDENTITY() keeps the value of an earlier INSERT in the batch
+        let last_insert_id = last_insert_id.filter(|_| is_insert(sql));
+        Ok(affected_rows_result(affected_rows.unwrap_or(0).max(0) as u64, last_insert_id))
     } else {
+        let column_source = column_source
+            .filter(|sources| sources.len() == columns.len())
+            .unwrap_or_else(|| vec![None; columns.len()]);
         Ok(QueryResult {
             columns,
             rows,
+            column_types,
+            truncated,
+            column_source,
+            ..Default::default()
         })
     }
 }
 
+/// `execute_mssql_batch` for `execute_sql_multi`: each metadata token in the
+/// stream starts a new result set
+async fn execute_mssql_batch_multi(
+    client: &mut Client<Compat<TcpStream>>,
+    sql: &str,
+    max_rows: usize,
+) -> Result<Vec<QueryResult>, ExecError> {
+    let converted_sql = convert_limit_to_top(sql);
+    let Some(batch) = mssql_counted_batch(&converted_sql, false) else {
+        let affected = execute_mssql_uncounted(client, &converted_sql).await?;
+        return Ok(vec![affected_rows_result(affected, None)]);
+    };
+    let mut stream: tiberius::QueryStream<'_> = client.query(&batch, &[])
+        .await
+        .map_err(|e| mssql_error("SQL 执行失败", e))?;
+
+    let mut sets: Vec<QueryResult> = Vec::new();
+    while let Some(item) = stream.try_next().await
+        .map_err(|e| mssql_error("读取结果失败", e))? {
+        match item {
+            QueryItem::Metadata(meta) => {
+                let (columns, column_types) = meta.columns()
+                    .iter()
+                    .map(|col| (col.name().to_string(), mssql_column_type_name(col.column_type()).to_string()))
+                    .unzip();
+                sets.push(QueryResult { columns, column_types, ..Default::default() });
+            }
+            QueryItem::Row(row) => {
+                let Some(set) = sets.last_mut() else {
+                    continue;
+                };
+                if set.rows.len() >= max_rows {
+                    set.truncated = true;
+                } else {
+                    set.rows.push((0..set.columns.len()).map(|i| mssql_value_to_json(&row, i)).collect());
+                }
+            }
+        }
+    }
+
+    // The last result set is the appended @@ROWCOUNT query
+    let affected_rows = sets.pop().and_then(|set| set.rows.first().and_then(|row| row.first()).cloned());
+    if sets.is_empty() {
+        sets.push(QueryResult {
+            columns: vec!["affected_rows".to_string()],
+            rows: vec![vec![affected_rows.unwrap_or_else(|| serde_json::Value::from(0))]],
+            ..Default::default()
+        });
+    }
+    Ok(sets)
+}
+
+/// Per-connection bound on the queries `execute_sql` runs at once, matching
+/// the pool size. Queries past it wait for a running one to finish instead
+/// of timing out on the pool or opening extra MSSQL clients.
+pub struct QueryQueue {
+    slots: std::sync::Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl QueryQueue {
+    pub fn new() -> Self {
+        Self {
+            slots: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a free slot on a connection; the query runs while the permit is held
+    pub(crate) async fn acquire(&self, connection_id: &str) -> Option<OwnedSemaphorePermit> {
+        let semaphore = {
+            let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+            slots
+                .entry(connection_id.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(POOL_MAX_CONNECTIONS as usize)))
+                .clone()
+        };
+        semaphore.acquire_owned().await.ok()
+    }
+
+    /// Forget the slots of a deleted connection. Queries still holding a
+    /// permit keep their semaphore until they finish.
+    pub(crate) fn remove(&self, connection_id: &str) {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner()).remove(connection_id);
+    }
+}
+
+impl Default for QueryQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Rows per event when `execute_sql_stream` is not given a batch size
+const DEFAULT_STREAM_BATCH_SIZE: usize = 500;
+
+struct RunningQuery {
+    tab_id: Option<String>,
+    cancel: oneshot::Sender<()>,
+}
+
+/// Queries in progress, keyed by query id (the event channel for streams),
+/// so they can be cancelled one by one or per query tab
+pub struct RunningQueries {
+    entries: Arc<RwLock<HashMap<String, RunningQuery>>>,
+}
+
+impl RunningQueries {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Register a query and get the receiver that fires when it is cancelled.
+    /// A query already registered under the same id is cancelled.
+    pub async fn register(&self, query_id: &str, tab_id: Option<&str>) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        let mut entries = self.entries.write().await;
+        let query = RunningQuery {
+            tab_id: tab_id.map(|t| t.to_string()),
+            cancel: sender,
+        };
+        if let Some(previous) = entries.insert(query_id.to_string(), query) {
+            let _ = previous.cancel.send(());
+        }
+        receiver
+    }
+
+    pub async fn remove(&self, query_id: &str) {
+        let mut entries = self.entries.write().await;
+        entries.remove(query_id);
+    }
+
+    /// Returns false when no query with this id is running
+    pub async fn cancel(&self, query_id: &str) -> bool {
+        let mut entries = self.entries.write().await;
+        match entries.remove(query_id) {
+            Some(query) => query.cancel.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Cancel every query of a tab and return how many were running
+    pub async fn cancel_tab(&self, tab_id: &str) -> usize {
+        let mut entries = self.entries.write().await;
+        let ids: Vec<String> = entries
+            .iter()
+            .filter(|(_, query)| query.tab_id.as_deref() == Some(tab_id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.iter()
+            .filter_map(|id| entries.remove(id))
+            .map(|query| query.cancel.send(()))
+            .filter(Result::is_ok)
+            .count()
+    }
+}
+
+impl Default for RunningQueries {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run a query future unless it is cancelled first. Dropping the future
+/// drops its pooled connection or MSSQL client, so nothing stays checked out.
+async fn cancellable<T>(
+    query: impl std::future::Future<Output = Result<T, String>>,
+    cancel: Option<&mut oneshot::Receiver<()>>,
+) -> Result<T, String> {
+    match cancel {
+        Some(cancel) => tokio::select! {
+            result = query => result,
+            _ = cancel => Err("Query cancelled".to_string()),
+        },
+        None => query.await,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamSummary {
+    pub total_rows: u64,
+    pub elapsed_ms: u64,
+    /// Stopped by `cancel_sql_stream` before the last row
+    pub cancelled: bool,
+}
+
+/// Payload emitted on the channel of `execute_sql_stream`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// Always the first event, empty when the query returned no rows
+    Columns { columns: Vec<String>, column_types: Vec<String> },
+    Rows { rows: Vec<Vec<serde_json::Value>> },
+    Done(StreamSummary),
+}
+
+/// Buffers streamed rows and emits them in batches
+struct StreamSink<'a> {
+    app: &'a tauri::AppHandle,
+    channel: &'a str,
+    batch_size: usize,
+    batch: Vec<Vec<serde_json::Value>>,
+    total_rows: u64,
+    columns_sent: bool,
+    /// Boolean columns, from the column types
+    boolean_columns: Vec<usize>,
+}
+
+impl<'a> StreamSink<'a> {
+    fn new(app: &'a tauri::AppHandle, channel: &'a str, batch_size: usize) -> Self {
+        StreamSink {
+            app,
+            channel,
+            batch_size,
+            batch: Vec::new(),
+            total_rows: 0,
+            columns_sent: false,
+            boolean_columns: Vec::new(),
+        }
+    }
+
+    fn emit(&self, event: StreamEvent) {
+        let _ = self.app.emit(self.channel, event);
+    }
+
+    fn columns(&mut self, columns: Vec<String>, column_types: Vec<String>) {
+        if !self.columns_sent {
+            self.columns_sent = true;
+            self.boolean_columns = boolean_columns(&column_types);
+            self.emit(StreamEvent::Columns { columns, column_types });
+        }
+    }
+
+    fn push(&mut self, mut row: Vec<serde_json::Value>) {
+        normalize_row_booleans(&mut row, &self.boolean_columns);
+        self.batch.push(row);
+        self.total_rows += 1;
+        if self.batch.len() >= self.batch_size {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.batch.is_empty() {
+            let rows = std::mem::take(&mut self.batch);
+            self.emit(StreamEvent::Rows { rows });
+        }
+    }
+}
+
+/// Feed a sqlx row stream into the sink until it ends or the stream is
+/// cancelled. Returns whether it was cancelled; dropping the stream stops the query.
+async fn stream_sqlx_rows<R, S>(
+    mut stream: S,
+    to_json: impl Fn(&R) -> Vec<serde_json::Value>,
+    sink: &mut StreamSink<'_>,
+    cancel: &mut oneshot::Receiver<()>,
+) -> Result<bool, String>
+where
+    R: Row,
+    S: futures_util::Stream<Item = Result<R, sqlx::Error>> + Unpin,
+{
+    loop {
+        tokio::select! {
+            _ = &mut *cancel => return Ok(true),
+            next = stream.try_next() => match next.map_err(execution_error)? {
+                Some(row) => {
+                    if !sink.columns_sent {
+                        let (columns, column_types) = column_metadata(&row);
+                        sink.columns(columns, column_types);
+                    }
+                    sink.push(to_json(&row));
+                }
+                None => return Ok(false),
+            },
+        }
+    }
+}
+
+/// MSSQL counterpart of `stream_sqlx_rows`; only the first result set is streamed
+async fn stream_mssql_rows(
+    config: &ConnectionConfig,
+    database: Option<&str>,
+    sql: &str,
+    sink: &mut StreamSink<'_>,
+    cancel: &mut oneshot::Receiver<()>,
+) -> Result<bool, String> {
+    let converted_sql = convert_limit_to_top(sql);
+    let mut client: Client<Compat<TcpStream>> = create_mssql_client(config, database).await?;
+    let mut stream: tiberius::QueryStream<'_> = client.query(&converted_sql, &[])
+        .await
+        .map_err(|e| mssql_error("SQL 执行失败", e))?;
+
+    let mut result_sets = 0;
+    let mut column_count = 0;
+    loop {
+        tokio::select! {
+            _ = &mut *cancel => return Ok(true),
+            next = stream.try_next() => match next.map_err(|e| mssql_error("读取结果失败", e))? {
+                Some(QueryItem::Metadata(meta)) => {
+                    result_sets += 1;
+                    if result_sets == 1 {
+                        column_count = meta.columns().len();
+                        sink.columns(
+                            meta.columns().iter().map(|col| col.name().to_string()).collect(),
+                            meta.columns()
+                                .iter()
+                                .map(|col| mssql_column_type_name(col.column_type()).to_string())
+                                .collect(),
+                        );
+                    }
+                }
+                Some(QueryItem::Row(row)) => {
+                    if result_sets == 1 {
+                        sink.push((0..column_count).map(|i| mssql_value_to_json(&row, i)).collect());
+                    }
+                }
+                None => return Ok(false),
+            },
+        }
+    }
+}
+
+async fn stream_query(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    reuse_across_databases: bool,
+    pool_manager: &PoolManager,
+    sink: &mut StreamSink<'_>,
+    cancel: &mut oneshot::Receiver<()>,
+) -> Result<bool, String> {
+    if connection.db_type == "mssql" {
+        let db_name = match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => database.or(config_db.as_deref()),
+            _ => return Err("无效的 MSSQL 配置".to_string()),
+        };
+        return stream_mssql_rows(&connection.config, db_name, sql, sink, cancel).await;
+    }
+
+    let (pool, use_database) = pool_manager
+        .get_pool_for_database(connection, database, reuse_across_databases)
+        .await?;
+    match pool {
+        DatabasePool::Sqlite(p) => {
+            let to_json = |row: &sqlx::sqlite::SqliteRow| row_to_json_values!(row, row.len());
+            stream_sqlx_rows(sqlx::query(sql).fetch(&p), to_json, sink, cancel).await
+        }
+        DatabasePool::Mysql(p) => {
+            let mut conn = p.acquire().await.map_err(acquire_error)?;
+            // Shared pools switch database per checkout
+            if let Some(db) = use_database {
+                sqlx::query(&format!("USE {}", quote_ident("mysql", &db)))
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| format!("Failed to switch database: {}", e))?;
+            }
+            let to_json = |row: &sqlx::mysql::MySqlRow| (0..row.len()).map(|i| mysql_value_to_json(row, i)).collect();
+            stream_sqlx_rows(sqlx::query(sql).fetch(&mut *conn), to_json, sink, cancel).await
+        }
+        DatabasePool::Postgres(p) => {
+            let to_json = |row: &sqlx::postgres::PgRow| (0..row.len()).map(|i| postgres_value_to_json(row, i)).collect();
+            stream_sqlx_rows(sqlx::query(sql).fetch(&p), to_json, sink, cancel).await
+        }
+    }
+}
+
+/// Stream the rows of a query on `channel`, ending with the `done` event
+#[allow(clippy::too_many_arguments)]
+async fn stream_to_channel(
+    connection: &Connection,
+    database: Option<&str>,
+    sql: &str,
+    channel: &str,
+    batch_size: usize,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+    cancel: &mut oneshot::Receiver<()>,
+) -> Result<StreamSummary, String> {
+    let reuse_across_databases = settings::load_settings(app).reuse_pool_across_databases;
+    let mut sink = StreamSink::new(app, channel, batch_size);
+
+    let started = Instant::now();
+    let cancelled = stream_query(connection, database, sql, reuse_across_databases, pool_manager, &mut sink, cancel).await?;
+    sink.flush();
+    sink.columns(Vec::new(), Vec::new());
+    let summary = StreamSummary {
+        total_rows: sink.total_rows,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        cancelled,
+    };
+    sink.emit(StreamEvent::Done(summary.clone()));
+    Ok(summary)
+}
+
+/// Run a query and emit its rows on `channel` as they arrive: a `columns`
+/// event first, then `rows` events of up to `batch_size` rows (500 by
+/// default), then a `done` event with the totals, which are also returned.
+/// `cancel_sql_stream` with the same channel, or `cancel_tab_queries` with
+/// its `tab_id`, stops it early.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn execute_sql_stream(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    batch_size: Option<usize>,
+    channel: String,
+    tab_id: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
+    running_queries: State<'_, RunningQueries>,
+) -> Result<StreamSummary, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let database = active_databases.resolve(connection, database).await;
+
+    if !is_row_query(&sql) {
+        return Err("Only queries that return rows can be streamed".to_string());
+    }
+
+    let batch_size = batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE).max(1);
+    let mut cancel = running_queries.register(&channel, tab_id.as_deref()).await;
+
+    let started = Instant::now();
+    let result = stream_to_channel(
+        connection,
+        database.as_deref(),
+        &sql,
+        &channel,
+        batch_size,
+        &app,
+        &pool_manager,
+        &mut cancel,
+    ).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    running_queries.remove(&channel).await;
+
+    if let Err(e) = history::add_sql_history(
+        connection_id.clone(),
+        connection.name.clone(),
+        sql,
+        result.is_ok(),
+        result.as_ref().err().cloned(),
+        None,
+        Some(elapsed_ms),
+        app.clone(),
+    ).await {
+        eprintln!("Failed to save SQL history: {}", e);
+    }
+
+    result
+}
+
+/// Stop the `execute_sql_stream` running on `channel`. Returns false when
+/// nothing was running there.
+#[tauri::command]
+pub async fn cancel_sql_stream(
+    channel: String,
+    running_queries: State<'_, RunningQueries>,
+) -> Result<bool, String> {
+    Ok(running_queries.cancel(&channel).await)
+}
+
+/// Cancel a running `execute_sql` by the `query_id` it was started with.
+/// Returns false when it had already finished.
+#[tauri::command]
+pub async fn cancel_query(
+    query_id: String,
+    running_queries: State<'_, RunningQueries>,
+) -> Result<bool, String> {
+    Ok(running_queries.cancel(&query_id).await)
+}
+
+/// Cancel every query and stream started with this `tab_id`, for when the
+/// tab is closed. Returns how many were stopped.
+#[tauri::command]
+pub async fn cancel_tab_queries(
+    tab_id: String,
+    running_queries: State<'_, RunningQueries>,
+) -> Result<usize, String> {
+    Ok(running_queries.cancel_tab(&tab_id).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::db::connections::{mssql_test_connection, postgres_test_connection, sqlite_test_connection};
+    #[tokio::test]
+    async fn joined_columns_with_one_name_get_unique_keys() {
+        let connection = sqlite_test_connection("duplicate-columns");
+        let pool_manager = PoolManager::new();
+        let settings = settings::AppSettings::default();
+        let setup = "CREATE TABLE a (id INTEGER); CREATE TABLE b (id INTEGER, a_id INTEGER);
+                     INSERT INTO a VALUES (1); INSERT INTO b VALUES (10, 1)";
+        execute_statement(&connection, None, setup, &pool_manager).await.unwrap();
+
+        let sql = "SELECT a.id, b.id FROM a JOIN b ON b.a_id = a.id";
+        let result = attempt_query(&connection, None, sql, &settings, &pool_manager, 10).await.map_err(String::from).unwrap();
+        let result = finish_result(result, &connection, sql);
+        assert_eq!(result.columns, vec!["id", "id"]);
+        assert_eq!(result.column_keys, vec!["id", "id_2"]);
+        assert_eq!(result.rows, vec![vec![serde_json::json!(1), serde_json::json!(10)]]);
+    }
+
+    #[test]
+    fn mssql_values_become_json() {
+        use tiberius::{numeric::Numeric, ColumnData, IntoSql};
+
+        let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let stamp = chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_opt(13, 45, 30).unwrap();
+        let cases = [
+            (ColumnData::U8(Some(255)), serde_json::json!(255)),
+            (ColumnData::I16(Some(-32768)), serde_json::json!(-32768)),
+            (ColumnData::Numeric(Some(Numeric::new_with_scale(-1234567890123456789, 4))), serde_json::json!("-123456789012345.6789")),
+            (ColumnData::Numeric(Some(Numeric::new_with_scale(-5, 2))), serde_json::json!("-0.05")),
+            (ColumnData::Numeric(Some(Numeric::new_with_scale(42, 0))), serde_json::json!("42")),
+            // money arrives as f64
+            (ColumnData::F64(Some(19.99)), serde_json::json!(19.99)),
+            (stamp.and_local_timezone(offset).unwrap().into_sql(), serde_json::json!("2024-02-29T13:45:30+02:00")),
+            (stamp.date().into_sql(), serde_json::json!("2024-02-29")),
+            (stamp.into_sql(), serde_json::json!("2024-02-29T13:45:30")),
+            (ColumnData::I32(None), serde_json::Value::Null),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(mssql_data_to_json(&data), expected);
+        }
+    }
+
+    #[test]
+    fn uuids_use_the_hyphenated_form() {
+        let v = uuid::Uuid::parse_str("A0EEBC999C0B4EF8BB6D6BB9BD380A11").unwrap();
+        assert_eq!(uuid_to_json(v), serde_json::json!("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL server in FEATHERSQL_TEST_POSTGRES"]
+    async fn postgres_uuid_round_trips() {
+        let connection = postgres_test_connection();
+        let pool_manager = PoolManager::new();
+        let settings = settings::AppSettings::default();
+        let table = format!("uuid_round_trip_{}", std::process::id());
+        execute_statement(&connection, None, &format!("CREATE TABLE {} (id uuid)", table), &pool_manager).await.unwrap();
+        execute_statement(&connection, None, &format!("INSERT INTO {} VALUES ('a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11')", table), &pool_manager)
+            .await
+            .unwrap();
+        let result = attempt_query(&connection, None, &format!("SELECT id FROM {}", table), &settings, &pool_manager, 10).await;
+        execute_statement(&connection, None, &format!("DROP TABLE {}", table), &pool_manager).await.unwrap();
+        assert_eq!(result.map_err(String::from).unwrap().rows, vec![vec![serde_json::json!("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")]]);
+    }
+
+    #[test]
+    fn postgres_arrays_become_json_arrays() {
+        assert_eq!(array_to_json(vec![1i32, 2, 3]), serde_json::json!([1, 2, 3]));
+        assert_eq!(array_to_json(vec!["a".to_string(), "b".to_string()]), serde_json::json!(["a", "b"]));
+        assert_eq!(array_to_json(vec![1.5, f64::NAN]), serde_json::json!([1.5, null]));
+        assert_eq!(array_to_json(Vec::<bool>::new()), serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL server in FEATHERSQL_TEST_POSTGRES"]
+    async fn postgres_arrays_and_jsonb_keep_their_structure() {
+        let connection = postgres_test_connection();
+        let pool_manager = PoolManager::new();
+        let settings = settings::AppSettings::default();
+        let sql = "SELECT ARRAY[1,2,3] AS ints, ARRAY['a','b'] AS texts, '{\"a\": [1, {\"b\": null}]}'::jsonb AS doc";
+        let result = attempt_query(&connection, None, sql, &settings, &pool_manager, 10).await.map_err(String::from).unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![serde_json::json!([1, 2, 3]), serde_json::json!(["a", "b"]), serde_json::json!({"a": [1, {"b": null}]})]]
+        );
+    }
+
+    #[test]
+    fn mssql_row_count_is_appended_to_dml() {
+        let batch = mssql_counted_batch("UPDATE t SET a = 1 -- all rows", false).unwrap();
+        assert_eq!(batch, "UPDATE t SET a = 1 -- all rows\n;SELECT @@ROWCOUNT AS affected_rows");
+        assert!(mssql_counted_batch("INSERT INTO t VALUES (1)", true).unwrap().ends_with("AS last_insert_id"));
+        assert!(mssql_counted_batch("CREATE TABLE t (a INT)", false).is_some());
+        assert!(mssql_counted_batch("ALTER TABLE t ADD b INT", false).is_some());
+    }
+
+    #[test]
+    fn mssql_object_definitions_are_sent_unchanged() {
+        for sql in [
+            "CREATE PROCEDURE p AS SELECT 1",
+            "create proc p as select 1",
+            "CREATE OR ALTER VIEW v AS SELECT 1 AS a",
+            "-- new trigger\nALTER TRIGGER tr ON t AFTER INSERT AS SELECT 1",
+            "CREATE FUNCTION f() RETURNS INT AS BEGIN RETURN 1 END",
+        ] {
+            assert_eq!(mssql_counted_batch(sql, true), None, "{}", sql);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an MSSQL server in FEATHERSQL_TEST_MSSQL"]
+    async fn mssql_update_reports_affected_rows() {
+        let config = mssql_test_connection().config;
+        let sql = "CREATE TABLE #counted (a INT); INSERT INTO #counted VALUES (1), (2), (3)";
+        let mut client = create_mssql_client(&config, None).await.unwrap();
+        execute_mssql_batch(&mut client, sql, 10).await.map_err(String::from).unwrap();
+
+        let result = execute_mssql_batch(&mut client, "UPDATE #counted SET a = a + 1 WHERE a > 1", 10)
+            .await
+            .map_err(String::from)
+            .unwrap();
+        assert_eq!(extract_rows_affected(&result), Some(2));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an MSSQL server in FEATHERSQL_TEST_MSSQL"]
+    async fn mssql_procedure_body_is_kept_as_written() {
+        let config = mssql_test_connection().config;
+        let mut client = create_mssql_client(&config, None).await.unwrap();
+        let name = format!("feathersql_test_{}", std::process::id());
+        execute_mssql_batch(&mut client, &format!("CREATE PROCEDURE {} AS SELECT 1 AS one", name), 10)
+            .await
+            .map_err(String::from)
+            .unwrap();
+
+        let definition = execute_mssql_batch(&mut client, &format!("SELECT OBJECT_DEFINITION(OBJECT_ID('{}'))", name), 10)
+            .await
+            .map_err(String::from);
+        execute_mssql_batch(&mut client, &format!("DROP PROCEDURE {}", name), 10).await.map_err(String::from).unwrap();
+        assert_eq!(definition.unwrap().rows, vec![vec![serde_json::json!(format!("CREATE PROCEDURE {} AS SELECT 1 AS one", name))]]);
+    }
+
+
+    #[tokio::test]
+    async fn queued_queries_stay_within_the_pool_size() {
+        let connection = sqlite_test_connection("query-queue");
+        let pool_manager = Arc::new(PoolManager::new());
+        let query_queue = Arc::new(QueryQueue::new());
+        let settings = Arc::new(settings::AppSettings::default());
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let sql = "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200000) SELECT COUNT(*) FROM n";
+        let tasks: Vec<_> = (0..POOL_MAX_CONNECTIONS as usize + 5)
+            .map(|_| {
+                let (connection, pool_manager, query_queue, settings, running, peak) = (
+                    connection.clone(),
+                    pool_manager.clone(),
+                    query_queue.clone(),
+                    settings.clone(),
+                    running.clone(),
+                    peak.clone(),
+                );
+                tokio::spawn(async move {
+                    let _slot = query_queue.acquire(&connection.id).await;
+                    peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    let result = attempt_query(&connection, None, sql, &settings, &pool_manager, 10).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    result.map_err(String::from)
+                })
+            })
+            .collect();
+        for task in tasks {
+            let result = task.await.unwrap().unwrap();
+            assert_eq!(result.rows, vec![vec![serde_json::json!(200000)]]);
+        }
+        assert!(peak.load(Ordering::SeqCst) <= POOL_MAX_CONNECTIONS as usize);
+    }
+
+    #[test]
+    fn boolean_columns_by_engine_type_name() {
+        // PostgreSQL `bool`, MySQL and SQLite `boolean`, MSSQL `bit`
+        let types: Vec<String> = ["bool", "int4", "boolean", "tinyint", "bit", "integer", "varbit"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(boolean_columns(&types), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn normalize_row_booleans_maps_only_zero_and_one() {
+        let mut row = vec![
+            serde_json::json!(0),
+            serde_json::json!(1),
+            serde_json::json!(2),
+            serde_json::json!(-1),
+            serde_json::Value::Null,
+            serde_json::json!(true),
+            serde_json::json!(1),
+        ];
+        normalize_row_booleans(&mut row, &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            row,
+            vec![
+                serde_json::json!(false),
+                serde_json::json!(true),
+                serde_json::json!(2),
+                serde_json::json!(-1),
+                serde_json::Value::Null,
+                serde_json::json!(true),
+                // Not a boolean column
+                serde_json::json!(1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_boolean_columns_come_back_as_booleans() {
+        let connection = sqlite_test_connection("booleans");
+        let pool_manager = PoolManager::new();
+        let settings = settings::AppSettings::default();
+        let setup = "CREATE TABLE t (id INTEGER, flag BOOLEAN); INSERT INTO t VALUES (0, 0), (1, 1), (2, 7), (3, NULL)";
+        execute_statement(&connection, None, setup, &pool_manager).await.unwrap();
+
+        let mut result = attempt_query(&connection, None, "SELECT id, flag FROM t ORDER BY id", &settings, &pool_manager, 10)
+            .await
+            .map_err(String::from)
+            .unwrap();
+        normalize_booleans(&mut result);
+        let flags: Vec<serde_json::Value> = result.rows.iter().map(|row| row[1].clone()).collect();
+        assert_eq!(
+            flags,
+            vec![serde_json::json!(false), serde_json::json!(true), serde_json::json!(7), serde_json::Value::Null]
+        );
+        // Integer columns keep their 0 and 1
+        assert_eq!(result.rows[1][0], serde_json::json!(1));
+    }
+
+    fn numbers(count: i32) -> impl futures_util::Stream<Item = Result<i32, sqlx::Error>> + Unpin {
+        futures_util::stream::iter((0..count).map(Ok))
+    }
+
+    #[tokio::test]
+    async fn fetch_limited_stops_at_max_rows() {
+        assert_eq!(fetch_limited(numbers(10), 3).await.unwrap(), (vec![0, 1, 2], true));
+        // Exactly max_rows rows left nothing behind
+        assert_eq!(fetch_limited(numbers(3), 3).await.unwrap(), (vec![0, 1, 2], false));
+        assert_eq!(fetch_limited(numbers(2), 3).await.unwrap(), (vec![0, 1], false));
+        assert_eq!(fetch_limited(numbers(0), 3).await.unwrap(), (vec![], false));
+    }
+
+    #[tokio::test]
+    async fn sqlite_result_is_capped_at_max_rows() {
+        let connection = sqlite_test_connection("max-rows");
+        let pool_manager = PoolManager::new();
+        let settings = settings::AppSettings::default();
+        let sql = "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 50) SELECT i FROM n";
+
+        let result = attempt_query(&connection, None, sql, &settings, &pool_manager, 20).await.map_err(String::from).unwrap();
+        assert_eq!(result.rows.len(), 20);
+        assert!(result.truncated);
+
+        let result = attempt_query(&connection, None, sql, &settings, &pool_manager, 50).await.map_err(String::from).unwrap();
+        assert_eq!(result.rows.len(), 50);
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn unsigned_past_i64_round_trips_as_text() {
+        assert_eq!(unsigned_to_json(18446744073709551615), serde_json::json!("18446744073709551615"));
+        assert_eq!(unsigned_to_json(i64::MAX as u64 + 1), serde_json::json!("9223372036854775808"));
+        assert_eq!(unsigned_to_json(i64::MAX as u64), serde_json::json!(i64::MAX));
+        assert_eq!(unsigned_to_json(42), serde_json::json!(42));
+    }
+
+    #[tokio::test]
+    async fn removing_a_connection_drops_its_slots() {
+        let query_queue = QueryQueue::new();
+        drop(query_queue.acquire("c1").await);
+        query_queue.remove("c1");
+        assert!(query_queue.slots.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::connections::{create_mssql_client, load_connections, Connection, ConnectionConfig};
+use crate::db::execute::{convert_limit_to_top, run_query_with_limit};
+use crate::db::pool_manager::PoolManager;
+use crate::db::session::ActiveDatabases;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanFormat {
+    /// `plan` is the server's JSON plan tree
+    Json,
+    /// `plan` is `{ "lines": [...] }`, one line of text plan per entry
+    Text,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainPlan {
+    pub format: PlanFormat,
+    pub plan: serde_json::Value,
+}
+
+/// A JSON cell as a value: Postgres returns json, MySQL a string holding it
+fn json_cell(value: Option<&serde_json::Value>) -> Result<serde_json::Value, String> {
+    match value {
+        Some(serde_json::Value::String(text)) => {
+            serde_json::from_str(text).map_err(|e| format!("Failed to parse plan: {}", e))
+        }
+        Some(value) => Ok(value.clone()),
+        None => Err("The server returned no plan".to_string()),
+    }
+}
+
+/// Estimated plan of an MSSQL query. SHOWPLAN_TEXT has to be set in a batch
+/// of its own, and the query is then compiled but not run.
+async fn mssql_plan_lines(connection: &Connection, database: Option<&str>, sql: &str) -> Result<Vec<String>, String> {
+    let db_name = match &connection.config {
+        ConnectionConfig::Mssql { database: config_db, .. } => database.or(config_db.as_deref()),
+        _ => return Err("无效的 MSSQL 配置".to_string()),
+    };
+    let mut client = create_mssql_client(&connection.config, db_name).await?;
+    client
+        .simple_query("SET SHOWPLAN_TEXT ON")
+        .await
+        .map_err(|e| format!("SQL 执行失败: {}", e))?
+        .into_results()
+        .await
+        .map_err(|e| format!("SQL 执行失败: {}", e))?;
+    let results = client
+        .simple_query(convert_limit_to_top(sql))
+        .await
+        .map_err(|e| format!("SQL 执行失败: {}", e))?
+        .into_results()
+        .await
+        .map_err(|e| format!("读取结果失败: {}", e))?;
+
+    // The first result set echoes the statement; the following ones are the plan
+    Ok(results
+        .iter()
+        .skip(1)
+        .flatten()
+        .filter_map(|row| row.try_get::<&str, _>("StmtText").ok().flatten())
+        .map(|s| s.to_string())
+        .collect())
+}
+
+/// Execution plan of a query as a tree the frontend can draw. Postgres
+/// (`EXPLAIN (FORMAT JSON)`) and MySQL (`EXPLAIN FORMAT=JSON`) return the
+/// JSON plan; SQLite and MSSQL return their text plan line by line.
+#[tauri::command]
+pub async fn explain_query_json(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<ExplainPlan, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let database = app.state::<ActiveDatabases>().resolve(connection, database).await;
+
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if sql.is_empty() {
+        return Err("SQL is required".to_string());
+    }
+
+    let (explain_sql, json) = match connection.db_type.as_str() {
+        "postgres" => (format!("EXPLAIN (FORMAT JSON) {}", sql), true),
+        "mysql" => (format!("EXPLAIN FORMAT=JSON {}", sql), true),
+        "sqlite" => (format!("EXPLAIN QUERY PLAN {}", sql), false),
+        _ => {
+            let lines = mssql_plan_lines(connection, database.as_deref(), sql).await?;
+            return Ok(ExplainPlan { format: PlanFormat::Text, plan: serde_json::json!({ "lines": lines }) });
+        }
+    };
+
+    let result = run_query_with_limit(connection, database.as_deref(), &explain_sql, &app, &pool_manager, usize::MAX).await?;
+    if json {
+        let plan = json_cell(result.rows.first().and_then(|row| row.first()))?;
+        return Ok(ExplainPlan { format: PlanFormat::Json, plan });
+    }
+
+    // EXPLAIN QUERY PLAN rows are (id, parent, notused, detail)
+    let detail = result.columns.iter().position(|c| c == "detail").unwrap_or(result.columns.len().saturating_sub(1));
+    let lines: Vec<&str> = result
+        .rows
+        .iter()
+        .filter_map(|row| row.get(detail).and_then(|v| v.as_str()))
+        .collect();
+    Ok(ExplainPlan { format: PlanFormat::Text, plan: serde_json::json!({ "lines": lines }) })
+}
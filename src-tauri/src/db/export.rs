@@ -0,0 +1,341 @@
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde::Deserialize;
+use tauri::State;
+use crate::db::connections::load_connections;
+use crate::db::execute::{run_query, QueryResult};
+use crate::db::pool_manager::PoolManager;
+use crate::db::progress::ProgressReporter;
+use crate::db::settings;
+use crate::error::AppError;
+
+/// Render a result value as CSV field text; NULL becomes `null_token`
+fn value_to_csv_field(value: &serde_json::Value, null_token: &str) -> String {
+    match value {
+        serde_json::Value::Null => null_token.to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Run a query and write its result set to a CSV file.
+///
+/// Returns the number of rows written; progress is reported on
+/// `progress_channel` when given.
+#[tauri::command]
+pub async fn export_query_result_csv(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    file_path: String,
+    progress_channel: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let mut progress = ProgressReporter::new(&app, progress_channel);
+    progress.update("querying", 0);
+
+    let result = run_query(connection, database.as_deref(), &sql, &app, &pool_manager).await?;
+    progress.set_total(result.rows.len() as u64);
+    let null_token = settings::load_settings(&app).null_token;
+
+    let mut writer = csv::Writer::from_path(&file_path)
+        .map_err(|e| AppError::Export(format!("Failed to create {}: {}", file_path, e)))?;
+    let written = write_csv(&mut writer, &result, &null_token, |written| progress.update("writing", written))?;
+
+    progress.finish(written);
+    Ok(written)
+}
+
+/// Write a result set as CSV under its unique column keys, so repeated
+/// column names each keep their column. `on_row` gets the rows written so far.
+fn write_csv<W: std::io::Write>(
+    writer: &mut csv::Writer<W>,
+    result: &QueryResult,
+    null_token: &str,
+    mut on_row: impl FnMut(u64),
+) -> Result<u64, AppError> {
+    writer
+        .write_record(&result.column_keys)
+        .map_err(|e| AppError::Export(e.to_string()))?;
+
+    let mut written = 0;
+    for row in &result.rows {
+        writer
+            .write_record(row.iter().map(|value| value_to_csv_field(value, null_token)))
+            .map_err(|e| AppError::Export(e.to_string()))?;
+        written += 1;
+        on_row(written);
+    }
+    writer.flush().map_err(AppError::from)?;
+    Ok(written)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    Markdown,
+    Html,
+}
+
+/// Escape a cell for a Markdown table: pipes end the cell and newlines the row
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Render a query result as a Markdown or HTML table, for pasting into docs
+/// and tickets. NULLs are written as the `null_token` setting; `null_marker`
+/// overrides it with `NULL` (true) or an empty cell (false).
+#[tauri::command]
+pub async fn format_result_as(
+    result: QueryResult,
+    format: TableFormat,
+    null_marker: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let null_token = match null_marker {
+        Some(true) => "NULL".to_string(),
+        Some(false) => String::new(),
+        None => settings::load_settings(&app).null_token,
+    };
+    if result.columns.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut out = String::new();
+    match format {
+        TableFormat::Markdown => {
+            let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+            out.push_str(&line(result.columns.iter().map(|c| escape_markdown_cell(c)).collect()));
+            out.push_str(&line(vec!["---".to_string(); result.columns.len()]));
+            for row in &result.rows {
+                out.push_str(&line(
+                    row.iter()
+                        .map(|v| escape_markdown_cell(&value_to_csv_field(v, &null_token)))
+                        .collect(),
+                ));
+            }
+        }
+        TableFormat::Html => {
+            out.push_str("<table>\n  <thead>\n    <tr>");
+            for column in &result.columns {
+                out.push_str(&format!("<th>{}</th>", escape_html(column)));
+            }
+            out.push_str("</tr>\n  </thead>\n  <tbody>\n");
+            for row in &result.rows {
+                out.push_str("    <tr>");
+                for value in row {
+                    out.push_str(&format!("<td>{}</td>", escape_html(&value_to_csv_field(value, &null_token))));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("  </tbody>\n</table>\n");
+        }
+    }
+    Ok(out)
+}
+
+// Excel limits: rows per sheet (including the header) and characters per cell
+const XLSX_MAX_ROWS: usize = 1_048_576;
+const XLSX_MAX_CELL_CHARS: usize = 32_767;
+
+/// Parse a date/time rendered by the query layer so it can be stored as an Excel date
+fn parse_excel_datetime(text: &str) -> Option<chrono::NaiveDateTime> {
+    // DateTime<Utc> values render with a trailing " UTC"
+    let text = text.trim_end_matches(" UTC");
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|f| chrono::NaiveDateTime::parse_from_str(text, f).ok())
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn write_xlsx_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: &serde_json::Value,
+    is_date_column: bool,
+    date_format: &Format,
+) -> Result<(), XlsxError> {
+    match value {
+        serde_json::Value::Null => {}
+        serde_json::Value::Bool(b) => {
+            worksheet.write_boolean(row, col, *b)?;
+        }
+        serde_json::Value::Number(n) => match n.as_f64() {
+            Some(f) => {
+                worksheet.write_number(row, col, f)?;
+            }
+            None => {
+                worksheet.write_string(row, col, n.to_string())?;
+            }
+        },
+        serde_json::Value::String(s) => match parse_excel_datetime(s).filter(|_| is_date_column) {
+            Some(dt) => {
+                worksheet.write_datetime_with_format(row, col, dt, date_format)?;
+            }
+            None if s.chars().count() > XLSX_MAX_CELL_CHARS => {
+                let cut: String = s.chars().take(XLSX_MAX_CELL_CHARS).collect();
+                worksheet.write_string(row, col, cut)?;
+            }
+            None => {
+                worksheet.write_string(row, col, s)?;
+            }
+        },
+        other => {
+            worksheet.write_string(row, col, other.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a query and write its result set to an Excel workbook.
+///
+/// Headers form a bold, frozen first row; numbers, booleans and date/time
+/// columns keep their Excel types and NULL cells stay empty. Returns the number
+/// of rows written; progress is reported on `progress_channel` when given.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn export_query_result_xlsx(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    file_path: String,
+    sheet_name: Option<String>,
+    progress_channel: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let mut progress = ProgressReporter::new(&app, progress_channel);
+    progress.update("querying", 0);
+
+    let result = run_query(connection, database.as_deref(), &sql, &app, &pool_manager).await?;
+    if result.rows.len() >= XLSX_MAX_ROWS {
+        return Err(AppError::Export(format!(
+            "{} rows exceed the Excel limit of {} rows per sheet",
+            result.rows.len(),
+            XLSX_MAX_ROWS - 1
+        )).into());
+    }
+    progress.set_total(result.rows.len() as u64);
+
+    let xlsx_err = |e: XlsxError| AppError::Export(e.to_string());
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+    let date_columns: Vec<bool> = (0..result.columns.len())
+        .map(|i| {
+            result
+                .column_types
+                .get(i)
+                .is_some_and(|t| t.contains("date") || t.contains("time"))
+        })
+        .collect();
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    if let Some(name) = sheet_name.as_deref().filter(|n| !n.is_empty()) {
+        worksheet.set_name(name).map_err(xlsx_err)?;
+    }
+
+    for (col, name) in result.column_keys.iter().enumerate() {
+        worksheet
+            .write_string_with_format(0, col as u16, name, &header_format)
+            .map_err(xlsx_err)?;
+    }
+    worksheet.set_freeze_panes(1, 0).map_err(xlsx_err)?;
+
+    let mut written = 0;
+    for (row_index, row) in result.rows.iter().enumerate() {
+        for (col, value) in row.iter().enumerate() {
+            write_xlsx_cell(
+                worksheet,
+                row_index as u32 + 1,
+                col as u16,
+                value,
+                date_columns[col],
+                &date_format,
+            ).map_err(xlsx_err)?;
+        }
+        written += 1;
+        progress.update("writing", written);
+    }
+
+    workbook
+        .save(&file_path)
+        .map_err(|e| AppError::Export(format!("Failed to write {}: {}", file_path, e)))?;
+
+    progress.finish(written);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::execute::unique_column_keys;
+
+    fn csv_line(row: &[serde_json::Value], null_token: &str) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(row.iter().map(|value| value_to_csv_field(value, null_token))).unwrap();
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn null_is_written_as_each_token() {
+        let row = [serde_json::Value::Null, serde_json::json!("text"), serde_json::json!(1)];
+        assert_eq!(csv_line(&row, ""), ",text,1\n");
+        assert_eq!(csv_line(&row, "NULL"), "NULL,text,1\n");
+        assert_eq!(csv_line(&row, "\\N"), "\\N,text,1\n");
+    }
+
+    #[test]
+    fn repeated_column_names_keep_both_columns() {
+        let columns = vec!["id".to_string(), "id".to_string()];
+        let result = QueryResult {
+            column_keys: unique_column_keys(&columns),
+            columns,
+            rows: vec![vec![serde_json::json!(1), serde_json::json!(10)]],
+            ..Default::default()
+        };
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        assert_eq!(write_csv(&mut writer, &result, "", |_| {}).unwrap(), 1);
+        assert_eq!(String::from_utf8(writer.into_inner().unwrap()).unwrap(), "id,id_2\n1,10\n");
+
+        // The result sent to the grid keys both columns apart as well
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["columns"], serde_json::json!(["id", "id"]));
+        assert_eq!(json["column_keys"], serde_json::json!(["id", "id_2"]));
+    }
+
+    #[test]
+    fn values_are_not_replaced_by_the_token() {
+        let row = [serde_json::json!(""), serde_json::json!("NULL"), serde_json::json!(true), serde_json::json!(1.5)];
+        assert_eq!(csv_line(&row, "\\N"), ",NULL,true,1.5\n");
+    }
+}
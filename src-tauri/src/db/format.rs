@@ -0,0 +1,153 @@
+use serde::Serialize;
+use sqlformat::{FormatOptions, Indent, QueryParams};
+use crate::db::column_source::{tokenize_spans, Token};
+use crate::db::dialect::is_sql_keyword;
+use crate::db::settings::{self, KeywordCase};
+
+/// Cheap sanity check before reformatting: quotes, comments and brackets must be
+/// closed and parentheses balanced. Anything else is left for the server to reject.
+fn is_well_formed(sql: &str, dialect: &str) -> bool {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut depth: i32 = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let closing = match c {
+            '\'' | '"' => Some(c),
+            '`' if dialect == "mysql" => Some('`'),
+            '[' if dialect == "mssql" => Some(']'),
+            _ => None,
+        };
+
+        if let Some(close) = closing {
+            // Skip to the closing quote; doubled quotes are escapes
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return false,
+                    Some(&ch) if ch == close => {
+                        if chars.get(i + 1) == Some(&close) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    Some(_) => i += 1,
+                }
+            }
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            i += 2;
+            while !(chars.get(i) == Some(&'*') && chars.get(i + 1) == Some(&'/')) {
+                if i >= chars.len() {
+                    return false;
+                }
+                i += 1;
+            }
+            i += 1;
+        } else if c == '(' {
+            depth += 1;
+        } else if c == ')' {
+            depth -= 1;
+            if depth < 0 {
+                return false;
+            }
+        }
+        i += 1;
+    }
+    depth == 0
+}
+
+/// `sql` with the keywords of the dialect in lower case. Quoted names,
+/// string literals and comments are left alone.
+fn lowercase_keywords(sql: &str, dialect: &str) -> String {
+    let mut out = sql.to_string();
+    for (token, span) in tokenize_spans(dialect, sql) {
+        if matches!(&token, Token::Word(word) if is_sql_keyword(dialect, word)) {
+            // ASCII keywords keep their byte length, so later spans still line up
+            out.replace_range(span.clone(), &sql[span].to_ascii_lowercase());
+        }
+    }
+    out
+}
+
+/// Reformat SQL with consistent keyword casing, indentation and clause breaks.
+/// Keyword case and indent width come from the settings. SQL that does not
+/// look well formed is returned unchanged. `dialect` selects the quoting
+/// rules of that check and the keywords lowercased in `lower` case.
+#[tauri::command]
+pub async fn format_sql(
+    sql: String,
+    dialect: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let dialect = dialect.as_deref().unwrap_or("");
+    if !is_well_formed(&sql, dialect) {
+        return Ok(sql);
+    }
+
+    let settings = settings::load_settings(&app);
+    let options = FormatOptions {
+        indent: Indent::Spaces(settings.format_indent_width),
+        uppercase: settings.format_keyword_case == KeywordCase::Upper,
+        lines_between_queries: 2,
+    };
+    let formatted = sqlformat::format(&sql, &QueryParams::None, options);
+    // sqlformat can only uppercase keywords or keep them
+    Ok(match settings.format_keyword_case {
+        KeywordCase::Lower => lowercase_keywords(&formatted, dialect),
+        _ => formatted,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonCell {
+    /// The value parsed as JSON
+    pub valid: bool,
+    /// Pretty-printed JSON, or the value unchanged when it is not valid
+    pub formatted: String,
+    /// Why parsing failed, with line and column
+    pub error: Option<String>,
+}
+
+/// Pretty-print a cell holding JSON for the "view as JSON" panel. A text
+/// column's value arrives as a string and is parsed; Postgres json/jsonb
+/// values arrive already structured and are printed as they are.
+#[tauri::command]
+pub async fn format_json_cell(value: serde_json::Value) -> Result<JsonCell, String> {
+    let parsed = match value {
+        serde_json::Value::String(text) => match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                return Ok(JsonCell {
+                    valid: false,
+                    formatted: text,
+                    error: Some(e.to_string()),
+                })
+            }
+        },
+        structured => structured,
+    };
+    let formatted = serde_json::to_string_pretty(&parsed).map_err(|e| e.to_string())?;
+    Ok(JsonCell { valid: true, formatted, error: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowercases_only_keywords() {
+        assert_eq!(
+            lowercase_keywords("SELECT Name, 'FROM' FROM \"SELECT\" WHERE id IN (1) -- WHERE", "postgres"),
+            "select Name, 'FROM' from \"SELECT\" where id in (1) -- WHERE"
+        );
+        assert_eq!(lowercase_keywords("SELECT TOP 5 [KEY] FROM t", "mssql"), "select top 5 [KEY] from t");
+        // TOP is no keyword in PostgreSQL
+        assert_eq!(lowercase_keywords("SELECT TOP FROM t", "postgres"), "select TOP from t");
+    }
+}
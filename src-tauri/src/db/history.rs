@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 use chrono::Utc;
+use crate::db::store::write_json_atomic;
 use crate::db::settings;
+use crate::db::sql_log::append_sql_log;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SqlHistory {
@@ -15,6 +18,9 @@ pub struct SqlHistory {
     pub success: bool,
     pub error_message: Option<String>,
     pub rows_affected: Option<u64>,
+    /// Wall-clock execution time, for successful and failed runs alike
+    #[serde(default)]
+    pub elapsed_ms: Option<u64>,
 }
 
 pub(crate) fn get_history_path(app: &AppHandle) -> PathBuf {
@@ -24,10 +30,39 @@ pub(crate) fn get_history_path(app: &AppHandle) -> PathBuf {
         .join("sql_history.json")
 }
 
-pub(crate) fn load_history(app: &AppHandle) -> Vec<SqlHistory> {
-    let path = get_history_path(app);
+/// Directory of the per-connection history files
+fn get_history_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("Failed to get app data directory")
+        .join("history")
+}
+
+/// `history/{connection_id}.json`, with anything but letters, digits, `-`
+/// and `_` in the id replaced so it cannot leave the directory
+fn get_connection_history_path(app: &AppHandle, connection_id: &str) -> PathBuf {
+    let file_name: String = connection_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    get_history_dir(app).join(format!("{}.json", file_name))
+}
+
+/// Every per-connection history file
+pub(crate) fn get_connection_history_paths(app: &AppHandle) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(get_history_dir(app)) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect()
+}
+
+fn read_history_file(path: &Path) -> Vec<SqlHistory> {
     if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
+        if let Ok(content) = fs::read_to_string(path) {
             if let Ok(history) = serde_json::from_str::<Vec<SqlHistory>>(&content) {
                 return history;
             }
@@ -36,17 +71,102 @@ pub(crate) fn load_history(app: &AppHandle) -> Vec<SqlHistory> {
     vec![]
 }
 
+/// The history of every connection, newest first
+pub(crate) fn load_history(app: &AppHandle) -> Vec<SqlHistory> {
+    if !settings::load_settings(app).per_connection_history {
+        return read_history_file(&get_history_path(app));
+    }
+    let mut history: Vec<SqlHistory> = get_connection_history_paths(app)
+        .iter()
+        .flat_map(|path| read_history_file(path))
+        .collect();
+    // RFC 3339 timestamps in UTC sort in time order
+    history.sort_by(|a, b| b.executed_at.cmp(&a.executed_at));
+    history
+}
+
+/// The history of one connection; with per-connection history only its file is read
+fn load_connection_history(app: &AppHandle, connection_id: &str) -> Vec<SqlHistory> {
+    if settings::load_settings(app).per_connection_history {
+        return read_history_file(&get_connection_history_path(app, connection_id));
+    }
+    let mut history = read_history_file(&get_history_path(app));
+    history.retain(|h| h.connection_id == connection_id);
+    history
+}
+
+/// Save history entries to wherever the current mode keeps them. With
+/// per-connection history the entries are grouped into their connections'
+/// files, and files of connections left without entries are removed.
 pub(crate) fn save_history(app: &AppHandle, history: &[SqlHistory]) -> Result<(), String> {
-    let path = get_history_path(app);
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    if !settings::load_settings(app).per_connection_history {
+        return write_json_atomic(&get_history_path(app), history);
+    }
+
+    let mut by_connection: BTreeMap<&str, Vec<&SqlHistory>> = BTreeMap::new();
+    for item in history {
+        by_connection.entry(item.connection_id.as_str()).or_default().push(item);
+    }
+    let mut written = Vec::new();
+    for (connection_id, items) in by_connection {
+        let path = get_connection_history_path(app, connection_id);
+        write_json_atomic(&path, &items)?;
+        written.push(path);
+    }
+    for path in get_connection_history_paths(app) {
+        if !written.contains(&path) {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Move the history between the combined `sql_history.json` and the
+/// per-connection files when `per_connection_history` changes. Entries are
+/// read in the old layout and written in the new one, then the old files go.
+pub(crate) fn migrate_history(app: &AppHandle, per_connection: bool) -> Result<(), String> {
+    let combined_path = get_history_path(app);
+    if per_connection {
+        let mut history = read_history_file(&combined_path);
+        // Keep anything already in per-connection files from an earlier switch
+        for path in get_connection_history_paths(app) {
+            history.extend(read_history_file(&path));
+        }
+        history.sort_by(|a, b| b.executed_at.cmp(&a.executed_at));
+        let max = settings::load_settings(app).max_history_count;
+        let mut by_connection: BTreeMap<String, Vec<SqlHistory>> = BTreeMap::new();
+        for item in history {
+            let items = by_connection.entry(item.connection_id.clone()).or_default();
+            if items.len() < max {
+                items.push(item);
+            }
+        }
+        for (connection_id, items) in by_connection {
+            write_json_atomic(&get_connection_history_path(app, &connection_id), &items)?;
+        }
+        if combined_path.exists() {
+            fs::remove_file(&combined_path).map_err(|e| format!("Failed to remove {}: {}", combined_path.display(), e))?;
+        }
+    } else {
+        let paths = get_connection_history_paths(app);
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let mut history = read_history_file(&combined_path);
+        for path in &paths {
+            history.extend(read_history_file(path));
+        }
+        history.sort_by(|a, b| b.executed_at.cmp(&a.executed_at));
+        history.truncate(settings::load_settings(app).max_history_count);
+        write_json_atomic(&combined_path, &history)?;
+        for path in paths {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
     }
-    let content = serde_json::to_string_pretty(history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))?;
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn add_sql_history(
     connection_id: String,
@@ -55,6 +175,7 @@ pub async fn add_sql_history(
     success: bool,
     error_message: Option<String>,
     rows_affected: Option<u64>,
+    elapsed_ms: Option<u64>,
     app: AppHandle,
 ) -> Result<String, String> {
     let id = uuid::Uuid::new_v4().to_string();
@@ -69,18 +190,37 @@ pub async fn add_sql_history(
         success,
         error_message,
         rows_affected,
+        elapsed_ms,
     };
 
-    let mut history = load_history(&app);
-    history.insert(0, history_item); // Insert at the beginning
-    
     // Get max history count from settings
     let settings = settings::load_settings(&app);
+
+    // The log is for debugging; failing to write it must not fail the statement
+    if let Some(log_path) = &settings.sql_log_path {
+        if let Err(e) = append_sql_log(log_path, settings.sql_log_max_bytes, &history_item) {
+            eprintln!("{}", e);
+        }
+    }
+
+    // With per-connection history only this connection's file is rewritten,
+    // and the cap applies to it alone
+    let (path, mut history) = if settings.per_connection_history {
+        let path = get_connection_history_path(&app, &history_item.connection_id);
+        let history = read_history_file(&path);
+        (path, history)
+    } else {
+        let path = get_history_path(&app);
+        let history = read_history_file(&path);
+        (path, history)
+    };
+    history.insert(0, history_item); // Insert at the beginning
+    
     if history.len() > settings.max_history_count {
         history.truncate(settings.max_history_count);
     }
     
-    save_history(&app, &history)?;
+    write_json_atomic(&path, &history)?;
     Ok(id)
 }
 
@@ -90,12 +230,10 @@ pub async fn get_sql_history(
     limit: Option<usize>,
     app: AppHandle,
 ) -> Result<Vec<SqlHistory>, String> {
-    let mut history = load_history(&app);
-    
-    // Filter by connection_id if provided
-    if let Some(conn_id) = connection_id {
-        history.retain(|h| h.connection_id == conn_id);
-    }
+    let mut history = match connection_id {
+        Some(conn_id) => load_connection_history(&app, &conn_id),
+        None => load_history(&app),
+    };
     
     // Apply limit
     if let Some(limit) = limit {
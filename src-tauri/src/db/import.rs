@@ -0,0 +1,315 @@
+use tauri::{Manager, State};
+use crate::db::bulk::insert_rows;
+use crate::db::connections::{describe_table, load_connections};
+use crate::db::quote::quote_ident;
+use crate::db::execute::execute_statement;
+use crate::db::pool_manager::PoolManager;
+use crate::db::progress::ProgressReporter;
+use crate::db::query_cache::QueryCache;
+use crate::error::AppError;
+
+// Rows sent to the database per insert batch
+const IMPORT_BATCH_SIZE: usize = 1000;
+// Rows sampled to infer column types when creating the table
+const TYPE_INFERENCE_ROWS: usize = 100;
+
+/// Value category used to coerce CSV text before inserting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Integer,
+    Float,
+    Boolean,
+    Text,
+}
+
+impl ValueKind {
+    /// Categorize an existing column by its declared data type
+    fn from_data_type(data_type: &str) -> ValueKind {
+        let t = data_type.to_lowercase();
+        if t.contains("bool") || t == "bit" {
+            ValueKind::Boolean
+        } else if t.contains("int") && !t.contains("interval") && !t.contains("point") {
+            ValueKind::Integer
+        } else if t.contains("real") || t.contains("float") || t.contains("double") {
+            ValueKind::Float
+        } else {
+            // decimal/numeric, dates and text are passed through as strings
+            // and converted by the server
+            ValueKind::Text
+        }
+    }
+
+    /// Column type used when creating a table for the given database type
+    fn sql_type(self, db_type: &str) -> &'static str {
+        match (self, db_type) {
+            (ValueKind::Integer, "sqlite") => "INTEGER",
+            (ValueKind::Integer, _) => "BIGINT",
+            (ValueKind::Float, "sqlite") => "REAL",
+            (ValueKind::Float, "postgres") => "DOUBLE PRECISION",
+            (ValueKind::Float, "mssql") => "FLOAT",
+            (ValueKind::Float, _) => "DOUBLE",
+            (ValueKind::Boolean, "mssql") => "BIT",
+            (ValueKind::Boolean, _) => "BOOLEAN",
+            (ValueKind::Text, "mssql") => "NVARCHAR(MAX)",
+            (ValueKind::Text, _) => "TEXT",
+        }
+    }
+}
+
+fn parse_bool(field: &str) -> Option<bool> {
+    match field.to_lowercase().as_str() {
+        "true" | "t" | "yes" | "1" => Some(true),
+        "false" | "f" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Pick the narrowest kind that every non-empty sample value fits
+fn infer_kind<'a>(samples: impl Iterator<Item = &'a str>) -> ValueKind {
+    let values: Vec<&str> = samples.filter(|v| !v.is_empty()).collect();
+    if values.is_empty() {
+        ValueKind::Text
+    } else if values.iter().all(|v| v.parse::<i64>().is_ok()) {
+        ValueKind::Integer
+    } else if values.iter().all(|v| v.parse::<f64>().is_ok()) {
+        ValueKind::Float
+    } else if values.iter().all(|v| matches!(v.to_lowercase().as_str(), "true" | "false")) {
+        ValueKind::Boolean
+    } else {
+        ValueKind::Text
+    }
+}
+
+/// Convert one CSV field to a JSON value of the target kind; empty fields become NULL
+fn coerce_field(field: &str, kind: ValueKind) -> Result<serde_json::Value, String> {
+    if field.is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    match kind {
+        ValueKind::Integer => field
+            .trim()
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| format!("'{}' is not an integer", field)),
+        ValueKind::Float => field
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("'{}' is not a number", field)),
+        ValueKind::Boolean => parse_bool(field.trim())
+            .map(serde_json::Value::Bool)
+            .ok_or_else(|| format!("'{}' is not a boolean", field)),
+        ValueKind::Text => Ok(serde_json::Value::String(field.to_string())),
+    }
+}
+
+/// Convert a CSV record into a row, reporting the line and column on failure
+fn record_to_row(
+    record: &csv::StringRecord,
+    columns: &[String],
+    kinds: &[ValueKind],
+) -> Result<Vec<serde_json::Value>, String> {
+    let line = record.position().map(|p| p.line()).unwrap_or(0);
+    columns
+        .iter()
+        .zip(kinds)
+        .enumerate()
+        .map(|(i, (column, kind))| {
+            coerce_field(record.get(i).unwrap_or(""), *kind)
+                .map_err(|e| format!("line {}, column {}: {}", line, column, e))
+        })
+        .collect()
+}
+
+/// Import a CSV file into a table.
+///
+/// With `create_table` the table is created from column types inferred from the
+/// first rows; otherwise CSV columns are mapped onto the existing table (by header
+/// name, or by position without a header). Rows are inserted in batches, with
+/// progress reported on `progress_channel` when given. If a row fails, a
+/// table created for the import is dropped again; otherwise the error tells
+/// how many rows the committed batches already imported.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn import_csv(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    file_path: String,
+    has_header: bool,
+    delimiter: Option<String>,
+    create_table: bool,
+    progress_channel: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<u64, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let mut progress = ProgressReporter::new(&app, progress_channel);
+    progress.update("reading", 0);
+
+    let delimiter = match delimiter.as_deref() {
+        None | Some("") => b',',
+        Some(d) if d.len() == 1 => d.as_bytes()[0],
+        Some(d) => return Err(AppError::CsvImport(format!("Invalid delimiter: {}", d)).into()),
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_header)
+        .from_path(&file_path)
+        .map_err(|e| AppError::CsvImport(format!("Failed to open {}: {}", file_path, e)))?;
+
+    let headers: Option<Vec<String>> = if has_header {
+        let headers = reader
+            .headers()
+            .map_err(|e| AppError::CsvImport(e.to_string()))?;
+        Some(headers.iter().map(|h| h.trim().to_string()).collect())
+    } else {
+        None
+    };
+
+    let mut records = reader.into_records();
+
+    // Read ahead the rows used for type inference (and the field count without a header)
+    let mut sample = Vec::new();
+    for record in records.by_ref().take(TYPE_INFERENCE_ROWS) {
+        sample.push(record.map_err(|e| AppError::CsvImport(e.to_string()))?);
+    }
+
+    let field_count = headers
+        .as_ref()
+        .map(|h| h.len())
+        .or_else(|| sample.first().map(|r| r.len()))
+        .unwrap_or(0);
+    if field_count == 0 {
+        return Err(AppError::CsvImport("The file contains no columns".to_string()).into());
+    }
+
+    let (columns, kinds): (Vec<String>, Vec<ValueKind>) = if create_table {
+        let columns: Vec<String> = headers
+            .clone()
+            .unwrap_or_else(|| (1..=field_count).map(|i| format!("col{}", i)).collect());
+        let kinds: Vec<ValueKind> = (0..field_count)
+            .map(|i| infer_kind(sample.iter().map(move |r| r.get(i).unwrap_or(""))))
+            .collect();
+
+        let column_defs = columns
+            .iter()
+            .zip(&kinds)
+            .map(|(c, k)| format!("{} {}", quote_ident(&connection.db_type, c), k.sql_type(&connection.db_type)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let create_sql = format!(
+            "CREATE TABLE {} ({})",
+            quote_ident(&connection.db_type, &table_name),
+            column_defs
+        );
+        execute_statement(connection, database.as_deref(), &create_sql, &pool_manager).await?;
+
+        (columns, kinds)
+    } else {
+        let table_columns = describe_table(
+            connection_id.clone(),
+            table_name.clone(),
+            database.clone(),
+            None,
+            app.clone(),
+            pool_manager.clone(),
+        ).await?;
+
+        match &headers {
+            Some(headers) => headers
+                .iter()
+                .map(|h| {
+                    table_columns
+                        .iter()
+                        .find(|c| c.name.eq_ignore_ascii_case(h))
+                        .map(|c| (c.name.clone(), ValueKind::from_data_type(&c.data_type)))
+                        .ok_or_else(|| {
+                            AppError::CsvImport(format!("Column '{}' does not exist in table {}", h, table_name)).into()
+                        })
+                })
+                .collect::<Result<Vec<_>, String>>()?
+                .into_iter()
+                .unzip(),
+            None => {
+                if field_count > table_columns.len() {
+                    return Err(AppError::CsvImport(format!(
+                        "The file has {} columns but table {} only has {}",
+                        field_count,
+                        table_name,
+                        table_columns.len()
+                    )).into());
+                }
+                table_columns
+                    .iter()
+                    .take(field_count)
+                    .map(|c| (c.name.clone(), ValueKind::from_data_type(&c.data_type)))
+                    .unzip()
+            }
+        }
+    };
+
+    let mut imported = 0;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut batch_first_line = 0;
+    let mut pending = sample.into_iter().map(Ok).chain(records);
+    let failure = loop {
+        let next = pending.next();
+        if let Some(record) = &next {
+            let row = match record {
+                Ok(record) => record_to_row(record, &columns, &kinds),
+                Err(e) => Err(e.to_string()),
+            };
+            match row {
+                Ok(row) => {
+                    if batch.is_empty() {
+                        batch_first_line = record.as_ref().ok().and_then(|r| r.position()).map_or(0, |p| p.line());
+                    }
+                    batch.push(row);
+                }
+                Err(e) => break Some(e),
+            }
+        }
+
+        if batch.len() >= IMPORT_BATCH_SIZE || (next.is_none() && !batch.is_empty()) {
+            match insert_rows(connection, database.as_deref(), &table_name, &columns, &batch, &pool_manager).await {
+                Ok(inserted) => imported += inserted,
+                Err(e) => break Some(format!("rows from line {}: {}", batch_first_line, e)),
+            }
+            // Each batch is committed on its own
+            app.state::<QueryCache>().invalidate(&connection.id).await;
+            batch.clear();
+            progress.update("inserting", imported);
+        }
+
+        if next.is_none() {
+            break None;
+        }
+    };
+
+    if let Some(error) = failure {
+        if !create_table {
+            return Err(AppError::CsvImport(format!("{} ({} rows were imported before the error)", error, imported)).into());
+        }
+        // Don't leave a half-filled table behind that the import created
+        let drop_sql = format!("DROP TABLE {}", quote_ident(&connection.db_type, &table_name));
+        let dropped = execute_statement(connection, database.as_deref(), &drop_sql, &pool_manager).await;
+        app.state::<QueryCache>().invalidate(&connection.id).await;
+        let message = match dropped {
+            Ok(_) => format!("{} (table {} was dropped again)", error, table_name),
+            Err(e) => format!("{} ({} rows were imported; dropping table {} failed: {})", error, imported, table_name, e),
+        };
+        return Err(AppError::CsvImport(message).into());
+    }
+
+    progress.finish(imported);
+    Ok(imported)
+}
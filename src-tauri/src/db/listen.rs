@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use serde::Serialize;
+use sqlx::postgres::PgListener;
+use tauri::async_runtime::JoinHandle;
+use tauri::{Emitter, State};
+use crate::db::connections::load_connections;
+use crate::db::pool_manager::{DatabasePool, PoolManager};
+use crate::db::session::ActiveDatabases;
+
+// Event every received notification is emitted on
+const NOTIFICATION_EVENT: &str = "pg-notification";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PgNotificationEvent {
+    pub connection_id: String,
+    pub channel: String,
+    pub payload: String,
+    /// Backend process that sent the NOTIFY
+    pub process_id: u32,
+}
+
+/// Running LISTEN subscriptions by connection id and channel. Each one holds
+/// a connection of its pool until it is stopped, which UNLISTENs it.
+pub struct PgListeners {
+    tasks: Mutex<HashMap<(String, String), JoinHandle<()>>>,
+}
+
+impl PgListeners {
+    pub fn new() -> Self {
+        Self {
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_listening(&self, connection_id: &str, channel: &str) -> bool {
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .get(&(connection_id.to_string(), channel.to_string()))
+            .is_some_and(|task| !task.inner().is_finished())
+    }
+
+    fn insert(&self, connection_id: &str, channel: &str, task: JoinHandle<()>) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(old) = tasks.insert((connection_id.to_string(), channel.to_string()), task) {
+            old.abort();
+        }
+    }
+
+    /// Stop one subscription. Returns false when it was not running.
+    pub fn stop(&self, connection_id: &str, channel: &str) -> bool {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        match tasks.remove(&(connection_id.to_string(), channel.to_string())) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop every subscription of a connection, e.g. when it is deleted
+    pub fn stop_connection(&self, connection_id: &str) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.retain(|(id, _), task| {
+            if id == connection_id {
+                task.abort();
+            }
+            id != connection_id
+        });
+    }
+
+    /// Stop every subscription, e.g. when the app exits
+    pub fn stop_all(&self) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        for (_, task) in tasks.drain() {
+            task.abort();
+        }
+    }
+}
+
+impl Default for PgListeners {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Subscribe to a PostgreSQL NOTIFY channel. Each notification is emitted
+/// as a `pg-notification` event until `unlisten_channel` is called; the
+/// listener reconnects by itself if the connection drops.
+#[tauri::command]
+pub async fn listen_channel(
+    connection_id: String,
+    channel: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
+    listeners: State<'_, PgListeners>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if connection.db_type != "postgres" {
+        return Err("LISTEN is only supported for PostgreSQL".to_string());
+    }
+    let channel = channel.trim().to_string();
+    if channel.is_empty() {
+        return Err("Channel name is required".to_string());
+    }
+    if listeners.is_listening(&connection_id, &channel) {
+        return Ok(());
+    }
+
+    let database = active_databases.resolve(connection, database).await;
+    let pool = match pool_manager.get_or_create_pool(connection, database.as_deref()).await? {
+        DatabasePool::Postgres(p) => p,
+        _ => return Err("LISTEN is only supported for PostgreSQL".to_string()),
+    };
+    let mut listener = PgListener::connect_with(&pool)
+        .await
+        .map_err(|e| format!("Failed to open listener: {}", e))?;
+    // LISTEN quotes the channel, so its case is kept
+    listener
+        .listen(&channel)
+        .await
+        .map_err(|e| format!("Failed to listen on {}: {}", channel, e))?;
+
+    let (task_connection_id, task_channel) = (connection_id.clone(), channel.clone());
+    let task = tauri::async_runtime::spawn(async move {
+        loop {
+            match listener.recv().await {
+                Ok(notification) => {
+                    let _ = app.emit(NOTIFICATION_EVENT, PgNotificationEvent {
+                        connection_id: task_connection_id.clone(),
+                        channel: notification.channel().to_string(),
+                        payload: notification.payload().to_string(),
+                        process_id: notification.process_id(),
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Listener on {} stopped: {}", task_channel, e);
+                    break;
+                }
+            }
+        }
+    });
+    listeners.insert(&connection_id, &channel, task);
+    Ok(())
+}
+
+/// Stop listening on a channel. Returns false when it was not subscribed.
+#[tauri::command]
+pub async fn unlisten_channel(
+    connection_id: String,
+    channel: String,
+    listeners: State<'_, PgListeners>,
+) -> Result<bool, String> {
+    Ok(listeners.stop(&connection_id, channel.trim()))
+}
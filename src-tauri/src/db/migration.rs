@@ -0,0 +1,205 @@
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::connections::{describe_table, load_connections, ColumnInfo, Connection};
+use crate::db::ddl::{column_definition_sql, create_table_sql, mssql_column_type, mysql_column_definition, table_ddl};
+use crate::db::pool_manager::PoolManager;
+use crate::db::quote::quote_ident;
+use crate::db::schema_diff::{ColumnChange, SchemaDiff};
+use crate::db::session::ActiveDatabases;
+
+#[derive(Debug, Serialize)]
+pub struct Migration {
+    /// Statements that add tables and columns or change columns
+    pub statements: Vec<String>,
+    /// Statements that drop tables or columns, listed even when not included
+    pub destructive: Vec<String>,
+    /// Changes that could not be expressed, or need checking by hand
+    pub warnings: Vec<String>,
+    /// The script to run: `statements`, then `destructive` if opted in
+    pub script: String,
+}
+
+/// Columns of a table on side A
+async fn columns_of(
+    connection: &Connection,
+    database: &Option<String>,
+    table_name: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<Vec<ColumnInfo>, String> {
+    describe_table(
+        connection.id.clone(),
+        table_name.to_string(),
+        database.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+    ).await
+}
+
+/// Statements that bring one column on side B in line with side A
+fn alter_column_sql(db_type: &str, table: &str, change: &ColumnChange, same_engine: bool, warnings: &mut Vec<String>) -> Vec<String> {
+    let table_sql = quote_ident(db_type, table);
+    let column_sql = quote_ident(db_type, &change.b.name);
+    let changed = |field: &str| change.fields.iter().any(|f| f == field);
+    // Across engines the target's own type spelling is unknown, so A's is used
+    let data_type = if db_type == "mssql" { mssql_column_type(&change.a.data_type) } else { change.a.data_type.clone() };
+
+    match db_type {
+        "postgres" => {
+            let mut statements = Vec::new();
+            if changed("type") {
+                statements.push(format!("ALTER TABLE {} ALTER COLUMN {} TYPE {}", table_sql, column_sql, data_type));
+            }
+            if changed("nullable") {
+                let action = if change.a.nullable { "DROP NOT NULL" } else { "SET NOT NULL" };
+                statements.push(format!("ALTER TABLE {} ALTER COLUMN {} {}", table_sql, column_sql, action));
+            }
+            if changed("default") {
+                let action = match &change.a.default {
+                    Some(default) => format!("SET DEFAULT {}", default),
+                    None => "DROP DEFAULT".to_string(),
+                };
+                statements.push(format!("ALTER TABLE {} ALTER COLUMN {} {}", table_sql, column_sql, action));
+            }
+            statements
+        }
+        "mysql" => {
+            // MODIFY restates the whole column; keep B's default when A's is in another dialect
+            let column = ColumnInfo {
+                default: if same_engine { change.a.default.clone() } else { change.b.default.clone() },
+                ..change.a.clone()
+            };
+            vec![format!(
+                "ALTER TABLE {} MODIFY COLUMN {} {}",
+                table_sql,
+                column_sql,
+                mysql_column_definition(&column, &data_type)
+            )]
+        }
+        "mssql" => {
+            let mut statements = Vec::new();
+            if changed("type") || changed("nullable") {
+                let data_type = if changed("type") { data_type } else { mssql_column_type(&change.b.data_type) };
+                statements.push(format!(
+                    "ALTER TABLE {} ALTER COLUMN {} {} {}",
+                    table_sql,
+                    column_sql,
+                    data_type,
+                    if change.a.nullable { "NULL" } else { "NOT NULL" }
+                ));
+            }
+            if changed("default") {
+                // Defaults are named constraints, which have to be dropped and re-added
+                warnings.push(format!("{}.{}: change the default by hand", table, change.b.name));
+            }
+            statements
+        }
+        _ => {
+            warnings.push(format!(
+                "{}.{}: SQLite cannot alter a column in place; use change_column_type to rebuild the table",
+                table, change.b.name
+            ));
+            Vec::new()
+        }
+    }
+}
+
+/// Generate the DDL that makes database B match database A, from the result
+/// of `diff_schemas`. Drops of tables and columns that only exist on B are
+/// kept apart in `destructive` and only added to the script with
+/// `include_destructive`.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn generate_migration_sql(
+    diff: SchemaDiff,
+    connection_id_a: String,
+    database_a: Option<String>,
+    connection_id_b: String,
+    include_destructive: Option<bool>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Migration, String> {
+    let connections = load_connections(&app);
+    let find = |id: &str| {
+        connections
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| "Connection not found".to_string())
+    };
+    let (connection_a, connection_b) = (find(&connection_id_a)?, find(&connection_id_b)?);
+    let db_type = connection_b.db_type.as_str();
+    let same_engine = connection_a.db_type == connection_b.db_type;
+    let database_a = app.state::<ActiveDatabases>().resolve(connection_a, database_a).await;
+
+    let mut statements = Vec::new();
+    let mut destructive = Vec::new();
+    let mut warnings = Vec::new();
+    if !same_engine {
+        warnings.push(format!(
+            "Types are copied from {} and may need adjusting for {}",
+            connection_a.db_type, connection_b.db_type
+        ));
+    }
+
+    for table in &diff.tables_only_in_a {
+        if same_engine && matches!(db_type, "sqlite" | "mysql") {
+            // The server's own definition keeps indexes and constraints
+            statements.push(table_ddl(connection_a, database_a.as_deref(), table, &app, &pool_manager).await?);
+            continue;
+        }
+        let mut columns = columns_of(connection_a, &database_a, table, &app, &pool_manager).await?;
+        if !same_engine && columns.iter().any(|c| c.default.is_some()) {
+            // Defaults are in the source dialect and would not parse on the target
+            for column in &mut columns {
+                column.default = None;
+            }
+            warnings.push(format!("{}: column defaults were left out", table));
+        }
+        statements.push(create_table_sql(db_type, table, &columns));
+    }
+
+    for table_diff in &diff.changed_tables {
+        let table = &table_diff.table;
+        let table_sql = quote_ident(db_type, table);
+
+        if !table_diff.columns_only_in_a.is_empty() {
+            let columns = columns_of(connection_a, &database_a, table, &app, &pool_manager).await?;
+            for name in &table_diff.columns_only_in_a {
+                let Some(column) = columns.iter().find(|c| &c.name == name) else {
+                    warnings.push(format!("{}.{}: column no longer exists", table, name));
+                    continue;
+                };
+                let mut column = column.clone();
+                if !same_engine {
+                    column.default = None;
+                }
+                if !column.nullable && column.default.is_none() {
+                    warnings.push(format!("{}.{}: NOT NULL without a default fails if the table has rows", table, name));
+                }
+                let add = if db_type == "mssql" { "ADD" } else { "ADD COLUMN" };
+                statements.push(format!("ALTER TABLE {} {} {}", table_sql, add, column_definition_sql(db_type, &column)));
+            }
+        }
+
+        for change in &table_diff.changed_columns {
+            statements.extend(alter_column_sql(db_type, table, change, same_engine, &mut warnings));
+        }
+
+        for name in &table_diff.columns_only_in_b {
+            destructive.push(format!("ALTER TABLE {} DROP COLUMN {}", table_sql, quote_ident(db_type, name)));
+        }
+    }
+
+    for table in &diff.tables_only_in_b {
+        destructive.push(format!("DROP TABLE {}", quote_ident(db_type, table)));
+    }
+
+    let mut script: Vec<&String> = statements.iter().collect();
+    if include_destructive.unwrap_or(false) {
+        script.extend(destructive.iter());
+    }
+    let script = script.iter().map(|s| format!("{};\n", s)).collect();
+
+    Ok(Migration { statements, destructive, warnings, script })
+}
@@ -3,4 +3,42 @@ pub mod execute;
 pub mod pool_manager;
 pub mod history;
 pub mod settings;
-
+pub mod bulk;
+pub mod import;
+pub mod progress;
+pub mod export;
+pub mod autocomplete;
+pub mod dialect;
+pub mod ddl;
+pub mod server;
+pub mod format;
+pub mod dump;
+pub mod script;
+pub mod profile;
+pub mod session;
+pub mod template;
+pub mod diagram;
+pub mod sort;
+pub mod query_cache;
+pub mod quote;
+pub mod pivot;
+pub mod connection_import;
+pub mod transaction;
+pub mod preview;
+pub mod store;
+pub mod schema_diff;
+pub mod column_source;
+pub mod recent;
+pub mod multi;
+pub mod migration;
+pub mod explain;
+pub mod risk;
+pub mod sql_log;
+pub mod listen;
+pub mod sqlkind;
+pub mod snippets;
+pub mod cursor;
+pub mod row_counts;
+pub mod workspace;
+pub mod benchmark;
+pub mod users;
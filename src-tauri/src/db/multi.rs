@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use tauri::State;
+use crate::db::connections::{load_connections, Connection};
+use crate::db::ddl::ensure_writable;
+use crate::db::execute::{extract_rows_affected, is_row_query, run_query, QueryQueue, QueryResult};
+use crate::db::history;
+use crate::db::pool_manager::PoolManager;
+use crate::db::query_cache::QueryCache;
+use crate::db::session::{ActiveDatabases, DatabaseListCache};
+use crate::db::settings;
+
+// Connections queried at once
+const MULTI_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Serialize)]
+pub struct MultiResults {
+    /// Result on each connection that ran the statement, by connection id
+    pub results: BTreeMap<String, QueryResult>,
+    /// Connections where it failed or was refused, with the error
+    pub failed: BTreeMap<String, String>,
+}
+
+/// Run the statement on one target through the same path as `execute_sql`
+/// (queue, retries, history), outside any manual-mode transaction
+async fn execute_on(
+    connection: &Connection,
+    database: Option<String>,
+    sql: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &PoolManager,
+    active_databases: &ActiveDatabases,
+    query_queue: &QueryQueue,
+) -> Result<QueryResult, String> {
+    let writes = !is_row_query(sql);
+    if writes {
+        ensure_writable(connection)?;
+        if !connection.autocommit {
+            return Err("Autocommit is off on this connection; run the statement in its own tab".to_string());
+        }
+    }
+    let database = active_databases.resolve(connection, database).await;
+
+    let started = Instant::now();
+    let result = {
+        let _slot = if settings::load_settings(app).queue_concurrent_queries {
+            query_queue.acquire(&connection.id).await
+        } else {
+            None
+        };
+        run_query(connection, database.as_deref(), sql, app, pool_manager).await
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let rows_affected = result.as_ref().ok().and_then(extract_rows_affected);
+    if let Err(e) = history::add_sql_history(
+        connection.id.clone(),
+        connection.name.clone(),
+        sql.to_string(),
+        result.is_ok(),
+        result.as_ref().err().cloned(),
+        rows_affected,
+        Some(elapsed_ms),
+        app.clone(),
+    ).await {
+        eprintln!("Failed to save SQL history: {}", e);
+    }
+
+    result.map(|r| QueryResult { elapsed_ms, ..r })
+}
+
+/// Run one statement on several connections, e.g. the same check against
+/// dev, staging and prod. Writes are refused on read-only connections. A
+/// failing connection is listed in `failed` without stopping the others.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn execute_on_many(
+    connection_ids: Vec<String>,
+    sql: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
+    query_cache: State<'_, QueryCache>,
+    database_list_cache: State<'_, DatabaseListCache>,
+    query_queue: State<'_, QueryQueue>,
+) -> Result<MultiResults, String> {
+    let connections = load_connections(&app);
+    let mut failed = BTreeMap::new();
+    let mut targets = Vec::new();
+    for id in connection_ids {
+        match connections.iter().find(|c| c.id == id) {
+            Some(connection) => targets.push(connection),
+            None => {
+                failed.insert(id, "Connection not found".to_string());
+            }
+        }
+    }
+
+    if !is_row_query(&sql) {
+        for connection in &targets {
+            query_cache.invalidate(&connection.id).await;
+            database_list_cache.invalidate(&connection.id).await;
+        }
+    }
+
+    let mut runs = stream::iter(targets)
+        .map(|connection| {
+            let (database, sql, app) = (database.clone(), sql.as_str(), &app);
+            let (pool_manager, active_databases, query_queue) = (&pool_manager, &active_databases, &query_queue);
+            async move {
+                let result = execute_on(connection, database, sql, app, pool_manager, active_databases, query_queue).await;
+                (connection.id.clone(), result)
+            }
+        })
+        .buffer_unordered(MULTI_CONCURRENCY);
+
+    let mut results = BTreeMap::new();
+    while let Some((id, result)) = runs.next().await {
+        match result {
+            Ok(result) => {
+                results.insert(id, result);
+            }
+            Err(e) => {
+                failed.insert(id, e);
+            }
+        }
+    }
+
+    Ok(MultiResults { results, failed })
+}
@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::db::execute::{unique_column_keys, QueryResult};
+use crate::db::sort::{as_number, compare_values, sort_kind};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PivotAggregate {
+    Sum,
+    Count,
+    Avg,
+    Min,
+    Max,
+}
+
+/// Running aggregate of one pivot cell
+#[derive(Default)]
+struct Accumulator {
+    count: u64,
+    sum: f64,
+    numeric: u64,
+    min: Option<Value>,
+    max: Option<Value>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: &Value, value_type: &str) {
+        if value.is_null() {
+            return;
+        }
+        self.count += 1;
+        if let Some(n) = as_number(value) {
+            self.sum += n;
+            self.numeric += 1;
+        }
+        let kind = sort_kind(value_type);
+        if self.min.as_ref().is_none_or(|m| compare_values(value, m, kind) == Ordering::Less) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().is_none_or(|m| compare_values(value, m, kind) == Ordering::Greater) {
+            self.max = Some(value.clone());
+        }
+    }
+
+    fn result(&self, aggregate: PivotAggregate) -> Value {
+        match aggregate {
+            PivotAggregate::Count => Value::from(self.count),
+            PivotAggregate::Sum if self.numeric > 0 => number(self.sum),
+            PivotAggregate::Avg if self.numeric > 0 => number(self.sum / self.numeric as f64),
+            PivotAggregate::Min => self.min.clone().unwrap_or(Value::Null),
+            PivotAggregate::Max => self.max.clone().unwrap_or(Value::Null),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// Whole numbers stay integers so sums of integer columns don't gain a `.0`
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map(Value::Number).unwrap_or(Value::Null)
+    }
+}
+
+fn label(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Index of a field, looked up by unique column key first so duplicated
+/// column names can still be told apart
+fn field_index(result: &QueryResult, field: &str) -> Result<usize, String> {
+    result
+        .column_keys
+        .iter()
+        .position(|k| k == field)
+        .or_else(|| result.columns.iter().position(|c| c == field))
+        .ok_or_else(|| format!("Column {} not found in the result", field))
+}
+
+/// Pivot a query result: one row per distinct combination of `row_fields`,
+/// one column per distinct value of `column_field` (sorted by that column's
+/// type, NULL last), each cell aggregating `value_field` with `aggregate`.
+/// Row groups keep the order in which they first appear.
+#[tauri::command]
+pub async fn pivot_query_result(
+    result: QueryResult,
+    row_fields: Vec<String>,
+    column_field: String,
+    value_field: String,
+    aggregate: PivotAggregate,
+) -> Result<QueryResult, String> {
+    let row_indexes = row_fields
+        .iter()
+        .map(|f| field_index(&result, f))
+        .collect::<Result<Vec<_>, String>>()?;
+    let column_index = field_index(&result, &column_field)?;
+    let value_index = field_index(&result, &value_field)?;
+    let type_of = |i: usize| result.column_types.get(i).cloned().unwrap_or_default();
+    let value_type = type_of(value_index);
+
+    // Distinct pivot column values, keyed by label
+    let mut pivot_values: Vec<Value> = Vec::new();
+    let mut pivot_positions: HashMap<String, usize> = HashMap::new();
+    for row in &result.rows {
+        let value = row.get(column_index).cloned().unwrap_or(Value::Null);
+        if let Entry::Vacant(entry) = pivot_positions.entry(label(&value)) {
+            entry.insert(pivot_values.len());
+            pivot_values.push(value);
+        }
+    }
+    let column_kind = sort_kind(&type_of(column_index));
+    let mut pivot_order: Vec<usize> = (0..pivot_values.len()).collect();
+    pivot_order.sort_by(|&a, &b| match (&pivot_values[a], &pivot_values[b]) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        (x, y) => compare_values(x, y, column_kind),
+    });
+
+    // Row groups in first-seen order, each with one accumulator per pivot value
+    let mut groups: Vec<(Vec<Value>, Vec<Accumulator>)> = Vec::new();
+    let mut group_positions: HashMap<Vec<String>, usize> = HashMap::new();
+    for row in &result.rows {
+        let cell = |i: usize| row.get(i).cloned().unwrap_or(Value::Null);
+        let key: Vec<String> = row_indexes.iter().map(|&i| label(&cell(i))).collect();
+        let position = *group_positions.entry(key).or_insert_with(|| {
+            let accumulators = (0..pivot_values.len()).map(|_| Accumulator::default()).collect();
+            groups.push((row_indexes.iter().map(|&i| cell(i)).collect(), accumulators));
+            groups.len() - 1
+        });
+        let pivot = pivot_positions[&label(&cell(column_index))];
+        groups[position].1[pivot].add(&cell(value_index), &value_type);
+    }
+
+    let aggregate_type = match aggregate {
+        PivotAggregate::Count => "bigint".to_string(),
+        PivotAggregate::Sum | PivotAggregate::Avg => "numeric".to_string(),
+        PivotAggregate::Min | PivotAggregate::Max => value_type.clone(),
+    };
+    let mut columns = row_fields.clone();
+    let mut column_types: Vec<String> = row_indexes.iter().map(|&i| type_of(i)).collect();
+    for &p in &pivot_order {
+        columns.push(label(&pivot_values[p]));
+        column_types.push(aggregate_type.clone());
+    }
+
+    let rows = groups
+        .into_iter()
+        .map(|(mut values, accumulators)| {
+            values.extend(pivot_order.iter().map(|&p| accumulators[p].result(aggregate)));
+            values
+        })
+        .collect();
+
+    Ok(QueryResult {
+        column_keys: unique_column_keys(&columns),
+        columns,
+        rows,
+        column_types,
+        ..Default::default()
+    })
+}
@@ -1,8 +1,15 @@
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedMutexGuard, RwLock};
+use futures_util::future::join_all;
+use serde::Serialize;
 use sqlx::Pool;
-use crate::db::connections::{Connection, ConnectionConfig};
+use tauri::State;
+use tauri::Manager;
+use crate::db::settings;
+use crate::db::connections::{application_name_param, charset_param, extra_query_params, is_sqlite_memory, maintenance_database, mysql_connect_options, postgres_connect_options, socket_param, sqlite_connect_options, Connection, ConnectionConfig, TlsMode};
+use crate::db::quote::quote_ident;
 
 #[derive(Clone)]
 pub enum DatabasePool {
@@ -11,14 +18,69 @@ pub enum DatabasePool {
     Postgres(Pool<sqlx::Postgres>),
 }
 
+impl DatabasePool {
+    fn num_idle(&self) -> usize {
+        match self {
+            DatabasePool::Sqlite(p) => p.num_idle(),
+            DatabasePool::Mysql(p) => p.num_idle(),
+            DatabasePool::Postgres(p) => p.num_idle(),
+        }
+    }
+
+    /// Open connections, idle or in use
+    fn size(&self) -> u32 {
+        match self {
+            DatabasePool::Sqlite(p) => p.size(),
+            DatabasePool::Mysql(p) => p.size(),
+            DatabasePool::Postgres(p) => p.size(),
+        }
+    }
+
+    /// Close every connection, waiting for checked-out ones to be returned
+    async fn close(&self) {
+        match self {
+            DatabasePool::Sqlite(p) => p.close().await,
+            DatabasePool::Mysql(p) => p.close().await,
+            DatabasePool::Postgres(p) => p.close().await,
+        }
+    }
+}
+
+/// `SET search_path` statement for the given schemas, each quoted as an identifier
+pub(crate) fn search_path_sql(schemas: &[String]) -> String {
+    let list = schemas
+        .iter()
+        .map(|s| quote_ident("postgres", s))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("SET search_path TO {}", list)
+}
+
+/// Connection limit of every sqlx pool
+pub const POOL_MAX_CONNECTIONS: u32 = 5;
+
+#[derive(Debug, Serialize)]
+pub struct PoolStats {
+    /// Database the pool is bound to; None for the connection's default database
+    pub database: Option<String>,
+    /// Shared MySQL pool that switches database per checkout
+    pub shared: bool,
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
 pub struct PoolManager {
     pools: Arc<RwLock<HashMap<String, DatabasePool>>>,
+    /// One lock per SQLite file so writes from different tabs run one at a time
+    sqlite_write_locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
 }
 
 impl PoolManager {
     pub fn new() -> Self {
         Self {
             pools: Arc::new(RwLock::new(HashMap::new())),
+            sqlite_write_locks: Mutex::new(HashMap::new()),
         }
     }
 
@@ -57,8 +119,91 @@ impl PoolManager {
         Ok(pool)
     }
 
-    pub async fn get_pool_without_db(&self, connection: &Connection) -> Result<DatabasePool, String> {
-        self.get_or_create_pool(connection, None).await
+    /// Lock to hold while writing to the SQLite file at `filepath`. SQLite
+    /// allows a single writer, so concurrent writes through pooled connections
+    /// fail with "database is locked" instead of waiting; reads stay concurrent.
+    pub fn sqlite_write_lock(&self, filepath: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.sqlite_write_locks.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(filepath.to_string()).or_default().clone()
+    }
+
+    /// Wait for the `sqlite_write_lock` of a SQLite connection's file; None
+    /// for other engines. Writes run while the returned guard is held.
+    pub async fn sqlite_write_guard(&self, connection: &Connection) -> Option<OwnedMutexGuard<()>> {
+        match &connection.config {
+            ConnectionConfig::Sqlite { filepath, .. } => Some(self.sqlite_write_lock(filepath).lock_owned().await),
+            _ => None,
+        }
+    }
+
+    /// Pool for server-level statements, on the connection's maintenance database
+    pub async fn get_maintenance_pool(&self, connection: &Connection) -> Result<DatabasePool, String> {
+        self.get_or_create_pool(connection, maintenance_database(&connection.config)).await
+    }
+
+    /// Usage of every cached pool of a connection
+    pub async fn pool_stats(&self, connection_id: &str) -> Vec<PoolStats> {
+        let prefix = format!("{}:", connection_id);
+        let pools = self.pools.read().await;
+        let mut stats: Vec<PoolStats> = pools
+            .iter()
+            .filter_map(|(key, pool)| {
+                let suffix = key.strip_prefix(&prefix)?;
+                Some(PoolStats {
+                    database: Some(suffix.to_string()).filter(|d| !d.is_empty() && d != "*"),
+                    shared: suffix == "*",
+                    size: pool.size(),
+                    idle: pool.num_idle() as u32,
+                    max: POOL_MAX_CONNECTIONS,
+                })
+            })
+            .collect();
+        stats.sort_by(|a, b| a.database.cmp(&b.database));
+        stats
+    }
+
+    /// Get a pool for running statements against `database`.
+    ///
+    /// When `reuse_across_databases` is set and the connection is MySQL, a single
+    /// shared pool is returned together with the database the caller must `USE`
+    /// on its checked-out connection. Switching per checkout keeps concurrent tabs
+    /// on different databases from clobbering each other's session state.
+    /// PostgreSQL binds a session to one database, so it always gets per-database pools.
+    /// MySQL cannot deselect a database once a session has issued `USE`, so
+    /// without any database to switch to the unshared default pool is returned.
+    pub async fn get_pool_for_database(
+        &self,
+        connection: &Connection,
+        database: Option<&str>,
+        reuse_across_databases: bool,
+    ) -> Result<(DatabasePool, Option<String>), String> {
+        let target_db = match &connection.config {
+            ConnectionConfig::Mysql { database: config_db, .. } if reuse_across_databases => {
+                database.or(config_db.as_deref()).map(|d| d.to_string())
+            }
+            _ => None,
+        };
+        let Some(target_db) = target_db else {
+            return Ok((self.get_or_create_pool(connection, database).await?, None));
+        };
+
+        // Separate key from the per-database pools so statements issued
+        // through get_maintenance_pool never see a switched database
+        let key = format!("{}:*", connection.id);
+        {
+            let pools = self.pools.read().await;
+            if let Some(pool) = pools.get(&key) {
+                return Ok((pool.clone(), Some(target_db)));
+            }
+        }
+
+        let pool = Self::create_pool(connection, None).await?;
+        {
+            let mut pools = self.pools.write().await;
+            pools.insert(key, pool.clone());
+        }
+
+        Ok((pool, Some(target_db)))
     }
 
     async fn create_pool(
@@ -66,11 +211,15 @@ impl PoolManager {
         database: Option<&str>,
     ) -> Result<DatabasePool, String> {
         match &connection.config {
-            ConnectionConfig::Sqlite { filepath } => {
-                let connection_string = format!("sqlite://{}", filepath);
-                let pool = sqlx::sqlite::SqlitePoolOptions::new()
-                    .max_connections(5)
-                    .connect(&connection_string)
+            ConnectionConfig::Sqlite { filepath, .. } => {
+                let options = sqlite_connect_options(&connection.config)?;
+                let mut pool_options = sqlx::sqlite::SqlitePoolOptions::new().max_connections(POOL_MAX_CONNECTIONS);
+                if is_sqlite_memory(filepath) {
+                    // An in-memory database lives only as long as a connection to it
+                    pool_options = pool_options.min_connections(1).idle_timeout(None).max_lifetime(None);
+                }
+                let pool = pool_options
+                    .connect_with(options)
                     .await
                     .map_err(|e| format!("Failed to create SQLite pool: {}", e))?;
                 Ok(DatabasePool::Sqlite(pool))
@@ -82,17 +231,28 @@ impl PoolManager {
                 password,
                 database: config_db,
                 ssl,
+                tls_mode,
+                ca_cert_path,
+                socket_path,
+                extra_params,
+                charset,
+                ..
             } => {
                 let db_name = database.or(config_db.as_deref());
                 let db_part = db_name.map(|d| format!("/{}", d)).unwrap_or_default();
-                let ssl_param = if *ssl { "?ssl-mode=REQUIRED" } else { "?ssl-mode=DISABLED" };
+                let ssl_param = TlsMode::resolve(*tls_mode, *ssl).mysql_params(ca_cert_path.as_deref());
+                let socket = socket_param("mysql", socket_path.as_deref())?;
+                let extra = extra_query_params(extra_params.as_ref())?;
+                // The driver sends SET NAMES with it on every new connection
+                let charset = charset_param(charset.as_deref());
                 let connection_string = format!(
-                    "mysql://{}:{}@{}:{}{}{}",
-                    user, password, host, port, db_part, ssl_param
+                    "mysql://{}:{}@{}:{}{}{}{}{}{}",
+                    user, password, host, port, db_part, ssl_param, socket, extra, charset
                 );
+                // No application name: the driver sends no connect attributes
                 let pool = sqlx::mysql::MySqlPoolOptions::new()
-                    .max_connections(5)
-                    .connect(&connection_string)
+                    .max_connections(POOL_MAX_CONNECTIONS)
+                    .connect_with(mysql_connect_options(&connection_string, &connection.config)?)
                     .await
                     .map_err(|e| format!("Failed to create MySQL pool: {}", e))?;
                 Ok(DatabasePool::Mysql(pool))
@@ -104,17 +264,35 @@ impl PoolManager {
                 password,
                 database: config_db,
                 ssl,
+                tls_mode,
+                ca_cert_path,
+                socket_path,
+                extra_params,
+                ..
             } => {
                 let db_name = database.or(config_db.as_deref());
                 let db_part = db_name.map(|d| format!("/{}", d)).unwrap_or_default();
-                let ssl_param = if *ssl { "?sslmode=require" } else { "?sslmode=disable" };
+                let ssl_param = TlsMode::resolve(*tls_mode, *ssl).postgres_params(ca_cert_path.as_deref());
+                let socket = socket_param("postgres", socket_path.as_deref())?;
+                let extra = extra_query_params(extra_params.as_ref())?;
+                let app_name = application_name_param(extra_params.as_ref(), Some(&connection.name));
                 let connection_string = format!(
-                    "postgres://{}:{}@{}:{}{}{}",
-                    user, password, host, port, db_part, ssl_param
+                    "postgres://{}:{}@{}:{}{}{}{}{}{}",
+                    user, password, host, port, db_part, ssl_param, socket, app_name, extra
                 );
+                let search_path = connection.search_path.as_ref().map(|schemas| search_path_sql(schemas));
                 let pool = sqlx::postgres::PgPoolOptions::new()
-                    .max_connections(5)
-                    .connect(&connection_string)
+                    .max_connections(POOL_MAX_CONNECTIONS)
+                    .after_connect(move |conn, _meta| {
+                        let search_path = search_path.clone();
+                        Box::pin(async move {
+                            if let Some(sql) = search_path {
+                                sqlx::query(&sql).execute(conn).await?;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .connect_with(postgres_connect_options(&connection_string, &connection.config)?)
                     .await
                     .map_err(|e| format!("Failed to create PostgreSQL pool: {}", e))?;
                 Ok(DatabasePool::Postgres(pool))
@@ -125,7 +303,7 @@ impl PoolManager {
         }
     }
 
-    async fn check_pool_health(pool: &DatabasePool) -> bool {
+    pub(crate) async fn check_pool_health(pool: &DatabasePool) -> bool {
         match pool {
             DatabasePool::Sqlite(p) => {
                 sqlx::query("SELECT 1").execute(p).await.is_ok()
@@ -139,6 +317,34 @@ impl PoolManager {
         }
     }
 
+    /// Ping every pool that has idle connections and drop the ones that fail,
+    /// so the next use creates a fresh pool instead of hitting dead sockets.
+    /// Pools without idle connections are skipped: pinging would only open a new one.
+    pub async fn ping_idle_pools(&self) {
+        let idle: Vec<(String, DatabasePool)> = {
+            let pools = self.pools.read().await;
+            pools
+                .iter()
+                .filter(|(_, pool)| pool.num_idle() > 0)
+                .map(|(key, pool)| (key.clone(), pool.clone()))
+                .collect()
+        };
+
+        let mut dead = Vec::new();
+        for (key, pool) in idle {
+            if !Self::check_pool_health(&pool).await {
+                dead.push(key);
+            }
+        }
+
+        if !dead.is_empty() {
+            let mut pools = self.pools.write().await;
+            for key in dead {
+                pools.remove(&key);
+            }
+        }
+    }
+
     pub async fn remove_pool(&self, connection_id: &str) {
         let mut pools = self.pools.write().await;
         // Remove all pools for this connection
@@ -149,5 +355,150 @@ impl PoolManager {
         let mut pools = self.pools.write().await;
         pools.clear();
     }
+
+    /// Remove every pool like `clear_all` and close its connections, so the
+    /// servers see clean disconnects instead of sockets going away
+    pub async fn close_all(&self) {
+        let pools: Vec<DatabasePool> = self.pools.write().await.drain().map(|(_, pool)| pool).collect();
+        join_all(pools.iter().map(DatabasePool::close)).await;
+    }
 }
 
+// How often the keep-alive task re-reads the setting while it is disabled
+const KEEPALIVE_DISABLED_POLL: Duration = Duration::from_secs(30);
+
+/// Background loop pinging idle pools every `keepalive_secs`. The setting is
+/// re-read on every tick, so changing it takes effect without a restart.
+pub async fn run_keepalive(app: tauri::AppHandle) {
+    loop {
+        let secs = settings::load_settings(&app).keepalive_secs;
+        if secs == 0 {
+            tokio::time::sleep(KEEPALIVE_DISABLED_POLL).await;
+            continue;
+        }
+
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        app.state::<PoolManager>().ping_idle_pools().await;
+    }
+}
+
+/// Handle of the keep-alive task, kept in managed state so it can be stopped on exit
+pub struct KeepAliveTask(Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+impl KeepAliveTask {
+    pub fn new(handle: tauri::async_runtime::JoinHandle<()>) -> Self {
+        Self(Mutex::new(Some(handle)))
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.0.lock().ok().and_then(|mut h| h.take()) {
+            handle.abort();
+        }
+    }
+}
+
+/// Open, idle and maximum connections of each cached pool of a connection,
+/// so the UI can show when queries are waiting for a free connection
+#[tauri::command]
+pub async fn get_pool_stats(
+    connection_id: String,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<PoolStats>, String> {
+    Ok(pool_manager.pool_stats(&connection_id).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connections::sqlite_test_connection;
+    use crate::db::transaction::OpenTransactions;
+    use sqlx::Executor;
+
+    fn mysql_connection(database: Option<&str>) -> Connection {
+        serde_json::from_value(serde_json::json!({
+            "id": "c1",
+            "name": "test",
+            "type": "mysql",
+            "config": {
+                "type": "mysql",
+                "host": "127.0.0.1",
+                "port": 3306,
+                "user": "root",
+                "password": "",
+                "database": database,
+                "ssl": false,
+            },
+        }))
+        .unwrap()
+    }
+
+    /// Pool that only connects when first used, so no server is needed
+    fn lazy_mysql_pool() -> DatabasePool {
+        DatabasePool::Mysql(
+            sqlx::mysql::MySqlPoolOptions::new()
+                .max_connections(POOL_MAX_CONNECTIONS)
+                .connect_lazy("mysql://root@127.0.0.1:3306")
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn switching_databases_reuses_the_shared_pool() {
+        let manager = PoolManager::new();
+        let connection = mysql_connection(None);
+        manager.pools.write().await.insert("c1:*".to_string(), lazy_mysql_pool());
+
+        for db in ["app", "reporting", "app", "archive"] {
+            let (_, target) = manager.get_pool_for_database(&connection, Some(db), true).await.unwrap();
+            assert_eq!(target.as_deref(), Some(db));
+        }
+
+        // Creating a pool would fail without a server; all switches shared the one
+        let stats = manager.pool_stats("c1").await;
+        assert_eq!(stats.len(), 1);
+        assert!(stats[0].shared);
+        assert_eq!(stats[0].max, POOL_MAX_CONNECTIONS);
+    }
+
+    #[tokio::test]
+    async fn no_database_uses_the_unshared_pool() {
+        let manager = PoolManager::new();
+        manager.pools.write().await.insert("c1:*".to_string(), lazy_mysql_pool());
+        manager.pools.write().await.insert("c1:".to_string(), lazy_mysql_pool());
+
+        // A shared connection may still have another database selected
+        let (_, target) = manager.get_pool_for_database(&mysql_connection(None), None, true).await.unwrap();
+        assert_eq!(target, None);
+
+        // The configured default database is switched to explicitly
+        let (_, target) = manager.get_pool_for_database(&mysql_connection(Some("app")), None, true).await.unwrap();
+        assert_eq!(target.as_deref(), Some("app"));
+    }
+
+    #[tokio::test]
+    async fn shutdown_rolls_back_and_closes_every_pool() {
+        let connection = sqlite_test_connection("close_all");
+        let manager = PoolManager::new();
+        let transactions = OpenTransactions::new();
+        let pool = manager.get_or_create_pool(&connection, None).await.unwrap();
+        let DatabasePool::Sqlite(sqlite) = &pool else { unreachable!() };
+        sqlite.execute("CREATE TABLE t (id INTEGER)").await.unwrap();
+        let transaction = transactions.get_or_begin(&connection, None, &manager).await.unwrap();
+        transaction.lock().await.conn.execute("INSERT INTO t VALUES (1)").await.unwrap();
+        drop(transaction);
+
+        // The order of close_all_connections: the transaction holds a pooled
+        // connection, which close_all would otherwise wait for forever
+        transactions.rollback_all().await;
+        manager.close_all().await;
+
+        assert!(manager.pools.read().await.is_empty());
+        assert!(sqlite.is_closed());
+        assert!(transactions.get(&connection.id).await.is_none());
+
+        let reopened = PoolManager::new();
+        let DatabasePool::Sqlite(sqlite) = reopened.get_or_create_pool(&connection, None).await.unwrap() else { unreachable!() };
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM t").fetch_one(&sqlite).await.unwrap();
+        assert_eq!(count, 0);
+    }
+}
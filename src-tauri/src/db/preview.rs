@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::connections::{list_tables, load_connections, resolve_schema, Connection};
+use crate::db::execute::{run_query_with_limit, QueryResult};
+use crate::db::pool_manager::PoolManager;
+use crate::db::quote::quote_ident;
+use crate::db::session::ActiveDatabases;
+
+const DEFAULT_PREVIEW_ROWS: usize = 10;
+const MAX_PREVIEW_ROWS: usize = 1000;
+// Tables queried at once, well below the pool size so other tabs keep working
+const PREVIEW_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Serialize)]
+pub struct TablePreviews {
+    /// First rows of each table, by table name
+    pub tables: BTreeMap<String, QueryResult>,
+    /// Tables whose query failed, with the error
+    pub failed: BTreeMap<String, String>,
+}
+
+/// A table from `list_tables`, quoted; Postgres and MSSQL tables are
+/// qualified with the schema it read them from
+pub(crate) fn listed_table_sql(connection: &Connection, table_name: &str) -> String {
+    let db_type = connection.db_type.as_str();
+    match db_type {
+        "postgres" | "mssql" => format!(
+            "{}.{}",
+            quote_ident(db_type, &resolve_schema(&connection.config, None)),
+            quote_ident(db_type, table_name)
+        ),
+        _ => quote_ident(db_type, table_name),
+    }
+}
+
+/// `SELECT *` of the first `rows` rows
+fn preview_sql(connection: &Connection, table_name: &str, rows: usize) -> String {
+    let table = listed_table_sql(connection, table_name);
+    if connection.db_type == "mssql" {
+        format!("SELECT TOP {} * FROM {}", rows, table)
+    } else {
+        format!("SELECT * FROM {} LIMIT {}", table, rows)
+    }
+}
+
+/// The first rows of every table in a database, for a quick look around.
+/// `rows_per_table` defaults to 10 and is capped at 1000. Tables that fail
+/// are listed in `failed` instead of failing the whole preview.
+#[tauri::command]
+pub async fn preview_all_tables(
+    connection_id: String,
+    database: Option<String>,
+    rows_per_table: Option<usize>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<TablePreviews, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    // Resolve here so the table list and the previews come from the same database
+    let active_databases = app.state::<ActiveDatabases>();
+    let database = active_databases.resolve(connection, database).await;
+
+    let table_names = list_tables(
+        connection_id.clone(),
+        database.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases,
+    ).await?;
+
+    let rows = rows_per_table.unwrap_or(DEFAULT_PREVIEW_ROWS).clamp(1, MAX_PREVIEW_ROWS);
+    let mut previews = stream::iter(table_names)
+        .map(|name| {
+            let sql = preview_sql(connection, &name, rows);
+            let (database, app, pool_manager) = (database.as_deref(), &app, &pool_manager);
+            async move {
+                let result = run_query_with_limit(connection, database, &sql, app, pool_manager, rows).await;
+                (name, result)
+            }
+        })
+        .buffer_unordered(PREVIEW_CONCURRENCY);
+
+    let mut tables = BTreeMap::new();
+    let mut failed = BTreeMap::new();
+    while let Some((name, result)) = previews.next().await {
+        match result {
+            Ok(result) => {
+                tables.insert(name, result);
+            }
+            Err(e) => {
+                failed.insert(name, e);
+            }
+        }
+    }
+
+    Ok(TablePreviews { tables, failed })
+}
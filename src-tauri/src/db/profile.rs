@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::db::connections::{describe_table, load_connections, ColumnInfo, Connection};
+use crate::db::quote::quote_ident;
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::PoolManager;
+
+const DEFAULT_DISTINCT_LIMIT: usize = 100;
+const MAX_DISTINCT_LIMIT: usize = 1000;
+
+/// Look the column up in the table definition, so only real column names ever
+/// reach the SQL text
+async fn find_column(
+    connection: &Connection,
+    database: Option<&str>,
+    table_name: &str,
+    column: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<ColumnInfo, String> {
+    let columns = describe_table(
+        connection.id.clone(),
+        table_name.to_string(),
+        database.map(|s| s.to_string()),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+    ).await?;
+    columns
+        .into_iter()
+        .find(|c| c.name == column)
+        .ok_or_else(|| format!("Column {} not found in table {}", column, table_name))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DistinctValues {
+    pub values: Vec<serde_json::Value>,
+    /// More distinct values exist than were returned
+    pub truncated: bool,
+}
+
+/// Distinct values of a column in sorted order, for filter dropdowns.
+/// `limit` defaults to 100 and is capped at 1000.
+#[tauri::command]
+pub async fn get_column_distinct_values(
+    connection_id: String,
+    table_name: String,
+    column: String,
+    database: Option<String>,
+    limit: Option<usize>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<DistinctValues, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let info = find_column(connection, database.as_deref(), &table_name, &column, &app, &pool_manager).await?;
+
+    let limit = limit.unwrap_or(DEFAULT_DISTINCT_LIMIT).clamp(1, MAX_DISTINCT_LIMIT);
+    let db_type = connection.db_type.as_str();
+    let col = quote_ident(db_type, &info.name);
+    let table = quote_ident(db_type, &table_name);
+    // One extra row tells whether the list was cut off
+    let sql = if db_type == "mssql" {
+        format!("SELECT DISTINCT TOP {} {} FROM {} ORDER BY {}", limit + 1, col, table, col)
+    } else {
+        format!("SELECT DISTINCT {} FROM {} ORDER BY {} LIMIT {}", col, table, col, limit + 1)
+    };
+
+    let result = run_query_with_limit(connection, database.as_deref(), &sql, &app, &pool_manager, limit + 1).await?;
+    let truncated = result.truncated || result.rows.len() > limit;
+    let values = result
+        .rows
+        .into_iter()
+        .take(limit)
+        .filter_map(|row| row.into_iter().next())
+        .collect();
+
+    Ok(DistinctValues { values, truncated })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnStats {
+    pub count: u64,
+    pub distinct_count: u64,
+    pub null_count: u64,
+    pub min: serde_json::Value,
+    pub max: serde_json::Value,
+}
+
+/// Aggregates may come back as numbers or, for DECIMAL sums, as strings
+pub(crate) fn value_to_count(value: Option<&serde_json::Value>) -> u64 {
+    match value {
+        Some(serde_json::Value::Number(n)) => n.as_u64().or_else(|| n.as_f64().map(|f| f as u64)).unwrap_or(0),
+        Some(serde_json::Value::String(s)) => s.trim().parse::<f64>().map(|f| f as u64).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Types MIN/MAX can compare directly; anything else (booleans, JSON, XML, ...)
+/// is compared as text
+fn is_natively_ordered(data_type: &str) -> bool {
+    let data_type = data_type.to_lowercase();
+    ["int", "num", "dec", "real", "double", "float", "money", "date", "time", "char", "text"]
+        .iter()
+        .any(|t| data_type.contains(t))
+}
+
+/// Row count, distinct and null counts, and min/max of a column, computed in
+/// one aggregate query. Min and max of non-numeric columns are lexical.
+#[tauri::command]
+pub async fn get_column_stats(
+    connection_id: String,
+    table_name: String,
+    column: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<ColumnStats, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let info = find_column(connection, database.as_deref(), &table_name, &column, &app, &pool_manager).await?;
+
+    let db_type = connection.db_type.as_str();
+    let col = quote_ident(db_type, &info.name);
+    // SQLite and MySQL compare mixed values without complaint; Postgres and
+    // MSSQL reject MIN/MAX on types like boolean or bit
+    let ordered = match db_type {
+        "postgres" if !is_natively_ordered(&info.data_type) => format!("CAST({} AS TEXT)", col),
+        "mssql" if !is_natively_ordered(&info.data_type) => format!("CAST({} AS NVARCHAR(MAX))", col),
+        _ => col.clone(),
+    };
+    let count = if db_type == "mssql" { "COUNT_BIG" } else { "COUNT" };
+    let sql = format!(
+        "SELECT {count}(*), {count}(DISTINCT {col}), SUM(CASE WHEN {col} IS NULL THEN 1 ELSE 0 END), MIN({ordered}), MAX({ordered}) FROM {table}",
+        count = count,
+        col = col,
+        ordered = ordered,
+        table = quote_ident(db_type, &table_name),
+    );
+
+    let result = run_query_with_limit(connection, database.as_deref(), &sql, &app, &pool_manager, 1).await?;
+    let row = result
+        .rows
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Column statistics query returned no rows".to_string())?;
+
+    Ok(ColumnStats {
+        count: value_to_count(row.first()),
+        distinct_count: value_to_count(row.get(1)),
+        null_count: value_to_count(row.get(2)),
+        min: row.get(3).cloned().unwrap_or(serde_json::Value::Null),
+        max: row.get(4).cloned().unwrap_or(serde_json::Value::Null),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableSize {
+    pub schema: Option<String>,
+    pub table: String,
+    /// Bytes used by the rows, including TOAST/LOB data
+    pub data_bytes: u64,
+    pub index_bytes: u64,
+    pub total_bytes: u64,
+    /// The server's row estimate from its statistics; None when it has none
+    pub row_estimate: Option<u64>,
+}
+
+/// On-disk size of every table in a database, largest first. Sizes come from
+/// the catalog (SQLite: the `dbstat` table), so nothing is scanned; row
+/// counts are the server's estimates.
+#[tauri::command]
+pub async fn get_table_sizes(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<TableSize>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    // Columns: schema, table, data bytes, index bytes, row estimate
+    let sql = match connection.db_type.as_str() {
+        "postgres" => "SELECT n.nspname::text, c.relname::text, pg_table_size(c.oid), pg_indexes_size(c.oid), c.reltuples::bigint
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE c.relkind IN ('r', 'p')
+               AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+               AND n.nspname NOT LIKE 'pg_toast%'",
+        "mysql" => "SELECT TABLE_SCHEMA, TABLE_NAME, DATA_LENGTH, INDEX_LENGTH, TABLE_ROWS
+             FROM information_schema.TABLES
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE'",
+        "mssql" => "SELECT SCHEMA_NAME(t.schema_id), t.name,
+                    SUM(CASE WHEN i.index_id IN (0, 1) THEN a.used_pages ELSE 0 END) * 8192,
+                    SUM(CASE WHEN i.index_id > 1 THEN a.used_pages ELSE 0 END) * 8192,
+                    (SELECT SUM(p2.rows) FROM sys.partitions p2 WHERE p2.object_id = t.object_id AND p2.index_id IN (0, 1))
+             FROM sys.tables t
+             JOIN sys.indexes i ON i.object_id = t.object_id
+             JOIN sys.partitions p ON p.object_id = i.object_id AND p.index_id = i.index_id
+             JOIN sys.allocation_units a ON a.container_id = p.partition_id
+             GROUP BY t.object_id, t.schema_id, t.name",
+        _ => "SELECT NULL, m.tbl_name,
+                    SUM(CASE WHEN m.type = 'table' THEN s.pgsize ELSE 0 END),
+                    SUM(CASE WHEN m.type = 'index' THEN s.pgsize ELSE 0 END),
+                    NULL
+             FROM sqlite_master m
+             JOIN dbstat s ON s.name = m.name
+             WHERE m.tbl_name NOT LIKE 'sqlite_%'
+             GROUP BY m.tbl_name",
+    };
+
+    let result = run_query_with_limit(connection, database.as_deref(), sql, &app, &pool_manager, usize::MAX)
+        .await
+        .map_err(|e| match connection.db_type.as_str() {
+            "sqlite" if e.contains("dbstat") => "Table sizes need SQLite built with the dbstat table".to_string(),
+            _ => e,
+        })?;
+
+    let text = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_str()).map(|s| s.to_string());
+    let mut sizes: Vec<TableSize> = result
+        .rows
+        .iter()
+        .filter_map(|row| {
+            let data_bytes = value_to_count(row.get(2));
+            let index_bytes = value_to_count(row.get(3));
+            // Postgres reports -1 for tables that were never analyzed
+            let row_estimate = row.get(4).filter(|v| !v.is_null() && v.as_i64() != Some(-1));
+            Some(TableSize {
+                schema: text(row.first()),
+                table: text(row.get(1))?,
+                data_bytes,
+                index_bytes,
+                total_bytes: data_bytes + index_bytes,
+                row_estimate: row_estimate.map(|v| value_to_count(Some(v))),
+            })
+        })
+        .collect();
+    sizes.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes).then_with(|| a.table.cmp(&b.table)));
+    Ok(sizes)
+}
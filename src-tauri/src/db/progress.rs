@@ -0,0 +1,73 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+// Minimum time between two progress events of the same operation
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Payload emitted on a command's `progress_channel`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    pub operation_id: String,
+    pub done: u64,
+    pub total: Option<u64>,
+    pub phase: String,
+}
+
+/// Emits throttled progress events for a long-running command.
+///
+/// Commands take an optional `progress_channel` (the event name the frontend
+/// listens on); without one every call is a no-op. Events are rate limited,
+/// except for phase changes and `finish`, which are always sent.
+pub struct ProgressReporter {
+    app: tauri::AppHandle,
+    channel: Option<String>,
+    operation_id: String,
+    total: Option<u64>,
+    phase: String,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressReporter {
+    pub fn new(app: &tauri::AppHandle, channel: Option<String>) -> Self {
+        ProgressReporter {
+            app: app.clone(),
+            channel: channel.filter(|c| !c.is_empty()),
+            operation_id: uuid::Uuid::new_v4().to_string(),
+            total: None,
+            phase: String::new(),
+            last_emit: None,
+        }
+    }
+
+    pub fn set_total(&mut self, total: u64) {
+        self.total = Some(total);
+    }
+
+    /// Report progress, skipping the event if one was sent too recently in the same phase
+    pub fn update(&mut self, phase: &str, done: u64) {
+        let phase_changed = self.phase != phase;
+        let due = self.last_emit.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL);
+        if phase_changed || due {
+            self.emit(phase, done);
+        }
+    }
+
+    /// Report the final count of a finished operation
+    pub fn finish(&mut self, done: u64) {
+        self.emit("done", done);
+    }
+
+    fn emit(&mut self, phase: &str, done: u64) {
+        self.phase = phase.to_string();
+        self.last_emit = Some(Instant::now());
+        if let Some(channel) = &self.channel {
+            let _ = self.app.emit(channel, ProgressEvent {
+                operation_id: self.operation_id.clone(),
+                done,
+                total: self.total,
+                phase: phase.to_string(),
+            });
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tauri::State;
+use crate::db::execute::QueryResult;
+
+struct CachedResult {
+    sql: String,
+    stored_at: Instant,
+    result: QueryResult,
+}
+
+/// Results of read-only queries, keyed by `{connection_id}:{database}:{sql hash}`.
+/// Used by `execute_sql` when `query_cache_ttl_secs` is set.
+pub struct QueryCache {
+    entries: Arc<RwLock<HashMap<String, CachedResult>>>,
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn key(connection_id: &str, database: Option<&str>, sql: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        sql.hash(&mut hasher);
+        format!("{}:{}:{:016x}", connection_id, database.unwrap_or(""), hasher.finish())
+    }
+
+    /// The cached result of `sql`, if it was stored less than `ttl` ago
+    pub async fn get(&self, connection_id: &str, database: Option<&str>, sql: &str, ttl: Duration) -> Option<QueryResult> {
+        let entries = self.entries.read().await;
+        entries
+            .get(&Self::key(connection_id, database, sql))
+            .filter(|entry| entry.sql == sql && entry.stored_at.elapsed() < ttl)
+            .map(|entry| entry.result.clone())
+    }
+
+    /// Store a result, dropping entries that have outlived `ttl`
+    pub async fn insert(&self, connection_id: &str, database: Option<&str>, sql: &str, result: QueryResult, ttl: Duration) {
+        let mut entries = self.entries.write().await;
+        entries.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+        entries.insert(
+            Self::key(connection_id, database, sql),
+            CachedResult {
+                sql: sql.to_string(),
+                stored_at: Instant::now(),
+                result,
+            },
+        );
+    }
+
+    /// Drop all cached results of a connection
+    pub async fn invalidate(&self, connection_id: &str) {
+        let prefix = format!("{}:", connection_id);
+        let mut entries = self.entries.write().await;
+        entries.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    pub async fn clear(&self) {
+        let mut entries = self.entries.write().await;
+        entries.clear();
+    }
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drop cached query results, of one connection or of all of them
+#[tauri::command]
+pub async fn clear_query_cache(
+    connection_id: Option<String>,
+    query_cache: State<'_, QueryCache>,
+) -> Result<(), String> {
+    match connection_id {
+        Some(id) => query_cache.invalidate(&id).await,
+        None => query_cache.clear().await,
+    }
+    Ok(())
+}
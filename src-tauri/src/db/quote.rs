@@ -0,0 +1,94 @@
+/// Quote an identifier (table or column name) for the given database type:
+/// backticks for MySQL, brackets for MSSQL, double quotes otherwise. The
+/// quote character itself is escaped by doubling it.
+pub(crate) fn quote_ident(db_type: &str, name: &str) -> String {
+    match db_type {
+        "mysql" => format!("`{}`", name.replace('`', "``")),
+        "mssql" => format!("[{}]", name.replace(']', "]]")),
+        _ => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Render a result value as a SQL literal for the given database type
+pub(crate) fn quote_literal(db_type: &str, value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::Bool(b) => match db_type {
+            "mysql" | "postgres" => if *b { "TRUE" } else { "FALSE" }.to_string(),
+            _ => if *b { "1" } else { "0" }.to_string(),
+        },
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => quote_string(db_type, s),
+        other => quote_string(db_type, &other.to_string()),
+    }
+}
+
+/// Quote text as a string literal for the given database type
+pub(crate) fn quote_string(db_type: &str, s: &str) -> String {
+    let escaped = s.replace('\'', "''");
+    match db_type {
+        // Backslash is an escape character in MySQL string literals by default
+        "mysql" => format!("'{}'", escaped.replace('\\', "\\\\")),
+        "mssql" => format!("N'{}'", escaped),
+        _ => format!("'{}'", escaped),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn quote_ident_doubles_the_dialects_quote_character() {
+        assert_eq!(quote_ident("mysql", "a`b"), "`a``b`");
+        assert_eq!(quote_ident("mssql", "a]b"), "[a]]b]");
+        assert_eq!(quote_ident("postgres", "a\"b"), "\"a\"\"b\"");
+        assert_eq!(quote_ident("sqlite", "a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn quote_ident_leaves_other_quote_characters_alone() {
+        assert_eq!(quote_ident("mysql", "a\"b]c'd"), "`a\"b]c'd`");
+        assert_eq!(quote_ident("mssql", "a`b\"c[d'e"), "[a`b\"c[d'e]");
+        assert_eq!(quote_ident("postgres", "a`b]c'd\\e"), "\"a`b]c'd\\e\"");
+    }
+
+    #[test]
+    fn quote_string_escapes_single_quotes() {
+        assert_eq!(quote_string("mysql", "it's"), "'it''s'");
+        assert_eq!(quote_string("mssql", "it's"), "N'it''s'");
+        assert_eq!(quote_string("postgres", "it's"), "'it''s'");
+        assert_eq!(quote_string("sqlite", "it's"), "'it''s'");
+    }
+
+    #[test]
+    fn quote_string_escapes_backslashes_on_mysql_only() {
+        assert_eq!(quote_string("mysql", "a\\b"), "'a\\\\b'");
+        assert_eq!(quote_string("mysql", "\\'"), "'\\\\'''");
+        assert_eq!(quote_string("mssql", "a\\b"), "N'a\\b'");
+        assert_eq!(quote_string("postgres", "a\\b"), "'a\\b'");
+        assert_eq!(quote_string("sqlite", "a\\b"), "'a\\b'");
+    }
+
+    #[test]
+    fn quote_string_leaves_identifier_quotes_alone() {
+        for db_type in ["mysql", "postgres", "sqlite"] {
+            assert_eq!(quote_string(db_type, "`\"]"), "'`\"]'");
+        }
+        assert_eq!(quote_string("mssql", "`\"]"), "N'`\"]'");
+    }
+
+    #[test]
+    fn quote_literal_renders_each_json_type() {
+        assert_eq!(quote_literal("mysql", &json!(null)), "NULL");
+        assert_eq!(quote_literal("postgres", &json!(true)), "TRUE");
+        assert_eq!(quote_literal("mysql", &json!(false)), "FALSE");
+        assert_eq!(quote_literal("mssql", &json!(true)), "1");
+        assert_eq!(quote_literal("sqlite", &json!(false)), "0");
+        assert_eq!(quote_literal("postgres", &json!(-1.5)), "-1.5");
+        assert_eq!(quote_literal("mssql", &json!("it's")), "N'it''s'");
+        assert_eq!(quote_literal("mysql", &json!("a\\'b")), "'a\\\\''b'");
+        assert_eq!(quote_literal("postgres", &json!({"k": "it's"})), "'{\"k\":\"it''s\"}'");
+    }
+}
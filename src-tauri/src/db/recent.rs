@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use chrono::Utc;
+use crate::db::store::write_json_atomic;
+
+// Entries kept per connection, for databases and for tables separately
+const MAX_RECENT_OBJECTS: usize = 20;
+
+// Serializes load-modify-save, since several commands record at once
+static RECENT_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentKind {
+    Database,
+    Table,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentObject {
+    pub connection_id: String,
+    pub kind: RecentKind,
+    /// Database the object is in; for a database, the database itself
+    pub database: Option<String>,
+    pub name: String,
+    pub opened_at: String, // ISO 8601 format
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentObjects {
+    /// Most recent first
+    pub databases: Vec<RecentObject>,
+    pub tables: Vec<RecentObject>,
+}
+
+fn get_recent_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("Failed to get app data directory")
+        .join("recent_objects.json")
+}
+
+fn load_recent(app: &AppHandle) -> Vec<RecentObject> {
+    let path = get_recent_path(app);
+    if path.exists() {
+        if let Ok(content) = fs::read_to_string(&path) {
+            if let Ok(recent) = serde_json::from_str::<Vec<RecentObject>>(&content) {
+                return recent;
+            }
+        }
+    }
+    vec![]
+}
+
+/// Move an object to the top of its connection's recent list, dropping the
+/// oldest entry of that kind past the cap
+pub(crate) fn record_recent(
+    app: &AppHandle,
+    connection_id: &str,
+    kind: RecentKind,
+    database: Option<&str>,
+    name: &str,
+) -> Result<(), String> {
+    let _guard = RECENT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut recent = load_recent(app);
+    recent.retain(|r| {
+        !(r.connection_id == connection_id && r.kind == kind && r.database.as_deref() == database && r.name == name)
+    });
+    recent.insert(0, RecentObject {
+        connection_id: connection_id.to_string(),
+        kind,
+        database: database.map(|s| s.to_string()),
+        name: name.to_string(),
+        opened_at: Utc::now().to_rfc3339(),
+    });
+
+    let mut kept = 0;
+    recent.retain(|r| {
+        if r.connection_id != connection_id || r.kind != kind {
+            return true;
+        }
+        kept += 1;
+        kept <= MAX_RECENT_OBJECTS
+    });
+    write_json_atomic(&get_recent_path(app), &recent)
+}
+
+/// Forget the recent objects of a deleted connection
+pub(crate) fn remove_recent(app: &AppHandle, connection_id: &str) -> Result<(), String> {
+    let _guard = RECENT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut recent = load_recent(app);
+    let before = recent.len();
+    recent.retain(|r| r.connection_id != connection_id);
+    if recent.len() == before {
+        return Ok(());
+    }
+    write_json_atomic(&get_recent_path(app), &recent)
+}
+
+/// Databases and tables recently opened on a connection, for quick access
+#[tauri::command]
+pub async fn get_recent_objects(
+    connection_id: String,
+    app: AppHandle,
+) -> Result<RecentObjects, String> {
+    let (databases, tables) = load_recent(&app)
+        .into_iter()
+        .filter(|r| r.connection_id == connection_id)
+        .partition(|r| r.kind == RecentKind::Database);
+    Ok(RecentObjects { databases, tables })
+}
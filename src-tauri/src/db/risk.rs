@@ -0,0 +1,269 @@
+use std::ops::Range;
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::column_source::{dotted_name, is_keyword, tokenize_spans, Token};
+use crate::db::connections::{load_connections, ConnectionConfig};
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::PoolManager;
+use crate::db::session::ActiveDatabases;
+
+// Clauses that end the WHERE of a MySQL/Postgres UPDATE or DELETE
+const AFTER_WHERE_KEYWORDS: &[&str] = &["ORDER", "LIMIT", "RETURNING"];
+// Modifiers allowed between UPDATE/DELETE and the table name
+const DML_MODIFIERS: &[&str] = &["LOW_PRIORITY", "QUICK", "IGNORE", "ONLY"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    /// Reads
+    None,
+    /// Writes limited by a WHERE, and other statements that change data or schema
+    Low,
+    /// UPDATE/DELETE without WHERE, TRUNCATE and DROP: confirm before running
+    High,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatementRisk {
+    pub sql: String,
+    pub level: RiskLevel,
+    /// Why the statement was flagged
+    pub reasons: Vec<String>,
+    /// Rows an UPDATE/DELETE would touch, when an estimate was asked for
+    pub estimated_rows: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskReport {
+    /// Highest level among the statements
+    pub level: RiskLevel,
+    pub statements: Vec<StatementRisk>,
+    /// The connection is read-only, so any write will be refused anyway
+    pub read_only: bool,
+}
+
+/// Statements of a script, split at top-level semicolons
+fn split_statements(tokens: &[(Token, Range<usize>)]) -> Vec<&[(Token, Range<usize>)]> {
+    tokens
+        .split(|(token, _)| *token == Token::Symbol(';'))
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+/// Index of the first keyword in `keywords` outside parentheses
+fn top_level_position(tokens: &[(Token, Range<usize>)], keywords: &[&str]) -> Option<usize> {
+    let mut depth = 0usize;
+    tokens.iter().position(|(token, _)| {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        depth == 0 && is_keyword(token, keywords)
+    })
+}
+
+/// `SELECT COUNT(*)` over the rows a single-table UPDATE or DELETE would
+/// touch. None for joins, multi-table forms and anything else it cannot
+/// rewrite safely.
+fn count_sql(sql: &str, statement: &[(Token, Range<usize>)]) -> Option<String> {
+    let text = |range: Range<usize>| sql[range].trim();
+    let mut rest = &statement[1..];
+    let is_update = is_keyword(&statement[0].0, &["UPDATE"]);
+    if !is_update {
+        // DELETE [FROM] table; MySQL's `DELETE t FROM t JOIN ...` names the table twice
+        if let [(first, _), tail @ ..] = rest {
+            if is_keyword(first, &["FROM"]) {
+                rest = tail;
+            }
+        }
+    }
+    while let [(first, _), tail @ ..] = rest {
+        if !is_keyword(first, DML_MODIFIERS) {
+            break;
+        }
+        rest = tail;
+    }
+    if rest.iter().any(|(token, _)| is_keyword(token, &["JOIN", "USING", "FROM", "OUTPUT", "TOP"])) {
+        return None;
+    }
+    let tokens: Vec<Token> = rest.iter().map(|(token, _)| token.clone()).collect();
+    dotted_name(&tokens)?;
+
+    let table_end = if is_update {
+        top_level_position(rest, &["SET"])?
+    } else {
+        top_level_position(rest, &["WHERE", "ORDER", "LIMIT", "RETURNING"]).unwrap_or(rest.len())
+    };
+    let table = &rest[..table_end];
+    // `UPDATE a, b SET ...` updates several tables
+    if table.is_empty() || table.iter().any(|(token, _)| *token == Token::Symbol(',')) {
+        return None;
+    }
+    let table_sql = text(table[0].1.start..table[table.len() - 1].1.end);
+
+    let Some(where_at) = top_level_position(rest, &["WHERE"]) else {
+        return Some(format!("SELECT COUNT(*) FROM {}", table_sql));
+    };
+    let condition = &rest[where_at + 1..];
+    let condition_end = top_level_position(condition, AFTER_WHERE_KEYWORDS).unwrap_or(condition.len());
+    if condition_end == 0 {
+        return None;
+    }
+    let condition_sql = text(condition[0].1.start..condition[condition_end - 1].1.end);
+    Some(format!("SELECT COUNT(*) FROM {} WHERE {}", table_sql, condition_sql))
+}
+
+/// Parts of a statement starting with WITH: the body of each CTE, then the
+/// main statement. A CTE body is the parenthesized group after `AS` (or
+/// `[NOT] MATERIALIZED`); the main statement starts at the first top-level
+/// SELECT, VALUES, TABLE, INSERT, UPDATE, DELETE or MERGE.
+fn with_parts(statement: &[(Token, Range<usize>)]) -> Vec<&[(Token, Range<usize>)]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut body_start = None;
+    for (i, (token, _)) in statement.iter().enumerate().skip(1) {
+        match token {
+            Token::Symbol('(') => {
+                let after_as = is_keyword(&statement[i - 1].0, &["AS", "MATERIALIZED"]);
+                if depth == 0 && after_as {
+                    body_start = Some(i + 1);
+                }
+                depth += 1;
+            }
+            Token::Symbol(')') => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    if let Some(start) = body_start.take() {
+                        parts.push(&statement[start..i]);
+                    }
+                }
+            }
+            _ if depth == 0 && is_keyword(token, &["SELECT", "VALUES", "TABLE", "INSERT", "UPDATE", "DELETE", "MERGE"]) => {
+                parts.push(&statement[i..]);
+                break;
+            }
+            _ => {}
+        }
+    }
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Level and reasons for one statement, from its leading keywords. A WITH
+/// statement is as risky as the riskiest of its CTEs and main statement, so
+/// `WITH ... DELETE FROM t` and Postgres' `WITH d AS (DELETE FROM t) SELECT`
+/// are both caught.
+fn classify(statement: &[(Token, Range<usize>)]) -> (RiskLevel, Vec<String>) {
+    if is_keyword(&statement[0].0, &["WITH"]) {
+        return with_parts(statement)
+            .into_iter()
+            .map(classify)
+            .fold((RiskLevel::None, Vec::new()), |(level, mut reasons), (part_level, part_reasons)| {
+                reasons.extend(part_reasons);
+                (level.max(part_level), reasons)
+            });
+    }
+    let keyword = match &statement[0].0 {
+        Token::Word(word) => word.to_uppercase(),
+        _ => String::new(),
+    };
+    let has_where = top_level_position(statement, &["WHERE"]).is_some();
+    match keyword.as_str() {
+        "SELECT" | "TABLE" | "SHOW" | "PRAGMA" | "VALUES" | "DESCRIBE" | "EXPLAIN" => (RiskLevel::None, Vec::new()),
+        "UPDATE" | "DELETE" if !has_where => (
+            RiskLevel::High,
+            vec![format!("{} without WHERE affects every row of the table", keyword)],
+        ),
+        "UPDATE" | "DELETE" => (RiskLevel::Low, Vec::new()),
+        "TRUNCATE" => (RiskLevel::High, vec!["TRUNCATE removes every row of the table".to_string()]),
+        "DROP" => (RiskLevel::High, vec!["DROP removes the object and its data".to_string()]),
+        "ALTER" if top_level_position(statement, &["DROP"]).is_some() => (
+            RiskLevel::High,
+            vec!["ALTER ... DROP removes a column or constraint".to_string()],
+        ),
+        _ => (RiskLevel::Low, Vec::new()),
+    }
+}
+
+/// Check statements before they run, so the frontend can ask for
+/// confirmation: UPDATE/DELETE without WHERE, TRUNCATE and DROP are high
+/// risk. With `estimate`, the rows an UPDATE/DELETE would touch are
+/// counted first with `SELECT COUNT(*)` and the same WHERE.
+#[tauri::command]
+pub async fn analyze_statement_risk(
+    connection_id: String,
+    sql: String,
+    database: Option<String>,
+    estimate: Option<bool>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<RiskReport, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let database = app.state::<ActiveDatabases>().resolve(connection, database).await;
+    let read_only = matches!(connection.config, ConnectionConfig::Sqlite { read_only: true, .. });
+
+    let tokens = tokenize_spans(&connection.db_type, &sql);
+    let mut statements = Vec::new();
+    for statement in split_statements(&tokens) {
+        let statement_sql = sql[statement[0].1.start..statement[statement.len() - 1].1.end].to_string();
+        let (level, mut reasons) = classify(statement);
+        if read_only && level != RiskLevel::None {
+            reasons.push("The connection is read-only; this statement will be refused".to_string());
+        }
+
+        let mut estimated_rows = None;
+        let counts = is_keyword(&statement[0].0, &["UPDATE", "DELETE"]);
+        if counts && estimate.unwrap_or(false) && !read_only {
+            match count_sql(&sql, statement) {
+                Some(count) => {
+                    match run_query_with_limit(connection, database.as_deref(), &count, &app, &pool_manager, 1).await {
+                        Ok(result) => {
+                            estimated_rows = result
+                                .rows
+                                .first()
+                                .and_then(|row| row.first())
+                                .and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.parse().ok())));
+                        }
+                        Err(e) => reasons.push(format!("Could not estimate affected rows: {}", e)),
+                    }
+                }
+                None => reasons.push("Affected rows cannot be estimated for this statement".to_string()),
+            }
+        }
+
+        statements.push(StatementRisk { sql: statement_sql, level, reasons, estimated_rows });
+    }
+
+    let level = statements.iter().map(|s| s.level).max().unwrap_or(RiskLevel::None);
+    Ok(RiskReport { level, statements, read_only })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(sql: &str) -> RiskLevel {
+        let tokens = tokenize_spans("postgres", sql);
+        classify(split_statements(&tokens)[0]).0
+    }
+
+    #[test]
+    fn with_takes_the_risk_of_its_main_statement() {
+        assert_eq!(level("WITH x AS (SELECT 1) SELECT * FROM x"), RiskLevel::None);
+        assert_eq!(level("WITH x AS (SELECT id FROM s) DELETE FROM t"), RiskLevel::High);
+        assert_eq!(level("WITH x AS (SELECT id FROM s) DELETE FROM t WHERE id IN (SELECT id FROM x)"), RiskLevel::Low);
+        assert_eq!(level("WITH RECURSIVE x (n) AS (SELECT 1) UPDATE t SET a = 1"), RiskLevel::High);
+    }
+
+    #[test]
+    fn with_takes_the_risk_of_data_modifying_ctes() {
+        assert_eq!(level("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"), RiskLevel::High);
+        assert_eq!(level("WITH d AS MATERIALIZED (DELETE FROM t WHERE a = 1 RETURNING *) SELECT * FROM d"), RiskLevel::Low);
+        assert_eq!(level("WITH a AS (SELECT 1), d AS (UPDATE t SET a = 1) SELECT * FROM a"), RiskLevel::High);
+    }
+}
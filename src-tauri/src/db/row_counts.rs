@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::connections::{list_tables, load_connections, resolve_schema, Connection};
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::PoolManager;
+use crate::db::preview::listed_table_sql;
+use crate::db::profile::{get_table_sizes, value_to_count};
+use crate::db::session::ActiveDatabases;
+
+// Counts run at once across both sides, like the table previews
+const COUNT_CONCURRENCY: usize = 4;
+
+// A table's row count, or why it could not be read
+type CountResult = Result<u64, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountStatus {
+    Match,
+    Mismatch,
+    OnlyInA,
+    OnlyInB,
+    /// Counting failed on at least one side; see `error`
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableCountComparison {
+    /// Table name as connection A lists it (B's name for tables only in B)
+    pub table: String,
+    pub count_a: Option<u64>,
+    pub count_b: Option<u64>,
+    pub status: CountStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableCountReport {
+    /// Every table of either side: A's in list order, then those only in B
+    pub tables: Vec<TableCountComparison>,
+    pub matching: usize,
+    pub mismatching: usize,
+    pub only_in_a: usize,
+    pub only_in_b: usize,
+    pub failed: usize,
+    /// Counts are the servers' statistics estimates where they had one
+    pub estimated: bool,
+}
+
+/// Row estimates from the catalog by table name, for the schema
+/// `list_tables` reads; empty when the server cannot report them
+async fn row_estimates(
+    connection: &Connection,
+    database: Option<String>,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> HashMap<String, u64> {
+    let Ok(sizes) = get_table_sizes(connection.id.clone(), database, app.clone(), pool_manager.clone()).await else {
+        return HashMap::new();
+    };
+    let schema = match connection.db_type.as_str() {
+        "postgres" | "mssql" => Some(resolve_schema(&connection.config, None)),
+        _ => None,
+    };
+    sizes
+        .into_iter()
+        .filter(|s| schema.is_none() || s.schema == schema)
+        .filter_map(|s| Some((s.table, s.row_estimate?)))
+        .collect()
+}
+
+/// Compare the row count of every table between two connections (or two
+/// databases of one), e.g. after a migration or to check a replica. Tables
+/// are matched by name, case-insensitively across engines; those on one side
+/// only are flagged. Counts use `COUNT(*)`, run a few tables at a time; with
+/// `estimate` the catalog's row estimates are used instead where available.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn compare_table_counts(
+    connection_id_a: String,
+    database_a: Option<String>,
+    connection_id_b: String,
+    database_b: Option<String>,
+    estimate: Option<bool>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<TableCountReport, String> {
+    let connections = load_connections(&app);
+    let find = |id: &str| {
+        connections
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| "Connection not found".to_string())
+    };
+    let (connection_a, connection_b) = (find(&connection_id_a)?, find(&connection_id_b)?);
+    let same_engine = connection_a.db_type == connection_b.db_type;
+    let key = |name: &str| if same_engine { name.to_string() } else { name.to_lowercase() };
+
+    let active_databases = app.state::<ActiveDatabases>();
+    let database_a = active_databases.resolve(connection_a, database_a).await;
+    let database_b = active_databases.resolve(connection_b, database_b).await;
+
+    let tables_a = list_tables(
+        connection_id_a.clone(),
+        database_a.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases.clone(),
+    ).await?;
+    let tables_b = list_tables(
+        connection_id_b.clone(),
+        database_b.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases,
+    ).await?;
+
+    let estimated = estimate.unwrap_or(false);
+    let (estimates_a, estimates_b) = if estimated {
+        (
+            row_estimates(connection_a, database_a.clone(), &app, &pool_manager).await,
+            row_estimates(connection_b, database_b.clone(), &app, &pool_manager).await,
+        )
+    } else {
+        (HashMap::new(), HashMap::new())
+    };
+
+    // Pair the tables up, keeping A's order; B's leftovers go last
+    let mut b_by_key: HashMap<String, String> = tables_b.iter().map(|t| (key(t), t.clone())).collect();
+    let pairs: Vec<(String, Option<String>)> = tables_a
+        .into_iter()
+        .map(|table_a| {
+            let table_b = b_by_key.remove(&key(&table_a));
+            (table_a, table_b)
+        })
+        .collect();
+    let only_in_b: Vec<String> = tables_b.into_iter().filter(|t| b_by_key.contains_key(&key(t))).collect();
+
+    // One job per side of each common table, so both servers are counted at once
+    let jobs = pairs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (table_a, table_b))| Some((i, table_a.clone(), table_b.clone()?)))
+        .flat_map(|(i, table_a, table_b)| {
+            [
+                (i, false, connection_a, database_a.as_deref(), table_a, &estimates_a),
+                (i, true, connection_b, database_b.as_deref(), table_b, &estimates_b),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let mut counts = stream::iter(jobs)
+        .map(|(i, is_b, connection, database, table, estimates)| {
+            let (app, pool_manager) = (&app, &pool_manager);
+            async move {
+                if let Some(rows) = estimates.get(&table) {
+                    return (i, is_b, Ok(*rows));
+                }
+                let sql = format!("SELECT COUNT(*) FROM {}", listed_table_sql(connection, &table));
+                let result = run_query_with_limit(connection, database, &sql, app, pool_manager, 1)
+                    .await
+                    .map(|r| value_to_count(r.rows.first().and_then(|row| row.first())));
+                (i, is_b, result)
+            }
+        })
+        .buffer_unordered(COUNT_CONCURRENCY);
+
+    let mut results: Vec<(Option<CountResult>, Option<CountResult>)> = pairs.iter().map(|_| (None, None)).collect();
+    while let Some((i, is_b, result)) = counts.next().await {
+        if is_b {
+            results[i].1 = Some(result);
+        } else {
+            results[i].0 = Some(result);
+        }
+    }
+    drop(counts);
+
+    let mut tables = Vec::new();
+    for ((table_a, table_b), (count_a, count_b)) in pairs.into_iter().zip(results) {
+        if table_b.is_none() {
+            tables.push(TableCountComparison {
+                table: table_a,
+                count_a: None,
+                count_b: None,
+                status: CountStatus::OnlyInA,
+                error: None,
+            });
+            continue;
+        }
+        let (count_a, count_b) = (count_a.unwrap_or(Ok(0)), count_b.unwrap_or(Ok(0)));
+        let error = match (&count_a, &count_b) {
+            (Err(a), Err(b)) => Some(format!("A: {}; B: {}", a, b)),
+            (Err(a), _) => Some(format!("A: {}", a)),
+            (_, Err(b)) => Some(format!("B: {}", b)),
+            _ => None,
+        };
+        let (count_a, count_b) = (count_a.ok(), count_b.ok());
+        let status = match (count_a, count_b) {
+            _ if error.is_some() => CountStatus::Failed,
+            (a, b) if a == b => CountStatus::Match,
+            _ => CountStatus::Mismatch,
+        };
+        tables.push(TableCountComparison { table: table_a, count_a, count_b, status, error });
+    }
+    tables.extend(only_in_b.into_iter().map(|table| TableCountComparison {
+        table,
+        count_a: None,
+        count_b: None,
+        status: CountStatus::OnlyInB,
+        error: None,
+    }));
+
+    let count = |status: CountStatus| tables.iter().filter(|t| t.status == status).count();
+    Ok(TableCountReport {
+        matching: count(CountStatus::Match),
+        mismatching: count(CountStatus::Mismatch),
+        only_in_a: count(CountStatus::OnlyInA),
+        only_in_b: count(CountStatus::OnlyInB),
+        failed: count(CountStatus::Failed),
+        tables,
+        estimated,
+    })
+}
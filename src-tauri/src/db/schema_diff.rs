@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use crate::db::connections::{describe_table, list_tables, load_connections, ColumnInfo};
+use crate::db::pool_manager::PoolManager;
+use crate::db::session::ActiveDatabases;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnChange {
+    pub column: String,
+    /// What differs: `type`, `nullable` and/or `default`
+    pub fields: Vec<String>,
+    pub a: ColumnInfo,
+    pub b: ColumnInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub table: String,
+    pub columns_only_in_a: Vec<String>,
+    pub columns_only_in_b: Vec<String>,
+    pub changed_columns: Vec<ColumnChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    pub tables_only_in_a: Vec<String>,
+    pub tables_only_in_b: Vec<String>,
+    /// Tables present on both sides whose columns differ
+    pub changed_tables: Vec<TableDiff>,
+}
+
+/// Engine-neutral name of a column type, for comparing across database types:
+/// lowercase, without length/precision, with common aliases folded together
+fn normalize_type(data_type: &str) -> String {
+    let t = data_type.to_lowercase();
+    let t = t.split('(').next().unwrap_or("").replace("unsigned", "");
+    let t = t.trim();
+    let normalized = match t {
+        "int" | "int4" | "integer" | "mediumint" | "serial" => "integer",
+        "bigint" | "int8" | "bigserial" => "bigint",
+        "smallint" | "int2" | "smallserial" => "smallint",
+        "bool" | "boolean" | "bit" => "boolean",
+        "real" | "float4" => "real",
+        "float" | "float8" | "double" | "double precision" => "double",
+        "decimal" | "numeric" | "money" | "smallmoney" => "decimal",
+        "varchar" | "character varying" | "nvarchar" | "varchar2" => "varchar",
+        "char" | "character" | "bpchar" | "nchar" => "char",
+        "text" | "ntext" | "tinytext" | "mediumtext" | "longtext" | "clob" => "text",
+        "datetime" | "datetime2" | "smalldatetime" | "timestamp" | "timestamp without time zone" => "timestamp",
+        "timestamptz" | "timestamp with time zone" | "datetimeoffset" => "timestamptz",
+        "time" | "time without time zone" => "time",
+        "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "binary" | "varbinary" | "image" => "binary",
+        "uuid" | "uniqueidentifier" => "uuid",
+        "json" | "jsonb" => "json",
+        other => other,
+    };
+    normalized.to_string()
+}
+
+/// Fields in which two columns differ. Across engines types are compared by
+/// `normalize_type` and defaults are skipped, since each engine spells them
+/// differently (`nextval(...)`, `((0))`, casts).
+fn column_differences(a: &ColumnInfo, b: &ColumnInfo, same_engine: bool) -> Vec<String> {
+    let mut fields = Vec::new();
+    let types_match = if same_engine {
+        a.data_type.eq_ignore_ascii_case(&b.data_type)
+    } else {
+        normalize_type(&a.data_type) == normalize_type(&b.data_type)
+    };
+    if !types_match {
+        fields.push("type".to_string());
+    }
+    if a.nullable != b.nullable {
+        fields.push("nullable".to_string());
+    }
+    if same_engine && a.default != b.default {
+        fields.push("default".to_string());
+    }
+    fields
+}
+
+/// Compare the tables of two databases, on the same connection or on two
+/// different ones. Names are matched case-insensitively when the two sides
+/// are different database types.
+#[tauri::command]
+pub async fn diff_schemas(
+    connection_id_a: String,
+    database_a: Option<String>,
+    connection_id_b: String,
+    database_b: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<SchemaDiff, String> {
+    let connections = load_connections(&app);
+    let find = |id: &str| {
+        connections
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| "Connection not found".to_string())
+    };
+    let (connection_a, connection_b) = (find(&connection_id_a)?, find(&connection_id_b)?);
+    let same_engine = connection_a.db_type == connection_b.db_type;
+    let key = |name: &str| if same_engine { name.to_string() } else { name.to_lowercase() };
+
+    let active_databases = app.state::<ActiveDatabases>();
+    let database_a = active_databases.resolve(connection_a, database_a).await;
+    let database_b = active_databases.resolve(connection_b, database_b).await;
+
+    let tables_a = list_tables(
+        connection_id_a.clone(),
+        database_a.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases.clone(),
+    ).await?;
+    let tables_b = list_tables(
+        connection_id_b.clone(),
+        database_b.clone(),
+        None,
+        app.clone(),
+        pool_manager.clone(),
+        active_databases,
+    ).await?;
+
+    let mut b_by_key: HashMap<String, String> = tables_b.iter().map(|t| (key(t), t.clone())).collect();
+    let mut diff = SchemaDiff {
+        tables_only_in_a: Vec::new(),
+        tables_only_in_b: Vec::new(),
+        changed_tables: Vec::new(),
+    };
+
+    for table_a in tables_a {
+        let Some(table_b) = b_by_key.remove(&key(&table_a)) else {
+            diff.tables_only_in_a.push(table_a);
+            continue;
+        };
+        let columns_a = describe_table(
+            connection_id_a.clone(),
+            table_a.clone(),
+            database_a.clone(),
+            None,
+            app.clone(),
+            pool_manager.clone(),
+        ).await?;
+        let columns_b = describe_table(
+            connection_id_b.clone(),
+            table_b,
+            database_b.clone(),
+            None,
+            app.clone(),
+            pool_manager.clone(),
+        ).await?;
+
+        let mut columns_b: Vec<Option<ColumnInfo>> = columns_b.into_iter().map(Some).collect();
+        let mut table_diff = TableDiff {
+            table: table_a,
+            columns_only_in_a: Vec::new(),
+            columns_only_in_b: Vec::new(),
+            changed_columns: Vec::new(),
+        };
+        for a in columns_a {
+            let matching = columns_b
+                .iter_mut()
+                .find(|b| b.as_ref().is_some_and(|b| key(&b.name) == key(&a.name)))
+                .and_then(Option::take);
+            match matching {
+                None => table_diff.columns_only_in_a.push(a.name),
+                Some(b) => {
+                    let fields = column_differences(&a, &b, same_engine);
+                    if !fields.is_empty() {
+                        table_diff.changed_columns.push(ColumnChange { column: a.name.clone(), fields, a, b });
+                    }
+                }
+            }
+        }
+        table_diff.columns_only_in_b = columns_b.into_iter().flatten().map(|b| b.name).collect();
+
+        if !table_diff.columns_only_in_a.is_empty()
+            || !table_diff.columns_only_in_b.is_empty()
+            || !table_diff.changed_columns.is_empty()
+        {
+            diff.changed_tables.push(table_diff);
+        }
+    }
+
+    diff.tables_only_in_b = tables_b.into_iter().filter(|t| b_by_key.contains_key(&key(t))).collect();
+    Ok(diff)
+}
@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::Instant;
+use tauri::{Manager, State};
+use crate::db::connections::load_connections;
+use crate::db::pool_manager::PoolManager;
+use crate::db::progress::ProgressReporter;
+use crate::db::query_cache::QueryCache;
+use crate::db::transaction::{begin_statement, commit_statement, rollback_statement, HeldConnection};
+use crate::error::AppError;
+
+// Longest SQL text echoed back per statement in the results
+const STATEMENT_PREVIEW_CHARS: usize = 200;
+
+/// A complete statement produced by `StatementSplitter`
+#[derive(Debug, Clone)]
+pub struct SplitStatement {
+    /// 1-based line the statement starts on
+    pub line: usize,
+    pub sql: String,
+}
+
+/// Splits SQL text into statements on `;`, fed one line at a time so large
+/// scripts never have to be held in memory.
+///
+/// Semicolons inside quotes, identifiers and comments are ignored, as are
+/// Postgres dollar-quoted bodies. MSSQL scripts are split into batches on
+/// lines holding only `GO` instead, as SSMS does, since procedure and trigger
+/// bodies contain `;`. MySQL `DELIMITER` directives are not supported.
+pub struct StatementSplitter {
+    dialect: String,
+    current: String,
+    has_code: bool,
+    start_line: usize,
+    line_no: usize,
+    quote: Option<char>,
+    block_comment: bool,
+    dollar_tag: Option<Vec<char>>,
+}
+
+impl StatementSplitter {
+    pub fn new(dialect: &str) -> Self {
+        StatementSplitter {
+            dialect: dialect.to_string(),
+            current: String::new(),
+            has_code: false,
+            start_line: 0,
+            line_no: 0,
+            quote: None,
+            block_comment: false,
+            dollar_tag: None,
+        }
+    }
+
+    /// Feed the next line (without its line break) and collect any statements it completes
+    pub fn push_line(&mut self, line: &str) -> Vec<SplitStatement> {
+        self.line_no += 1;
+        let mut statements = Vec::new();
+
+        let in_code = self.quote.is_none() && !self.block_comment && self.dollar_tag.is_none();
+        if in_code && self.dialect == "mssql" && line.trim().eq_ignore_ascii_case("go") {
+            statements.extend(self.flush());
+            return statements;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+
+            if let Some(tag) = &self.dollar_tag {
+                if chars[i..].starts_with(tag) {
+                    let len = tag.len();
+                    self.current.extend(&chars[i..i + len]);
+                    self.dollar_tag = None;
+                    i += len;
+                } else {
+                    self.current.push(c);
+                    i += 1;
+                }
+                continue;
+            }
+
+            if self.block_comment {
+                self.current.push(c);
+                if c == '*' && next == Some('/') {
+                    self.current.push('/');
+                    self.block_comment = false;
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+
+            if let Some(close) = self.quote {
+                self.current.push(c);
+                if c == close {
+                    // A doubled closing quote is an escaped quote
+                    if next == Some(close) {
+                        self.current.push(close);
+                        i += 1;
+                    } else {
+                        self.quote = None;
+                    }
+                } else if c == '\\' && self.dialect == "mysql" && close != '`' {
+                    if let Some(escaped) = next {
+                        self.current.push(escaped);
+                        i += 1;
+                    }
+                }
+                i += 1;
+                continue;
+            }
+
+            let line_comment = (c == '-' && next == Some('-')) || (c == '#' && self.dialect == "mysql");
+            if line_comment {
+                self.current.extend(&chars[i..]);
+                break;
+            }
+            if c == '/' && next == Some('*') {
+                self.current.push_str("/*");
+                self.block_comment = true;
+                i += 2;
+                continue;
+            }
+            if c == ';' && self.dialect != "mssql" {
+                statements.extend(self.flush());
+                i += 1;
+                continue;
+            }
+            if c == '$' && self.dialect == "postgres" {
+                if let Some(tag) = dollar_tag_at(&chars, i) {
+                    self.mark_code();
+                    self.current.extend(&tag);
+                    i += tag.len();
+                    self.dollar_tag = Some(tag);
+                    continue;
+                }
+            }
+
+            self.quote = match c {
+                '\'' | '"' => Some(c),
+                '`' if self.dialect == "mysql" => Some('`'),
+                '[' if self.dialect == "mssql" => Some(']'),
+                _ => None,
+            };
+            if !c.is_whitespace() {
+                self.mark_code();
+            }
+            self.current.push(c);
+            i += 1;
+        }
+
+        self.current.push('\n');
+        statements
+    }
+
+    /// Return the trailing statement that has no terminating semicolon
+    pub fn finish(mut self) -> Option<SplitStatement> {
+        self.flush()
+    }
+
+    fn mark_code(&mut self) {
+        if !self.has_code {
+            self.has_code = true;
+            self.start_line = self.line_no;
+        }
+    }
+
+    fn flush(&mut self) -> Option<SplitStatement> {
+        let sql = std::mem::take(&mut self.current);
+        let has_code = std::mem::replace(&mut self.has_code, false);
+        // Chunks holding nothing but comments and whitespace are dropped
+        if !has_code {
+            return None;
+        }
+        Some(SplitStatement {
+            line: self.start_line,
+            sql: sql.trim().to_string(),
+        })
+    }
+}
+
+/// Opening `$tag$` of a Postgres dollar-quoted string at `start`, if there is one
+fn dollar_tag_at(chars: &[char], start: usize) -> Option<Vec<char>> {
+    let mut end = start + 1;
+    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+        end += 1;
+    }
+    // Tags cannot start with a digit, which also rules out $1 style parameters
+    let tag_starts_with_digit = chars.get(start + 1).is_some_and(|c| c.is_ascii_digit());
+    if chars.get(end) == Some(&'$') && !tag_starts_with_digit {
+        Some(chars[start..=end].to_vec())
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScriptStatementResult {
+    pub line: usize,
+    /// Start of the statement text, cut to a preview
+    pub sql: String,
+    pub success: bool,
+    pub rows_affected: Option<u64>,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScriptResult {
+    pub statements: Vec<ScriptStatementResult>,
+    /// Set when a failure rolled back the statements that had already run
+    pub rolled_back: bool,
+}
+
+/// Execute a `.sql` script statement by statement, reading it line by line.
+///
+/// With `stop_on_error` the whole script runs in one transaction and the first
+/// failure rolls it back; otherwise every statement runs on its own and
+/// failures are reported without stopping. Progress is reported in bytes read.
+#[tauri::command]
+pub async fn run_sql_file(
+    connection_id: String,
+    file_path: String,
+    database: Option<String>,
+    stop_on_error: bool,
+    progress_channel: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<ScriptResult, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let file = File::open(&file_path).map_err(|e| format!("Failed to open {}: {}", file_path, e))?;
+    let file_len = file.metadata().map(|m| m.len()).ok();
+    let reader = BufReader::new(file);
+
+    // One connection for the whole script, so a transaction spans every statement.
+    // Scripts are mostly writes, so SQLite keeps the file's write lock throughout.
+    let _write_guard = pool_manager.sqlite_write_guard(connection).await;
+    let mut conn = HeldConnection::open(connection, database.as_deref(), &pool_manager).await?;
+
+    let mut progress = ProgressReporter::new(&app, progress_channel);
+    if let Some(len) = file_len {
+        progress.set_total(len);
+    }
+
+    if stop_on_error {
+        conn.control(begin_statement(&connection.db_type)).await?;
+    }
+
+    let mut splitter = StatementSplitter::new(&connection.db_type);
+    let mut results = Vec::new();
+    let mut bytes_read = 0u64;
+    let mut failed = false;
+    let mut read_error = None;
+    let mut lines = reader.lines();
+
+    while !failed {
+        let (statements, at_end) = match lines.next() {
+            Some(Err(e)) => {
+                // Stop without executing the partial statement, but still close the transaction
+                read_error = Some(e);
+                break;
+            }
+            Some(Ok(line)) => {
+                bytes_read += line.len() as u64 + 1;
+                (splitter.push_line(&line), false)
+            }
+            None => {
+                let rest = std::mem::replace(&mut splitter, StatementSplitter::new(&connection.db_type));
+                (rest.finish().into_iter().collect(), true)
+            }
+        };
+
+        for statement in statements {
+            let started = Instant::now();
+            let result = conn.execute(&statement.sql).await;
+            failed = result.is_err();
+            results.push(ScriptStatementResult {
+                line: statement.line,
+                sql: statement.sql.chars().take(STATEMENT_PREVIEW_CHARS).collect(),
+                success: result.is_ok(),
+                rows_affected: result.as_ref().ok().copied(),
+                error: result.err(),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
+            progress.update("executing", bytes_read);
+            if failed && stop_on_error {
+                break;
+            }
+            failed = false;
+        }
+
+        if at_end {
+            break;
+        }
+    }
+
+    let rolled_back = (failed || read_error.is_some()) && stop_on_error;
+    if stop_on_error {
+        let end = if rolled_back {
+            rollback_statement(&connection.db_type)
+        } else {
+            commit_statement(&connection.db_type)
+        };
+        conn.control(end).await?;
+    }
+    app.state::<QueryCache>().invalidate(&connection.id).await;
+    if let Some(e) = read_error {
+        return Err(AppError::from(e).into());
+    }
+
+    progress.finish(bytes_read);
+    Ok(ScriptResult {
+        statements: results,
+        rolled_back,
+    })
+}
@@ -0,0 +1,562 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tauri::{Manager, State};
+use sqlx::Row;
+use tiberius::{Client, QueryItem};
+use tokio::net::TcpStream;
+use tokio_util::compat::Compat;
+use futures_util::TryStreamExt;
+use crate::db::connections::{create_mssql_client, load_connections, maintenance_database, Connection, ConnectionConfig};
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::{PoolManager, DatabasePool};
+use crate::db::session::ActiveDatabases;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerInfo {
+    /// Version string as reported by the server
+    pub version: String,
+    pub major: u32,
+    pub minor: u32,
+    pub edition: Option<String>,
+    /// MySQL connection that is actually served by MariaDB
+    #[serde(default)]
+    pub is_mariadb: bool,
+}
+
+/// Server info cached per connection id
+pub struct ServerInfoCache {
+    entries: Arc<RwLock<HashMap<String, ServerInfo>>>,
+}
+
+impl ServerInfoCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn get(&self, connection_id: &str) -> Option<ServerInfo> {
+        let entries = self.entries.read().await;
+        entries.get(connection_id).cloned()
+    }
+
+    pub async fn insert(&self, connection_id: &str, info: ServerInfo) {
+        let mut entries = self.entries.write().await;
+        entries.insert(connection_id.to_string(), info);
+    }
+
+    pub async fn invalidate(&self, connection_id: &str) {
+        let mut entries = self.entries.write().await;
+        entries.remove(connection_id);
+    }
+}
+
+impl Default for ServerInfoCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse major and minor from the first `N.N` sequence in a version string
+/// (e.g. "PostgreSQL 15.3 on x86_64" or "8.0.33-0ubuntu")
+fn parse_version(version: &str) -> (u32, u32) {
+    version
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .find_map(|token| {
+            let mut parts = token.split('.');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            Some((major, minor))
+        })
+        .unwrap_or((0, 0))
+}
+
+/// Query the server version of a connection
+async fn fetch_server_info(
+    connection: &Connection,
+    database: Option<&str>,
+    pool_manager: &PoolManager,
+) -> Result<ServerInfo, String> {
+    // (full version text, text to parse the numeric version from, edition)
+    let (version, numeric, edition) = if connection.db_type == "mssql" {
+        match &connection.config {
+            ConnectionConfig::Mssql { database: config_db, .. } => {
+                let db_name = database.or(config_db.as_deref());
+                let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, db_name).await?;
+
+                // @@VERSION leads with the marketing year, ProductVersion has the real numbers
+                let mut stream: tiberius::QueryStream<'_> = client.query(
+                    "SELECT @@VERSION, CAST(SERVERPROPERTY('ProductVersion') AS NVARCHAR(128)), CAST(SERVERPROPERTY('Edition') AS NVARCHAR(128))",
+                    &[]
+                ).await
+                    .map_err(|e| format!("查询服务器版本失败: {}", e))?;
+
+                let mut result = None;
+                while let Some(item) = stream.try_next().await
+                    .map_err(|e| format!("读取结果失败: {}", e))? {
+                    if let QueryItem::Row(row) = item {
+                        let get = |i: usize| row.try_get::<&str, _>(i).ok().flatten().map(|s| s.to_string());
+                        result = Some((get(0).unwrap_or_default(), get(1).unwrap_or_default(), get(2)));
+                    }
+                }
+                result.ok_or_else(|| "查询服务器版本失败".to_string())?
+            }
+            _ => return Err("无效的 MSSQL 配置".to_string()),
+        }
+    } else {
+        let pool = pool_manager.get_or_create_pool(connection, database).await?;
+
+        let (version, edition): (String, Option<String>) = match pool {
+            DatabasePool::Sqlite(p) => sqlx::query("SELECT sqlite_version()")
+                .fetch_one(&p)
+                .await
+                .map(|row| (row.get(0), None)),
+            DatabasePool::Mysql(p) => sqlx::query("SELECT VERSION(), @@version_comment")
+                .fetch_one(&p)
+                .await
+                .map(|row| (row.get(0), row.try_get(1).ok())),
+            DatabasePool::Postgres(p) => sqlx::query("SELECT version()")
+                .fetch_one(&p)
+                .await
+                .map(|row| (row.get(0), None)),
+        }
+        .map_err(|e| format!("Failed to query server version: {}", e))?;
+
+        (version.clone(), version, edition)
+    };
+
+    // MariaDB speaks the MySQL protocol and only tells itself apart in the version text
+    let is_mariadb = connection.db_type == "mysql" && version.to_lowercase().contains("mariadb");
+    // Old clients needed a 5.5.5- prefix to accept a major version of 10
+    let numeric = numeric.strip_prefix("5.5.5-").unwrap_or(&numeric);
+    let (major, minor) = parse_version(numeric);
+    Ok(ServerInfo {
+        version,
+        major,
+        minor,
+        edition,
+        is_mariadb,
+    })
+}
+
+/// Server info of a connection, from the cache when available
+pub(crate) async fn server_info(
+    connection: &Connection,
+    database: Option<&str>,
+    pool_manager: &PoolManager,
+    server_info_cache: &ServerInfoCache,
+) -> Result<ServerInfo, String> {
+    if let Some(info) = server_info_cache.get(&connection.id).await {
+        return Ok(info);
+    }
+    let info = fetch_server_info(connection, database, pool_manager).await?;
+    server_info_cache.insert(&connection.id, info.clone()).await;
+    Ok(info)
+}
+
+/// Version of the connected server, for gating features in the UI
+#[tauri::command]
+pub async fn get_server_info(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    server_info_cache: State<'_, ServerInfoCache>,
+) -> Result<ServerInfo, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    server_info(connection, database.as_deref(), &pool_manager, &server_info_cache).await
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub database: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+}
+
+/// List client sessions on the server, excluding the one running this query.
+/// SQLite has no server sessions and returns an empty list.
+#[tauri::command]
+pub async fn list_sessions(
+    connection_id: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<SessionInfo>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    if connection.db_type == "sqlite" {
+        return Ok(vec![]);
+    }
+
+    // Handle MSSQL separately since it uses tiberius instead of sqlx
+    if connection.db_type == "mssql" {
+        let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, None).await?;
+
+        let mut stream: tiberius::QueryStream<'_> = client.query(
+            "SELECT s.session_id, s.login_name, s.host_name, DB_NAME(s.database_id), s.status, t.text
+             FROM sys.dm_exec_sessions s
+             LEFT JOIN sys.dm_exec_requests r ON r.session_id = s.session_id
+             OUTER APPLY sys.dm_exec_sql_text(r.sql_handle) t
+             WHERE s.is_user_process = 1 AND s.session_id <> @@SPID
+             ORDER BY s.session_id",
+            &[]
+        ).await
+            .map_err(|e| format!("查询会话列表失败: {}", e))?;
+
+        let mut sessions = Vec::new();
+        while let Some(item) = stream.try_next().await
+            .map_err(|e| format!("读取结果失败: {}", e))? {
+            if let QueryItem::Row(row) = item {
+                let get = |i: usize| row.try_get::<&str, _>(i).ok().flatten().map(|s| s.to_string());
+                sessions.push(SessionInfo {
+                    id: row.try_get::<i16, _>(0).ok().flatten().unwrap_or(0).to_string(),
+                    user: get(1),
+                    host: get(2),
+                    database: get(3),
+                    state: get(4),
+                    query: get(5),
+                });
+            }
+        }
+
+        return Ok(sessions);
+    }
+
+    let pool = pool_manager.get_maintenance_pool(connection).await?;
+
+    match pool {
+        DatabasePool::Mysql(p) => {
+            let rows = sqlx::query("SHOW FULL PROCESSLIST")
+                .fetch_all(&p)
+                .await
+                .map_err(|e| format!("Failed to list sessions: {}", e))?;
+
+            // Columns: Id, User, Host, db, Command, Time, State, Info
+            let sessions = rows
+                .into_iter()
+                .map(|row| {
+                    let command: Option<String> = row.try_get(4).ok();
+                    let state: Option<String> = row.try_get::<Option<String>, _>(6).ok().flatten().filter(|s| !s.is_empty());
+                    SessionInfo {
+                        id: row.try_get::<u64, _>(0).map(|id| id.to_string()).unwrap_or_default(),
+                        user: row.try_get(1).ok(),
+                        host: row.try_get(2).ok(),
+                        database: row.try_get::<Option<String>, _>(3).ok().flatten(),
+                        state: state.or(command),
+                        query: row.try_get::<Option<String>, _>(7).ok().flatten(),
+                    }
+                })
+                .filter(|s| s.query.as_deref() != Some("SHOW FULL PROCESSLIST"))
+                .collect();
+
+            Ok(sessions)
+        }
+        DatabasePool::Postgres(p) => {
+            let rows = sqlx::query(
+                "SELECT pid, usename::text, client_addr::text, datname::text, state, query
+                 FROM pg_stat_activity
+                 WHERE pid <> pg_backend_pid() AND backend_type = 'client backend'
+                 ORDER BY pid"
+            )
+                .fetch_all(&p)
+                .await
+                .map_err(|e| format!("Failed to list sessions: {}", e))?;
+
+            let sessions = rows
+                .into_iter()
+                .map(|row| SessionInfo {
+                    id: row.get::<i32, _>(0).to_string(),
+                    user: row.get(1),
+                    host: row.get(2),
+                    database: row.get(3),
+                    state: row.get(4),
+                    query: row.get(5),
+                })
+                .collect();
+
+            Ok(sessions)
+        }
+        DatabasePool::Sqlite(_) => Ok(vec![]),
+    }
+}
+
+/// Terminate a server session by id
+#[tauri::command]
+pub async fn kill_session(
+    connection_id: String,
+    session_id: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<(), String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    // Session ids are numeric on every engine; parsing also keeps the id out of the SQL text
+    let id: i64 = session_id
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid session id: {}", session_id))?;
+
+    if connection.db_type == "mssql" {
+        let mut client: Client<Compat<TcpStream>> = create_mssql_client(&connection.config, None).await?;
+        client.simple_query(format!("KILL {}", id))
+            .await
+            .map_err(|e| format!("终止会话失败: {}", e))?
+            .into_results()
+            .await
+            .map_err(|e| format!("终止会话失败: {}", e))?;
+        return Ok(());
+    }
+
+    let pool = pool_manager.get_maintenance_pool(connection).await?;
+
+    match pool {
+        DatabasePool::Mysql(p) => {
+            sqlx::query(&format!("KILL {}", id))
+                .execute(&p)
+                .await
+                .map_err(|e| format!("Failed to kill session: {}", e))?;
+            Ok(())
+        }
+        DatabasePool::Postgres(p) => {
+            let terminated: bool = sqlx::query("SELECT pg_terminate_backend($1)")
+                .bind(id as i32)
+                .fetch_one(&p)
+                .await
+                .map_err(|e| format!("Failed to kill session: {}", e))?
+                .get(0);
+            if terminated {
+                Ok(())
+            } else {
+                Err(format!("Session {} not found", id))
+            }
+        }
+        DatabasePool::Sqlite(_) => Err("SQLite has no server sessions".to_string()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockingQuery {
+    /// Session waiting for the lock
+    pub blocked_id: String,
+    pub blocked_user: Option<String>,
+    pub blocked_query: Option<String>,
+    /// Session holding it; its query is the last one it ran, which may not be
+    /// the one that took the lock
+    pub blocking_id: String,
+    pub blocking_user: Option<String>,
+    pub blocking_query: Option<String>,
+    /// How long the waiting statement (MSSQL: the wait) has lasted
+    pub wait_seconds: Option<f64>,
+    /// What is waited for, e.g. `relation public.orders` or `KEY`
+    pub resource: Option<String>,
+}
+
+// InnoDB lock waits: performance_schema on MySQL 8.0, information_schema before
+const MYSQL_LOCK_WAITS: &str =
+    "SELECT CAST(r.trx_mysql_thread_id AS CHAR), rp.USER, r.trx_query,
+            CAST(b.trx_mysql_thread_id AS CHAR), bp.USER, b.trx_query,
+            TIMESTAMPDIFF(SECOND, r.trx_wait_started, NOW()),
+            CONCAT(l.LOCK_TYPE, ' ', l.OBJECT_SCHEMA, '.', l.OBJECT_NAME)
+     FROM performance_schema.data_lock_waits w
+     JOIN information_schema.INNODB_TRX r ON r.trx_id = w.REQUESTING_ENGINE_TRANSACTION_ID
+     JOIN information_schema.INNODB_TRX b ON b.trx_id = w.BLOCKING_ENGINE_TRANSACTION_ID
+     LEFT JOIN performance_schema.data_locks l ON l.ENGINE_LOCK_ID = w.REQUESTING_ENGINE_LOCK_ID
+     LEFT JOIN information_schema.PROCESSLIST rp ON rp.ID = r.trx_mysql_thread_id
+     LEFT JOIN information_schema.PROCESSLIST bp ON bp.ID = b.trx_mysql_thread_id
+     ORDER BY 7 DESC";
+const MYSQL_LOCK_WAITS_LEGACY: &str =
+    "SELECT CAST(r.trx_mysql_thread_id AS CHAR), rp.USER, r.trx_query,
+            CAST(b.trx_mysql_thread_id AS CHAR), bp.USER, b.trx_query,
+            TIMESTAMPDIFF(SECOND, r.trx_wait_started, NOW()),
+            CONCAT(l.lock_type, ' ', l.lock_table)
+     FROM information_schema.INNODB_LOCK_WAITS w
+     JOIN information_schema.INNODB_TRX r ON r.trx_id = w.requesting_trx_id
+     JOIN information_schema.INNODB_TRX b ON b.trx_id = w.blocking_trx_id
+     LEFT JOIN information_schema.INNODB_LOCKS l ON l.lock_id = w.requested_lock_id
+     LEFT JOIN information_schema.PROCESSLIST rp ON rp.ID = r.trx_mysql_thread_id
+     LEFT JOIN information_schema.PROCESSLIST bp ON bp.ID = b.trx_mysql_thread_id
+     ORDER BY 7 DESC";
+
+/// Sessions waiting on a lock, each paired with a session that holds it, to
+/// find what is stuck and why; longest waits first. A session blocked by
+/// several others appears once per blocker. SQLite returns an empty list.
+#[tauri::command]
+pub async fn get_blocking_queries(
+    connection_id: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<BlockingQuery>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let database = maintenance_database(&connection.config);
+
+    let result = match connection.db_type.as_str() {
+        "mysql" => match run_query_with_limit(connection, database, MYSQL_LOCK_WAITS, &app, &pool_manager, usize::MAX).await {
+            Ok(result) => result,
+            Err(_) => run_query_with_limit(connection, database, MYSQL_LOCK_WAITS_LEGACY, &app, &pool_manager, usize::MAX).await?,
+        },
+        "postgres" => {
+            let sql = "SELECT a.pid::text, a.usename::text, a.query,
+                              b.pid::text, b.usename::text, b.query,
+                              EXTRACT(EPOCH FROM now() - a.query_start)::float8,
+                              (SELECT l.locktype || COALESCE(' ' || l.relation::regclass::text, '')
+                               FROM pg_locks l WHERE l.pid = a.pid AND NOT l.granted LIMIT 1)
+                       FROM pg_stat_activity a
+                       CROSS JOIN LATERAL unnest(pg_blocking_pids(a.pid)) AS blocker(pid)
+                       JOIN pg_stat_activity b ON b.pid = blocker.pid
+                       ORDER BY 7 DESC NULLS LAST";
+            run_query_with_limit(connection, database, sql, &app, &pool_manager, usize::MAX).await?
+        }
+        "mssql" => {
+            let sql = "SELECT CAST(w.session_id AS nvarchar(20)), rs.login_name, rt.text,
+                              CAST(w.blocking_session_id AS nvarchar(20)), bs.login_name, bt.text,
+                              CAST(w.wait_duration_ms / 1000.0 AS float),
+                              COALESCE(l.resource_type, w.wait_type)
+                       FROM sys.dm_os_waiting_tasks w
+                       JOIN sys.dm_exec_sessions rs ON rs.session_id = w.session_id
+                       JOIN sys.dm_exec_sessions bs ON bs.session_id = w.blocking_session_id
+                       LEFT JOIN sys.dm_tran_locks l ON l.lock_owner_address = w.resource_address
+                       LEFT JOIN sys.dm_exec_requests rr ON rr.session_id = w.session_id
+                       OUTER APPLY sys.dm_exec_sql_text(rr.sql_handle) rt
+                       LEFT JOIN sys.dm_exec_connections bc ON bc.session_id = w.blocking_session_id
+                       OUTER APPLY sys.dm_exec_sql_text(bc.most_recent_sql_handle) bt
+                       WHERE w.blocking_session_id IS NOT NULL AND w.blocking_session_id <> w.session_id
+                       ORDER BY w.wait_duration_ms DESC";
+            run_query_with_limit(connection, None, sql, &app, &pool_manager, usize::MAX).await?
+        }
+        _ => return Ok(vec![]),
+    };
+
+    let text = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).map(|s| s.to_string());
+    let waits = result
+        .rows
+        .iter()
+        .map(|row| BlockingQuery {
+            blocked_id: text(row.first()).unwrap_or_default(),
+            blocked_user: text(row.get(1)),
+            blocked_query: text(row.get(2)),
+            blocking_id: text(row.get(3)).unwrap_or_default(),
+            blocking_user: text(row.get(4)),
+            blocking_query: text(row.get(5)),
+            wait_seconds: row.get(6).and_then(|v| v.as_f64()),
+            resource: text(row.get(7)),
+        })
+        .collect();
+    Ok(waits)
+}
+
+#[derive(Debug, Serialize)]
+pub struct CharsetInfo {
+    /// Server default; the code page of the server collation on MSSQL (`CP936`)
+    pub server_charset: Option<String>,
+    pub server_collation: Option<String>,
+    /// Encoding of the database (SQLite: `PRAGMA encoding`)
+    pub database_charset: Option<String>,
+    pub database_collation: Option<String>,
+    /// Character set the server expects from this client
+    pub client_charset: Option<String>,
+    /// Every `character_set_*` / `collation_*` variable on MySQL, for diagnosis
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Character sets and collations in effect on a connection, to diagnose
+/// garbled text: the server's, the database's and the client's
+#[tauri::command]
+pub async fn get_connection_charset(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<CharsetInfo, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let database = app.state::<ActiveDatabases>().resolve(connection, database).await;
+
+    let sql = match connection.db_type.as_str() {
+        "sqlite" => "PRAGMA encoding",
+        "mysql" => "SHOW VARIABLES WHERE Variable_name LIKE 'character\\_set\\_%' OR Variable_name LIKE 'collation\\_%'",
+        "mssql" => {
+            "SELECT 'CP' + CAST(COLLATIONPROPERTY(CAST(SERVERPROPERTY('Collation') AS nvarchar(128)), 'CodePage') AS varchar(10)), \
+             CAST(SERVERPROPERTY('Collation') AS nvarchar(128)), \
+             'CP' + CAST(COLLATIONPROPERTY(CAST(DATABASEPROPERTYEX(DB_NAME(), 'Collation') AS nvarchar(128)), 'CodePage') AS varchar(10)), \
+             CAST(DATABASEPROPERTYEX(DB_NAME(), 'Collation') AS nvarchar(128))"
+        }
+        _ => {
+            "SELECT current_setting('server_encoding'), NULL, pg_encoding_to_char(encoding), datcollate, \
+             current_setting('client_encoding') FROM pg_database WHERE datname = current_database()"
+        }
+    };
+    let result = run_query_with_limit(connection, database.as_deref(), sql, &app, &pool_manager, usize::MAX).await?;
+    let text = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).map(|s| s.to_string());
+    let first = result.rows.first();
+    let column = |i: usize| text(first.and_then(|row| row.get(i)));
+
+    let info = match connection.db_type.as_str() {
+        "sqlite" => CharsetInfo {
+            server_charset: None,
+            server_collation: None,
+            database_charset: column(0),
+            database_collation: None,
+            client_charset: None,
+            variables: BTreeMap::new(),
+        },
+        "mysql" => {
+            let variables: BTreeMap<String, String> = result
+                .rows
+                .iter()
+                .filter_map(|row| Some((text(row.first())?, text(row.get(1)).unwrap_or_default())))
+                .collect();
+            let variable = |name: &str| variables.get(name).cloned();
+            CharsetInfo {
+                server_charset: variable("character_set_server"),
+                server_collation: variable("collation_server"),
+                database_charset: variable("character_set_database"),
+                database_collation: variable("collation_database"),
+                client_charset: variable("character_set_client"),
+                variables,
+            }
+        }
+        // Strings travel as UTF-16 in TDS, so the client has no character set
+        "mssql" => CharsetInfo {
+            server_charset: column(0),
+            server_collation: column(1),
+            database_charset: column(2),
+            database_collation: column(3),
+            client_charset: None,
+            variables: BTreeMap::new(),
+        },
+        _ => CharsetInfo {
+            server_charset: column(0),
+            server_collation: column(1),
+            database_charset: column(2),
+            database_collation: column(3),
+            client_charset: column(4),
+            variables: BTreeMap::new(),
+        },
+    };
+    Ok(info)
+}
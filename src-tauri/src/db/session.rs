@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tauri::{Manager, State};
+use crate::db::connections::{list_databases, list_schemas, load_connections, save_connections, Connection, ConnectionConfig};
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::PoolManager;
+use crate::db::query_cache::QueryCache;
+use crate::error::AppError;
+
+/// How long a fetched database list is trusted by `ensure_database_exists`
+const DATABASE_LIST_TTL: Duration = Duration::from_secs(30);
+
+/// MSSQL system databases, which `list_databases` leaves out
+const MSSQL_SYSTEM_DATABASES: [&str; 4] = ["master", "tempdb", "model", "msdb"];
+
+/// Database selected per connection id, used when a command omits `database`
+pub struct ActiveDatabases {
+    entries: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl ActiveDatabases {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn get(&self, connection_id: &str) -> Option<String> {
+        let entries = self.entries.read().await;
+        entries.get(connection_id).cloned()
+    }
+
+    pub async fn insert(&self, connection_id: &str, database: String) {
+        let mut entries = self.entries.write().await;
+        entries.insert(connection_id.to_string(), database);
+    }
+
+    pub async fn invalidate(&self, connection_id: &str) {
+        let mut entries = self.entries.write().await;
+        entries.remove(connection_id);
+    }
+
+    /// The database a command should use: the one given, else the active one,
+    /// else the one last selected in a previous session
+    pub async fn resolve(&self, connection: &Connection, database: Option<String>) -> Option<String> {
+        match database {
+            Some(db) => Some(db),
+            None => self.get(&connection.id).await.or_else(|| connection.last_database.clone()),
+        }
+    }
+}
+
+impl Default for ActiveDatabases {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct DatabaseList {
+    fetched_at: Instant,
+    names: Vec<String>,
+}
+
+/// Database names of each connection, keyed by connection id
+pub struct DatabaseListCache {
+    entries: Arc<RwLock<HashMap<String, DatabaseList>>>,
+}
+
+impl DatabaseListCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// The cached list, if it was fetched less than `DATABASE_LIST_TTL` ago
+    pub async fn get(&self, connection_id: &str) -> Option<Vec<String>> {
+        let entries = self.entries.read().await;
+        entries
+            .get(connection_id)
+            .filter(|list| list.fetched_at.elapsed() < DATABASE_LIST_TTL)
+            .map(|list| list.names.clone())
+    }
+
+    pub async fn insert(&self, connection_id: &str, names: Vec<String>) {
+        let mut entries = self.entries.write().await;
+        entries.insert(connection_id.to_string(), DatabaseList { fetched_at: Instant::now(), names });
+    }
+
+    pub async fn invalidate(&self, connection_id: &str) {
+        let mut entries = self.entries.write().await;
+        entries.remove(connection_id);
+    }
+}
+
+impl Default for DatabaseListCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `database` is one of the connection's databases. The one named in
+/// the connection config and MSSQL system databases are always accepted;
+/// SQLite has no databases to check.
+async fn database_known(
+    connection: &Connection,
+    database: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<bool, String> {
+    let configured = match &connection.config {
+        ConnectionConfig::Sqlite { .. } => return Ok(true),
+        ConnectionConfig::Mysql { database, .. }
+        | ConnectionConfig::Postgres { database, .. }
+        | ConnectionConfig::Mssql { database, .. } => database.as_deref(),
+    };
+    if configured == Some(database) {
+        return Ok(true);
+    }
+
+    let cache = app.state::<DatabaseListCache>();
+    let databases = match cache.get(&connection.id).await {
+        Some(databases) => databases,
+        None => {
+            let databases = list_databases(connection.id.clone(), app.clone(), pool_manager.clone()).await?;
+            cache.insert(&connection.id, databases.clone()).await;
+            databases
+        }
+    };
+
+    // MSSQL names follow the server collation, which is usually case-insensitive
+    Ok(if connection.db_type == "mssql" {
+        MSSQL_SYSTEM_DATABASES.into_iter().chain(databases.iter().map(|d| d.as_str())).any(|d| d.eq_ignore_ascii_case(database))
+    } else {
+        databases.iter().any(|d| d == database)
+    })
+}
+
+/// Fail with `AppError::NotFound` unless `database` exists on the connection.
+/// Call before interpolating a caller-supplied database name into SQL.
+pub(crate) async fn ensure_database_exists(
+    connection: &Connection,
+    database: &str,
+    app: &tauri::AppHandle,
+    pool_manager: &State<'_, PoolManager>,
+) -> Result<(), String> {
+    if database_known(connection, database, app, pool_manager).await? {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!("Database {}", database)).into())
+    }
+}
+
+/// Whether a database exists on the connection, using a briefly cached list
+#[tauri::command]
+pub async fn database_exists(
+    connection_id: String,
+    database: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<bool, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    database_known(connection, &database, &app, &pool_manager).await
+}
+
+/// Make `database` the default for later commands on this connection. The
+/// choice is also saved with the connection so it survives a restart.
+#[tauri::command]
+pub async fn set_active_database(
+    connection_id: String,
+    database: String,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    active_databases: State<'_, ActiveDatabases>,
+) -> Result<(), String> {
+    let mut connections = load_connections(&app);
+    let connection = connections
+        .iter_mut()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    ensure_database_exists(connection, &database, &app, &pool_manager).await?;
+
+    connection.last_database = Some(database.clone());
+    save_connections(&app, &connections)?;
+
+    active_databases.insert(&connection_id, database).await;
+    Ok(())
+}
+
+/// The database commands on this connection default to, if one was selected
+#[tauri::command]
+pub async fn get_active_database(
+    connection_id: String,
+    app: tauri::AppHandle,
+    active_databases: State<'_, ActiveDatabases>,
+) -> Result<Option<String>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    Ok(active_databases.resolve(connection, None).await)
+}
+
+/// The PostgreSQL search_path of a connection: the saved one, or the
+/// server's current setting when none was saved
+#[tauri::command]
+pub async fn get_search_path(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<String>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if connection.db_type != "postgres" {
+        return Err("search_path is only supported for PostgreSQL".to_string());
+    }
+    if let Some(schemas) = &connection.search_path {
+        return Ok(schemas.clone());
+    }
+
+    let result = run_query_with_limit(connection, database.as_deref(), "SHOW search_path", &app, &pool_manager, 1).await?;
+    let value = result
+        .rows
+        .first()
+        .and_then(|row| row.first())
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    Ok(value
+        .split(',')
+        .map(|s| s.trim().trim_matches('"').to_string())
+        .filter(|s| !s.is_empty())
+        .collect())
+}
+
+/// Save the PostgreSQL search_path of a connection; it is applied to every
+/// session opened afterwards. An empty list goes back to the server default.
+/// Schemas must exist, except for the special `$user` entry.
+#[tauri::command]
+pub async fn set_search_path(
+    connection_id: String,
+    database: Option<String>,
+    schemas: Vec<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+    query_cache: State<'_, QueryCache>,
+) -> Result<(), String> {
+    let mut connections = load_connections(&app);
+    let connection = connections
+        .iter_mut()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if connection.db_type != "postgres" {
+        return Err("search_path is only supported for PostgreSQL".to_string());
+    }
+
+    let existing = list_schemas(connection_id.clone(), database, app.clone(), pool_manager.clone()).await?;
+    if let Some(missing) = schemas.iter().find(|s| *s != "$user" && !existing.contains(s)) {
+        return Err(format!("Schema not found: {}", missing));
+    }
+
+    connection.search_path = Some(schemas).filter(|s| !s.is_empty());
+    save_connections(&app, &connections)?;
+
+    // Pooled sessions keep the old search_path; new pools apply the saved one
+    pool_manager.remove_pool(&connection_id).await;
+    query_cache.invalidate(&connection_id).await;
+    Ok(())
+}
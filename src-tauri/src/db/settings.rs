@@ -1,22 +1,134 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::RwLock;
 use tauri::{AppHandle, Manager};
+use crate::db::history;
+use crate::db::store::write_json_atomic;
+
+// Copy of the application_name setting, for connection code that has no AppHandle
+static APPLICATION_NAME: RwLock<String> = RwLock::new(String::new());
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
     #[serde(default = "default_max_history_count")]
     pub max_history_count: usize,
+    /// Share one MySQL pool across databases and `USE` the target database on
+    /// each checkout instead of opening a pool per database. PostgreSQL cannot
+    /// switch databases inside a session, so it always keeps per-database pools.
+    #[serde(default)]
+    pub reuse_pool_across_databases: bool,
+    /// Maximum rows collected per query result; further rows are dropped and
+    /// the result is flagged as truncated
+    #[serde(default = "default_max_result_rows")]
+    pub max_result_rows: usize,
+    /// Interval in seconds at which idle pools are pinged to keep them alive
+    /// behind idle-timeout firewalls; 0 disables the keep-alive task
+    #[serde(default)]
+    pub keepalive_secs: u64,
+    /// Case of keywords when formatting SQL
+    #[serde(default = "default_format_keyword_case")]
+    pub format_keyword_case: KeywordCase,
+    #[serde(default = "default_format_indent_width")]
+    pub format_indent_width: u8,
+    /// Longest string shown in a result cell before it is cut off; None shows everything
+    #[serde(default)]
+    pub max_cell_chars: Option<usize>,
+    /// Client name reported to servers (Postgres `application_name`, MSSQL
+    /// application name); empty sends nothing. MySQL connections are not
+    /// labelled.
+    #[serde(default = "default_application_name")]
+    pub application_name: String,
+    /// Times a query is retried on a fresh connection after a network-level
+    /// failure. A statement cut off mid-flight may already have been applied,
+    /// so 0 turns retrying off.
+    #[serde(default = "default_query_retry_count")]
+    pub query_retry_count: u32,
+    /// Seconds a read-only query result is reused when the same SQL runs again
+    /// on the same connection and database; 0 disables the cache
+    #[serde(default)]
+    pub query_cache_ttl_secs: u64,
+    /// Queue queries beyond the pool size on a connection instead of running
+    /// them all at once, so a burst of tabs cannot exceed the server's
+    /// connection limit
+    #[serde(default = "default_queue_concurrent_queries")]
+    pub queue_concurrent_queries: bool,
+    /// Keep each connection's SQL history in `history/{connection_id}.json`
+    /// instead of one combined `sql_history.json`
+    #[serde(default)]
+    pub per_connection_history: bool,
+    /// File every executed statement is appended to, uncapped unlike the
+    /// history; None turns the log off
+    #[serde(default)]
+    pub sql_log_path: Option<String>,
+    /// Size at which the SQL log is rotated to `<path>.1`
+    #[serde(default = "default_sql_log_max_bytes")]
+    pub sql_log_max_bytes: u64,
+    /// Text written for NULL in CSV, Markdown and HTML exports, e.g. `NULL`
+    /// or `\N` for MySQL `LOAD DATA`; empty leaves the field blank
+    #[serde(default)]
+    pub null_token: String,
 }
 
 fn default_max_history_count() -> usize {
     1000
 }
 
+fn default_max_result_rows() -> usize {
+    10000
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    /// Leave keywords as they are written
+    Preserve,
+}
+
+fn default_format_keyword_case() -> KeywordCase {
+    KeywordCase::Upper
+}
+
+fn default_format_indent_width() -> u8 {
+    2
+}
+
+fn default_application_name() -> String {
+    "FeatherSQL".to_string()
+}
+
+fn default_query_retry_count() -> u32 {
+    1
+}
+
+fn default_queue_concurrent_queries() -> bool {
+    true
+}
+
+fn default_sql_log_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         AppSettings {
             max_history_count: 1000,
+            reuse_pool_across_databases: false,
+            max_result_rows: 10000,
+            keepalive_secs: 0,
+            format_keyword_case: KeywordCase::Upper,
+            format_indent_width: 2,
+            max_cell_chars: None,
+            application_name: default_application_name(),
+            query_retry_count: 1,
+            query_cache_ttl_secs: 0,
+            queue_concurrent_queries: true,
+            per_connection_history: false,
+            sql_log_path: None,
+            sql_log_max_bytes: default_sql_log_max_bytes(),
+            null_token: String::new(),
         }
     }
 }
@@ -40,15 +152,28 @@ pub(crate) fn load_settings(app: &AppHandle) -> AppSettings {
     AppSettings::default()
 }
 
+pub(crate) fn set_application_name(name: &str) {
+    if let Ok(mut current) = APPLICATION_NAME.write() {
+        *current = name.to_string();
+    }
+}
+
+/// Label to report to the server, with the connection name appended when known.
+/// None when the application name setting is empty.
+pub(crate) fn application_name(connection_name: Option<&str>) -> Option<String> {
+    let base = APPLICATION_NAME.read().ok()?.clone();
+    if base.is_empty() {
+        return None;
+    }
+    Some(match connection_name.filter(|n| !n.is_empty()) {
+        Some(name) => format!("{} - {}", base, name),
+        None => base,
+    })
+}
+
 pub(crate) fn save_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
     let path = get_settings_path(app);
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
-    let content = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))?;
-    Ok(())
+    write_json_atomic(&path, settings)
 }
 
 #[tauri::command]
@@ -56,22 +181,146 @@ pub async fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
     Ok(load_settings(&app))
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn update_settings(
     max_history_count: Option<usize>,
+    reuse_pool_across_databases: Option<bool>,
+    max_result_rows: Option<usize>,
+    keepalive_secs: Option<u64>,
+    format_keyword_case: Option<KeywordCase>,
+    format_indent_width: Option<u8>,
+    max_cell_chars: Option<usize>,
+    application_name: Option<String>,
+    query_retry_count: Option<u32>,
+    query_cache_ttl_secs: Option<u64>,
+    queue_concurrent_queries: Option<bool>,
+    per_connection_history: Option<bool>,
+    sql_log_path: Option<String>,
+    sql_log_max_bytes: Option<u64>,
+    null_token: Option<String>,
     app: AppHandle,
 ) -> Result<AppSettings, String> {
     let mut settings = load_settings(&app);
     
     if let Some(count) = max_history_count {
         // Validate: must be between 1 and 100000
-        if count < 1 || count > 100000 {
+        if !(1..=100000).contains(&count) {
             return Err("最大历史记录数必须在 1 到 100000 之间".to_string());
         }
         settings.max_history_count = count;
     }
+
+    if let Some(reuse) = reuse_pool_across_databases {
+        settings.reuse_pool_across_databases = reuse;
+    }
+
+    if let Some(max_rows) = max_result_rows {
+        // Validate: must be between 1 and 10000000
+        if !(1..=10_000_000).contains(&max_rows) {
+            return Err("最大结果行数必须在 1 到 10000000 之间".to_string());
+        }
+        settings.max_result_rows = max_rows;
+    }
+
+    if let Some(secs) = keepalive_secs {
+        // Validate: 0 (disabled) or between 5 and 3600
+        if secs != 0 && !(5..=3600).contains(&secs) {
+            return Err("保活间隔必须为 0 或在 5 到 3600 秒之间".to_string());
+        }
+        settings.keepalive_secs = secs;
+    }
+
+    if let Some(case) = format_keyword_case {
+        settings.format_keyword_case = case;
+    }
+
+    if let Some(width) = format_indent_width {
+        // Validate: must be between 1 and 8
+        if !(1..=8).contains(&width) {
+            return Err("缩进宽度必须在 1 到 8 之间".to_string());
+        }
+        settings.format_indent_width = width;
+    }
+
+    if let Some(max_chars) = max_cell_chars {
+        // 0 turns cell truncation off
+        settings.max_cell_chars = if max_chars == 0 { None } else { Some(max_chars) };
+    }
+
+    if let Some(name) = application_name {
+        // Postgres keeps at most 63 bytes of application_name
+        let name = name.trim().to_string();
+        if name.len() > 63 {
+            return Err("应用名称不能超过 63 个字节".to_string());
+        }
+        settings.application_name = name;
+    }
+
+    if let Some(retries) = query_retry_count {
+        // Validate: must be between 0 and 5
+        if retries > 5 {
+            return Err("查询重试次数必须在 0 到 5 之间".to_string());
+        }
+        settings.query_retry_count = retries;
+    }
+
+    if let Some(ttl) = query_cache_ttl_secs {
+        // Validate: 0 (disabled) or up to one hour
+        if ttl > 3600 {
+            return Err("查询缓存有效期必须在 0 到 3600 秒之间".to_string());
+        }
+        settings.query_cache_ttl_secs = ttl;
+    }
+
+    if let Some(queue) = queue_concurrent_queries {
+        settings.queue_concurrent_queries = queue;
+    }
+
+    if let Some(per_connection) = per_connection_history {
+        if per_connection != settings.per_connection_history {
+            // Move the existing history first, so a failure leaves the setting unchanged
+            history::migrate_history(&app, per_connection)?;
+            settings.per_connection_history = per_connection;
+        }
+    }
+
+    if let Some(path) = sql_log_path {
+        // An empty path turns the log off
+        let path = path.trim().to_string();
+        settings.sql_log_path = if path.is_empty() { None } else { Some(path) };
+    }
+
+    if let Some(max_bytes) = sql_log_max_bytes {
+        // Validate: between 64 KB and 1 GB
+        if !(64 * 1024..=1024 * 1024 * 1024).contains(&max_bytes) {
+            return Err("SQL 日志文件大小上限必须在 64 KB 到 1 GB 之间".to_string());
+        }
+        settings.sql_log_max_bytes = max_bytes;
+    }
+
+    if let Some(token) = null_token {
+        // Written into every NULL field, so keep it short and on one line
+        if token.chars().count() > 16 || token.contains(['\r', '\n']) {
+            return Err("NULL 显示文本不能超过 16 个字符且不能换行".to_string());
+        }
+        settings.null_token = token;
+    }
     
     save_settings(&app, &settings)?;
+    set_application_name(&settings.application_name);
     Ok(settings)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyword_case_defaults_to_upper() {
+        let settings: AppSettings = serde_json::from_str(r#"{"format_keyword_case": "lower"}"#).unwrap();
+        assert_eq!(settings.format_keyword_case, KeywordCase::Lower);
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.format_keyword_case, KeywordCase::Upper);
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use chrono::Utc;
+use crate::db::store::write_json_atomic;
+use crate::error::AppError;
+
+// Serializes load-modify-save, like the other stores
+static SNIPPETS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    pub body: String,
+    /// Dialect the snippet is written for; None offers it for every database
+    pub db_type: Option<String>,
+    /// Word the editor expands into the body, e.g. `sfw`
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    pub updated_at: String, // ISO 8601 format
+}
+
+pub(crate) fn get_snippets_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("Failed to get app data directory")
+        .join("snippets.json")
+}
+
+fn load_snippets(app: &AppHandle) -> Vec<Snippet> {
+    let path = get_snippets_path(app);
+    if path.exists() {
+        if let Ok(content) = fs::read_to_string(&path) {
+            if let Ok(snippets) = serde_json::from_str::<Vec<Snippet>>(&content) {
+                return snippets;
+            }
+        }
+    }
+    vec![]
+}
+
+/// Create a snippet, or replace the one with `id`. A shortcut must be a
+/// single word and unique among the snippets offered for the same dialect.
+#[tauri::command]
+pub async fn save_snippet(
+    id: Option<String>,
+    name: String,
+    body: String,
+    db_type: Option<String>,
+    shortcut: Option<String>,
+    app: AppHandle,
+) -> Result<Snippet, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Snippet name is required".to_string());
+    }
+    if body.trim().is_empty() {
+        return Err("Snippet body is required".to_string());
+    }
+    let db_type = db_type.filter(|t| !t.is_empty());
+    let shortcut = shortcut.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    if shortcut.as_ref().is_some_and(|s| s.chars().any(char::is_whitespace)) {
+        return Err("Snippet shortcut cannot contain spaces".to_string());
+    }
+
+    let _guard = SNIPPETS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut snippets = load_snippets(&app);
+    if let Some(shortcut) = &shortcut {
+        // A dialect-neutral snippet is offered alongside every dialect's own
+        let clash = snippets.iter().find(|s| {
+            Some(&s.id) != id.as_ref()
+                && s.shortcut.as_deref().is_some_and(|other| other.eq_ignore_ascii_case(shortcut))
+                && (s.db_type.is_none() || db_type.is_none() || s.db_type == db_type)
+        });
+        if let Some(other) = clash {
+            return Err(format!("Shortcut '{}' is already used by snippet '{}'", shortcut, other.name));
+        }
+    }
+
+    let snippet = Snippet {
+        id: id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name,
+        body,
+        db_type,
+        shortcut,
+        updated_at: Utc::now().to_rfc3339(),
+    };
+    match id.and_then(|id| snippets.iter_mut().find(|s| s.id == id)) {
+        Some(existing) => *existing = snippet.clone(),
+        None => snippets.push(snippet.clone()),
+    }
+    write_json_atomic(&get_snippets_path(&app), &snippets)?;
+    Ok(snippet)
+}
+
+/// Snippets for a dialect, including the dialect-neutral ones, by name.
+/// Without `db_type` every snippet is returned.
+#[tauri::command]
+pub async fn get_snippets(
+    db_type: Option<String>,
+    app: AppHandle,
+) -> Result<Vec<Snippet>, String> {
+    let mut snippets: Vec<Snippet> = load_snippets(&app)
+        .into_iter()
+        .filter(|s| db_type.is_none() || s.db_type.is_none() || s.db_type == db_type)
+        .collect();
+    snippets.sort_by_key(|s| s.name.to_lowercase());
+    Ok(snippets)
+}
+
+#[tauri::command]
+pub async fn delete_snippet(
+    id: String,
+    app: AppHandle,
+) -> Result<(), String> {
+    let _guard = SNIPPETS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut snippets = load_snippets(&app);
+    let before = snippets.len();
+    snippets.retain(|s| s.id != id);
+    if snippets.len() == before {
+        return Err(AppError::NotFound(format!("Snippet {}", id)).into());
+    }
+    write_json_atomic(&get_snippets_path(&app), &snippets)
+}
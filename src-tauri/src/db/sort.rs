@@ -0,0 +1,133 @@
+use std::cmp::Ordering;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Deserialize;
+use serde_json::Value;
+use crate::db::execute::QueryResult;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SortKind {
+    Number,
+    Temporal,
+    Text,
+}
+
+/// How to compare a column, from its lowercase database type name
+pub(crate) fn sort_kind(type_name: &str) -> SortKind {
+    let t = type_name.to_lowercase();
+    let numeric = ["int", "decimal", "numeric", "float", "double", "real", "money", "number", "serial", "bool", "bit"];
+    if t.contains("interval") {
+        SortKind::Text
+    } else if t.contains("date") || t.contains("time") {
+        SortKind::Temporal
+    } else if numeric.iter().any(|n| t.contains(n)) {
+        SortKind::Number
+    } else {
+        SortKind::Text
+    }
+}
+
+/// Numbers arrive as JSON numbers, or as strings for decimals and 64-bit values
+pub(crate) fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parse the date/time renderings produced by the backends (naive or with an
+/// offset or `UTC` suffix) into a comparable timestamp
+fn as_timestamp(value: &Value) -> Option<NaiveDateTime> {
+    let s = value.as_str()?.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.naive_utc());
+    }
+    if let Ok(dt) = DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Some(dt.naive_utc());
+    }
+    let s = s.trim_end_matches(" UTC");
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(dt);
+        }
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return d.and_hms_opt(0, 0, 0);
+    }
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .ok()
+        .map(|t| NaiveDate::default().and_time(t))
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Compare two non-null values; values that do not parse as the column's kind
+/// fall back to text comparison
+pub(crate) fn compare_values(a: &Value, b: &Value, kind: SortKind) -> Ordering {
+    let typed = match kind {
+        SortKind::Number => as_number(a).zip(as_number(b)).and_then(|(x, y)| x.partial_cmp(&y)),
+        SortKind::Temporal => as_timestamp(a).zip(as_timestamp(b)).map(|(x, y)| x.cmp(&y)),
+        SortKind::Text => None,
+    };
+    typed.unwrap_or_else(|| as_text(a).cmp(&as_text(b)))
+}
+
+/// Sort the rows of a result by one column without going back to the server.
+/// Numbers compare numerically and dates chronologically, based on
+/// `type_hint` or the column's type in `column_types`; NULLs always sort last.
+/// The sort is stable, and `truncated_cells` follow their rows.
+#[tauri::command]
+pub async fn sort_query_result(
+    mut result: QueryResult,
+    column_index: usize,
+    direction: SortDirection,
+    type_hint: Option<String>,
+) -> Result<QueryResult, String> {
+    if column_index >= result.columns.len() {
+        return Err(format!("Column {} is out of range", column_index));
+    }
+    let type_name = type_hint
+        .or_else(|| result.column_types.get(column_index).cloned())
+        .unwrap_or_default();
+    let kind = sort_kind(&type_name);
+
+    let cell = |row: &[Value]| row.get(column_index).filter(|v| !v.is_null()).cloned();
+    let keys: Vec<Option<Value>> = result.rows.iter().map(|row| cell(row)).collect();
+    let mut order: Vec<usize> = (0..result.rows.len()).collect();
+    order.sort_by(|&i, &j| match (&keys[i], &keys[j]) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => match direction {
+            SortDirection::Asc => compare_values(a, b, kind),
+            SortDirection::Desc => compare_values(b, a, kind),
+        },
+    });
+
+    let mut new_index = vec![0; order.len()];
+    for (new, &old) in order.iter().enumerate() {
+        new_index[old] = new;
+    }
+    let mut rows: Vec<Option<Vec<Value>>> = std::mem::take(&mut result.rows).into_iter().map(Some).collect();
+    result.rows = order.iter().filter_map(|&old| rows[old].take()).collect();
+    for cell in &mut result.truncated_cells {
+        if let Some(&new) = new_index.get(cell.row) {
+            cell.row = new;
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,68 @@
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::db::history::SqlHistory;
+
+// The open log file, kept between statements; reopened when the path changes
+static SQL_LOG: Mutex<Option<(PathBuf, BufWriter<File>)>> = Mutex::new(None);
+
+/// One line of the log, as JSON
+#[derive(Serialize)]
+struct SqlLogLine<'a> {
+    executed_at: &'a str,
+    connection_id: &'a str,
+    connection_name: &'a str,
+    elapsed_ms: Option<u64>,
+    success: bool,
+    error_message: Option<&'a str>,
+    sql: &'a str,
+}
+
+fn open_log(path: &Path) -> Result<BufWriter<File>, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open SQL log {}: {}", path.display(), e))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Append a statement to the SQL log at `path`, one JSON object per line.
+/// Past `max_bytes` the file is moved to `<path>.1`, replacing the previous
+/// one, and a new file is started.
+pub(crate) fn append_sql_log(path: &str, max_bytes: u64, entry: &SqlHistory) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    let mut log = SQL_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if log.as_ref().is_none_or(|(open, _)| *open != path) {
+        *log = Some((path.clone(), open_log(&path)?));
+    }
+
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    if size >= max_bytes {
+        *log = None;
+        let mut rotated = path.clone().into_os_string();
+        rotated.push(".1");
+        fs::rename(&path, &rotated).map_err(|e| format!("Failed to rotate SQL log: {}", e))?;
+        *log = Some((path.clone(), open_log(&path)?));
+    }
+
+    let line = SqlLogLine {
+        executed_at: &entry.executed_at,
+        connection_id: &entry.connection_id,
+        connection_name: &entry.connection_name,
+        elapsed_ms: entry.elapsed_ms,
+        success: entry.success,
+        error_message: entry.error_message.as_deref(),
+        sql: &entry.sql,
+    };
+    let Some((_, writer)) = log.as_mut() else {
+        return Ok(());
+    };
+    serde_json::to_writer(&mut *writer, &line).map_err(|e| format!("Failed to write SQL log: {}", e))?;
+    writer
+        .write_all(b"\n")
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write SQL log: {}", e))
+}
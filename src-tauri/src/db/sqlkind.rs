@@ -0,0 +1,160 @@
+use crate::db::column_source::{is_keyword, tokenize_spans, Token};
+
+// Keywords that start the main statement after a WITH clause
+const MAIN_STATEMENT_KEYWORDS: &[&str] = &["SELECT", "VALUES", "TABLE", "INSERT", "UPDATE", "DELETE", "MERGE"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StatementKind {
+    /// Returns rows without changing data: SELECT, VALUES, WITH ... SELECT
+    Query,
+    /// Reads metadata: SHOW, DESCRIBE, EXPLAIN, PRAGMA
+    Inspect,
+    /// Changes rows: INSERT, UPDATE, DELETE, MERGE, and procedure calls,
+    /// which may do anything
+    Dml,
+    /// Changes the schema or permissions: CREATE, ALTER, DROP, TRUNCATE, GRANT
+    Ddl,
+    /// Transaction control, session settings and anything not recognized
+    Utility,
+}
+
+impl StatementKind {
+    /// Whether statements of this kind are read for their rows
+    pub(crate) fn returns_rows(self) -> bool {
+        matches!(self, StatementKind::Query | StatementKind::Inspect)
+    }
+}
+
+/// The statement without leading whitespace, comments and opening parentheses
+fn skip_leading_noise(sql: &str) -> &str {
+    let mut rest = sql;
+    loop {
+        let trimmed = rest.trim_start().trim_start_matches('(');
+        if let Some(comment) = trimmed.strip_prefix("--") {
+            rest = comment.split_once('\n').map_or("", |(_, after)| after);
+        } else if let Some(comment) = trimmed.strip_prefix("/*") {
+            rest = comment.split_once("*/").map_or("", |(_, after)| after);
+        } else if trimmed.len() == rest.len() {
+            return rest;
+        } else {
+            rest = trimmed;
+        }
+    }
+}
+
+/// First keyword of the statement in upper case, past leading comments
+pub(crate) fn leading_keyword(sql: &str) -> String {
+    skip_leading_noise(sql)
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Keyword of the statement a WITH clause belongs to: the first one of
+/// `MAIN_STATEMENT_KEYWORDS` outside the parentheses of the CTE bodies. A
+/// CTE that itself changes rows (Postgres `WITH d AS (DELETE ...)`) wins,
+/// since the statement writes whatever its main part is.
+fn main_keyword_after_with(sql: &str) -> Option<String> {
+    let mut depth = 0usize;
+    let mut body_start = false;
+    for (token, _) in tokenize_spans("mssql", sql).into_iter().skip(1) {
+        match &token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => depth = depth.saturating_sub(1),
+            Token::Word(word) if depth == 1 && body_start && is_keyword(&token, &["INSERT", "UPDATE", "DELETE", "MERGE"]) => {
+                return Some(word.to_uppercase());
+            }
+            Token::Word(word) if depth == 0 && is_keyword(&token, MAIN_STATEMENT_KEYWORDS) => {
+                return Some(word.to_uppercase());
+            }
+            _ => {}
+        }
+        body_start = token == Token::Symbol('(') && depth == 1;
+    }
+    None
+}
+
+/// Kind of the first statement in `sql`, from its leading keyword. Leading
+/// comments are skipped, and a WITH clause takes the kind of the statement
+/// it introduces, so `WITH ... INSERT` is DML.
+pub(crate) fn classify_statement(sql: &str) -> StatementKind {
+    let keyword = leading_keyword(sql);
+    let keyword = match keyword.as_str() {
+        "WITH" => match main_keyword_after_with(skip_leading_noise(sql)) {
+            Some(main) => main,
+            None => return StatementKind::Utility,
+        },
+        _ => keyword,
+    };
+    match keyword.as_str() {
+        "SELECT" | "VALUES" | "TABLE" => StatementKind::Query,
+        "SHOW" | "DESCRIBE" | "DESC" | "EXPLAIN" | "PRAGMA" => StatementKind::Inspect,
+        "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "REPLACE" | "UPSERT" | "COPY" | "CALL" | "EXEC" | "EXECUTE"
+        | "DO" => StatementKind::Dml,
+        "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME" | "COMMENT" | "GRANT" | "REVOKE" => StatementKind::Ddl,
+        _ => StatementKind::Utility,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_statements_are_classified_by_their_keyword() {
+        assert_eq!(classify_statement("select 1"), StatementKind::Query);
+        assert_eq!(classify_statement("VALUES (1), (2)"), StatementKind::Query);
+        assert_eq!(classify_statement("SHOW TABLES"), StatementKind::Inspect);
+        assert_eq!(classify_statement("insert into t values (1)"), StatementKind::Dml);
+        assert_eq!(classify_statement("DROP TABLE t"), StatementKind::Ddl);
+        assert_eq!(classify_statement("BEGIN"), StatementKind::Utility);
+        assert_eq!(classify_statement(""), StatementKind::Utility);
+    }
+
+    #[test]
+    fn leading_comments_are_skipped() {
+        assert_eq!(classify_statement("-- delete everything\nSELECT 1"), StatementKind::Query);
+        assert_eq!(classify_statement("/* select */ DELETE FROM t"), StatementKind::Dml);
+        assert_eq!(classify_statement("  /* a */\n-- b\n/* c */ UPDATE t SET a = 1"), StatementKind::Dml);
+        assert_eq!(classify_statement("-- only a comment"), StatementKind::Utility);
+    }
+
+    #[test]
+    fn parenthesized_selects_are_queries() {
+        assert_eq!(classify_statement("(SELECT 1) UNION (SELECT 2)"), StatementKind::Query);
+        assert_eq!(classify_statement("((select 1))"), StatementKind::Query);
+    }
+
+    #[test]
+    fn explain_is_inspect() {
+        assert_eq!(classify_statement("EXPLAIN SELECT * FROM t"), StatementKind::Inspect);
+        assert_eq!(classify_statement("explain analyze delete from t"), StatementKind::Inspect);
+    }
+
+    #[test]
+    fn with_takes_the_kind_of_its_main_statement() {
+        assert_eq!(classify_statement("WITH x AS (SELECT 1) SELECT * FROM x"), StatementKind::Query);
+        assert_eq!(
+            classify_statement("WITH x AS (SELECT 1 AS a) INSERT INTO t SELECT a FROM x"),
+            StatementKind::Dml
+        );
+        assert_eq!(
+            classify_statement("WITH RECURSIVE x (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM x) UPDATE t SET a = 1"),
+            StatementKind::Dml
+        );
+        assert_eq!(classify_statement("-- cte\nWITH x AS (SELECT 1) DELETE FROM t"), StatementKind::Dml);
+    }
+
+    #[test]
+    fn with_containing_a_data_modifying_cte_is_dml() {
+        assert_eq!(
+            classify_statement("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"),
+            StatementKind::Dml
+        );
+        assert_eq!(
+            classify_statement("WITH a AS (SELECT 1), u AS (UPDATE t SET a = 1 RETURNING a) SELECT * FROM u"),
+            StatementKind::Dml
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::db::connections::{get_store_path, Connection};
+use crate::db::history::{get_connection_history_paths, get_history_path, SqlHistory};
+use crate::db::snippets::{get_snippets_path, Snippet};
+use crate::db::workspace::{get_workspace_path, WorkspaceTab};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreStatus {
+    Ok,
+    /// Not created yet; nothing was saved to it
+    Missing,
+    /// Could not be parsed; the readable entries were saved back
+    Repaired,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoreReport {
+    pub file: String,
+    pub status: StoreStatus,
+    /// Entries in the store after the check
+    pub entries: usize,
+    /// Entries found in a damaged file that could not be read
+    pub lost: usize,
+    /// Copy of the damaged file, kept before it was rewritten
+    pub backup_path: Option<String>,
+    /// Why the file could not be parsed
+    pub error: Option<String>,
+}
+
+// Numbers the temporary files, so concurrent saves never share one
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Write `value` as pretty JSON to `path` without ever leaving it half
+/// written: the data goes to a temporary file in the same directory, is
+/// flushed to disk, then renamed over the target in one step. Each write has
+/// a temporary file of its own, so concurrent saves of one store cannot
+/// rename each other's half-written data into place.
+pub(crate) fn write_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let parent = path.parent().ok_or_else(|| format!("Invalid store path: {}", path.display()))?;
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp_path = parent.join(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write file: {}", e));
+    }
+    Ok(())
+}
+
+/// Each top-level element of a JSON array, as text. Tolerates a damaged
+/// file: an element cut off by a partial write is dropped, and text that
+/// is not inside an element is skipped.
+fn array_elements(content: &str) -> Vec<&str> {
+    let Some(start) = content.find('[') else {
+        return Vec::new();
+    };
+    let mut elements = Vec::new();
+    let mut depth = 0usize;
+    let mut element_start = None;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, c) in content[start + 1..].char_indices() {
+        let i = start + 1 + offset;
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                if depth == 0 {
+                    element_start = Some(i);
+                }
+                depth += 1;
+            }
+            '}' | ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(s) = element_start.take() {
+                        elements.push(&content[s..=i]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    elements
+}
+
+/// Where to copy a damaged store: `{file}.bak`, or a timestamped name if an
+/// earlier backup would be overwritten
+fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let backup = path.with_file_name(format!("{}.bak", name));
+    if backup.exists() {
+        path.with_file_name(format!("{}.{}.bak", name, Utc::now().format("%Y%m%d%H%M%S")))
+    } else {
+        backup
+    }
+}
+
+/// Parse a store holding a JSON array of `T`. If it fails, back the file up,
+/// recover every element that still parses and write those back.
+fn check_store<T: DeserializeOwned + Serialize>(path: &Path) -> Result<StoreReport, String> {
+    let file = path.display().to_string();
+    if !path.exists() {
+        return Ok(StoreReport { file, status: StoreStatus::Missing, entries: 0, lost: 0, backup_path: None, error: None });
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+    let error = match serde_json::from_str::<Vec<T>>(&content) {
+        Ok(entries) => {
+            return Ok(StoreReport { file, status: StoreStatus::Ok, entries: entries.len(), lost: 0, backup_path: None, error: None });
+        }
+        Err(e) => e.to_string(),
+    };
+
+    let backup = backup_path(path);
+    fs::copy(path, &backup).map_err(|e| format!("Failed to back up {}: {}", file, e))?;
+
+    let elements = array_elements(&content);
+    let recovered: Vec<T> = elements
+        .iter()
+        .filter_map(|element| serde_json::from_str(element).ok())
+        .collect();
+    write_json_atomic(path, &recovered)?;
+
+    Ok(StoreReport {
+        file,
+        status: StoreStatus::Repaired,
+        entries: recovered.len(),
+        lost: elements.len() - recovered.len(),
+        backup_path: Some(backup.display().to_string()),
+        error: Some(error),
+    })
+}
+
+/// Check that the connection, history, snippet and workspace stores parse.
+/// A damaged store is backed up next to itself (`connections.json.bak`) and
+/// rewritten with the entries that could still be read, instead of loading
+/// as empty.
+#[tauri::command]
+pub async fn check_store_integrity(app: tauri::AppHandle) -> Result<Vec<StoreReport>, String> {
+    let mut reports = vec![
+        check_store::<Connection>(&get_store_path(&app)?)?,
+        check_store::<SqlHistory>(&get_history_path(&app))?,
+        check_store::<Snippet>(&get_snippets_path(&app))?,
+        check_store::<WorkspaceTab>(&get_workspace_path(&app))?,
+    ];
+    for path in get_connection_history_paths(&app) {
+        reports.push(check_store::<SqlHistory>(&path)?);
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("feathersql-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn temp_files(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".tmp"))
+            .collect()
+    }
+
+    #[test]
+    fn failed_write_leaves_the_existing_file_intact() {
+        let dir = test_dir("failed");
+        let path = dir.join("store.json");
+        write_json_atomic(&path, &vec!["saved"]).unwrap();
+
+        // JSON object keys must be strings, so this fails to serialize
+        let unserializable: BTreeMap<Vec<u8>, u8> = BTreeMap::from([(vec![1], 1)]);
+        assert!(write_json_atomic(&path, &unserializable).is_err());
+
+        let kept: Vec<String> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(kept, vec!["saved"]);
+        assert!(temp_files(&dir).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn partial_temp_file_does_not_affect_the_store() {
+        let dir = test_dir("partial");
+        let path = dir.join("store.json");
+        write_json_atomic(&path, &vec![1, 2, 3]).unwrap();
+
+        // What a save cut off before its rename leaves behind
+        fs::write(dir.join(".store.json.1.0.tmp"), "[1, 2").unwrap();
+        let kept: Vec<u32> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(kept, vec![1, 2, 3]);
+
+        write_json_atomic(&path, &vec![4]).unwrap();
+        let saved: Vec<u32> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, vec![4]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writes_never_leave_a_mixed_file() {
+        let dir = test_dir("concurrent");
+        let path = dir.join("store.json");
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for round in 0..25 {
+                        // Lengths differ, so interleaved data would not parse
+                        let value = vec![format!("{}-{}", writer, round); 50 + writer * 40];
+                        write_json_atomic(&path, &value).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let saved: Vec<String> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved.iter().all(|entry| *entry == saved[0]));
+        assert!(temp_files(&dir).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+use crate::db::connections::{describe_table, load_connections};
+use crate::db::quote::{quote_ident, quote_literal, quote_string};
+use crate::db::pool_manager::PoolManager;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryTemplateKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+/// Positional placeholder in the syntax of the database type (1-based)
+fn placeholder(db_type: &str, index: usize) -> String {
+    match db_type {
+        "postgres" => format!("${}", index),
+        "mssql" => format!("@P{}", index),
+        _ => "?".to_string(),
+    }
+}
+
+/// Build a SELECT, INSERT, UPDATE or DELETE statement for a table, with
+/// quoted identifiers and placeholders for the values. UPDATE and DELETE match
+/// on the primary key, or on every column when the table has none.
+#[tauri::command]
+pub async fn generate_query_template(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    kind: QueryTemplateKind,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<String, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let columns = describe_table(
+        connection_id.clone(),
+        table_name.clone(),
+        database,
+        None,
+        app.clone(),
+        pool_manager,
+    ).await?;
+    if columns.is_empty() {
+        return Err(format!("Table not found: {}", table_name));
+    }
+
+    let db_type = connection.db_type.as_str();
+    let table = quote_ident(db_type, &table_name);
+    let quoted = |name: &str| quote_ident(db_type, name);
+
+    let key_columns: Vec<&str> = if columns.iter().any(|c| c.primary_key) {
+        columns.iter().filter(|c| c.primary_key).map(|c| c.name.as_str()).collect()
+    } else {
+        columns.iter().map(|c| c.name.as_str()).collect()
+    };
+    // WHERE clause on the key columns, numbering placeholders from `first`
+    let where_clause = |first: usize| {
+        key_columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{} = {}", quoted(c), placeholder(db_type, first + i)))
+            .collect::<Vec<_>>()
+            .join("\n  AND ")
+    };
+
+    let sql = match kind {
+        QueryTemplateKind::Select => {
+            let list = columns.iter().map(|c| quoted(&c.name)).collect::<Vec<_>>().join(", ");
+            format!("SELECT {}\nFROM {}", list, table)
+        }
+        QueryTemplateKind::Insert => {
+            // Identity columns are filled in by the database
+            let insert_columns: Vec<&str> = columns
+                .iter()
+                .filter(|c| !c.auto_increment)
+                .map(|c| c.name.as_str())
+                .collect();
+            let list = insert_columns.iter().map(|c| quoted(c)).collect::<Vec<_>>().join(", ");
+            let values = (1..=insert_columns.len())
+                .map(|i| placeholder(db_type, i))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("INSERT INTO {} ({})\nVALUES ({})", table, list, values)
+        }
+        QueryTemplateKind::Update => {
+            let set_columns: Vec<&str> = columns
+                .iter()
+                .filter(|c| !c.primary_key && !c.auto_increment)
+                .map(|c| c.name.as_str())
+                .collect();
+            if set_columns.is_empty() {
+                return Err(format!("Table {} has no columns to update", table_name));
+            }
+            let assignments = set_columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("{} = {}", quoted(c), placeholder(db_type, i + 1)))
+                .collect::<Vec<_>>()
+                .join(",\n  ");
+            format!("UPDATE {}\nSET {}\nWHERE {}", table, assignments, where_clause(set_columns.len() + 1))
+        }
+        QueryTemplateKind::Delete => format!("DELETE FROM {}\nWHERE {}", table, where_clause(1)),
+    };
+
+    Ok(sql)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowWhere {
+    /// Condition without the WHERE keyword, with placeholders for the values
+    pub sql: String,
+    /// Values for the placeholders, in order
+    pub params: Vec<Value>,
+    /// The table has no primary key, so every comparable column is matched
+    /// and the condition may hit more than one row
+    pub full_row: bool,
+}
+
+/// Types the engine cannot compare with `=`, left out of a full-row match
+fn is_incomparable_type(db_type: &str, data_type: &str) -> bool {
+    let t = data_type.to_lowercase();
+    match db_type {
+        "mssql" => matches!(t.as_str(), "text" | "ntext" | "image" | "xml"),
+        "postgres" => matches!(t.as_str(), "json" | "xml" | "point" | "polygon" | "line" | "box" | "path" | "circle"),
+        _ => false,
+    }
+}
+
+/// WHERE condition that identifies a grid row by its primary key, for
+/// updating or deleting it. `row` maps column names to values; placeholders
+/// are numbered from `first_param` (1 by default) so the condition can follow
+/// a SET list. Without a primary key every comparable column is matched,
+/// with `IS NULL` for NULL values.
+#[tauri::command]
+pub async fn build_pk_where(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    row: serde_json::Map<String, Value>,
+    first_param: Option<usize>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<RowWhere, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let columns = describe_table(
+        connection_id.clone(),
+        table_name.clone(),
+        database,
+        None,
+        app.clone(),
+        pool_manager,
+    ).await?;
+    if columns.is_empty() {
+        return Err(format!("Table not found: {}", table_name));
+    }
+
+    let db_type = connection.db_type.as_str();
+    let full_row = !columns.iter().any(|c| c.primary_key);
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    let mut index = first_param.unwrap_or(1).max(1);
+    for column in &columns {
+        if !full_row && !column.primary_key {
+            continue;
+        }
+        let quoted = quote_ident(db_type, &column.name);
+        let value = match row.get(&column.name) {
+            Some(value) => value,
+            None if full_row => continue,
+            None => return Err(format!("Row has no value for key column {}", column.name)),
+        };
+        if full_row && is_incomparable_type(db_type, &column.data_type) {
+            continue;
+        }
+        if value.is_null() {
+            if !full_row {
+                return Err(format!("Key column {} is NULL", column.name));
+            }
+            conditions.push(format!("{} IS NULL", quoted));
+            continue;
+        }
+        conditions.push(format!("{} = {}", quoted, placeholder(db_type, index)));
+        params.push(value.clone());
+        index += 1;
+    }
+    if conditions.is_empty() {
+        return Err("Row has no values to match on".to_string());
+    }
+
+    Ok(RowWhere { sql: conditions.join(" AND "), params, full_row })
+}
+
+/// Whether a column type holds plain numbers, so numeric strings (decimals,
+/// 64-bit values) can be written unquoted
+fn is_numeric_type(data_type: &str) -> bool {
+    let t = data_type.to_lowercase();
+    ["int", "tinyint", "smallint", "mediumint", "bigint", "decimal", "numeric", "float", "double", "real", "money", "smallmoney", "number"]
+        .iter()
+        .any(|prefix| t.starts_with(prefix))
+}
+
+/// SQL literal for a value going into a column of type `data_type`
+fn column_literal(db_type: &str, data_type: &str, value: &Value) -> String {
+    let t = data_type.to_lowercase();
+    match value {
+        Value::String(s) if is_numeric_type(data_type) && s.trim().parse::<f64>().is_ok() => s.trim().to_string(),
+        // UTC timestamps come back as `2024-01-01 12:00:00 UTC`, which only PostgreSQL parses
+        Value::String(s) if db_type != "postgres" && (t.contains("date") || t.contains("time")) => {
+            quote_string(db_type, s.trim_end_matches(" UTC"))
+        }
+        _ => quote_literal(db_type, value),
+    }
+}
+
+/// Render rows as one `INSERT INTO t (...) VALUES (...);` statement each, for
+/// copying them to another database. `columns` names the value positions of
+/// every row and must exist in the table; NULLs are written as NULL.
+#[tauri::command]
+pub async fn rows_to_insert_sql(
+    connection_id: String,
+    table_name: String,
+    database: Option<String>,
+    rows: Vec<Vec<Value>>,
+    columns: Vec<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<String, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    if columns.is_empty() {
+        return Err("No columns given".to_string());
+    }
+
+    let table_columns = describe_table(
+        connection_id.clone(),
+        table_name.clone(),
+        database,
+        None,
+        app.clone(),
+        pool_manager,
+    ).await?;
+    let column_types = columns
+        .iter()
+        .map(|name| {
+            table_columns
+                .iter()
+                .find(|c| &c.name == name)
+                .map(|c| c.data_type.as_str())
+                .ok_or_else(|| format!("Column {} not found in table {}", name, table_name))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let db_type = connection.db_type.as_str();
+    let table = quote_ident(db_type, &table_name);
+    let column_list = columns.iter().map(|c| quote_ident(db_type, c)).collect::<Vec<_>>().join(", ");
+
+    let mut statements = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        if row.len() != columns.len() {
+            return Err(format!("Row {} has {} values, expected {}", index + 1, row.len(), columns.len()));
+        }
+        let values = row
+            .iter()
+            .zip(&column_types)
+            .map(|(value, data_type)| column_literal(db_type, data_type, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        statements.push(format!("INSERT INTO {} ({}) VALUES ({});", table, column_list, values));
+    }
+
+    Ok(statements.join("\n"))
+}
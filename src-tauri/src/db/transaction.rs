@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use tauri::State;
+use sqlx::pool::PoolConnection;
+use sqlx::{Executor, MySql, Postgres, Sqlite};
+use tiberius::Client;
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::compat::Compat;
+use crate::db::connections::{create_mssql_client, load_connections, save_connections, Connection, ConnectionConfig};
+use crate::db::pool_manager::{PoolManager, DatabasePool};
+
+/// One connection held across statements, so a transaction spans all of them
+pub(crate) enum HeldConnection {
+    Sqlite(PoolConnection<Sqlite>),
+    Mysql(PoolConnection<MySql>),
+    Postgres(PoolConnection<Postgres>),
+    Mssql(Client<Compat<TcpStream>>),
+}
+
+impl HeldConnection {
+    /// Take a connection out of the pool (or open an MSSQL client) for `database`
+    pub(crate) async fn open(
+        connection: &Connection,
+        database: Option<&str>,
+        pool_manager: &PoolManager,
+    ) -> Result<Self, String> {
+        if connection.db_type == "mssql" {
+            return match &connection.config {
+                ConnectionConfig::Mssql { database: config_db, .. } => {
+                    let db_name = database.or(config_db.as_deref());
+                    Ok(HeldConnection::Mssql(create_mssql_client(&connection.config, db_name).await?))
+                }
+                _ => Err("无效的 MSSQL 配置".to_string()),
+            };
+        }
+
+        let pool = pool_manager.get_or_create_pool(connection, database).await?;
+        let acquired = match pool {
+            DatabasePool::Sqlite(p) => p.acquire().await.map(HeldConnection::Sqlite),
+            DatabasePool::Mysql(p) => p.acquire().await.map(HeldConnection::Mysql),
+            DatabasePool::Postgres(p) => p.acquire().await.map(HeldConnection::Postgres),
+        };
+        acquired.map_err(|e| format!("Failed to acquire connection: {}", e))
+    }
+
+    pub(crate) async fn execute(&mut self, sql: &str) -> Result<u64, String> {
+        // Plain &str statements go through the simple query protocol, which
+        // accepts statements that cannot be prepared
+        let result = match self {
+            HeldConnection::Sqlite(conn) => conn.execute(sql).await.map(|r| r.rows_affected()),
+            HeldConnection::Mysql(conn) => conn.execute(sql).await.map(|r| r.rows_affected()),
+            HeldConnection::Postgres(conn) => conn.execute(sql).await.map(|r| r.rows_affected()),
+            HeldConnection::Mssql(client) => {
+                return client
+                    .execute(sql, &[])
+                    .await
+                    .map(|r| r.total())
+                    .map_err(|e| format!("SQL 执行失败: {}", e));
+            }
+        };
+        result.map_err(|e| format!("SQL execution failed: {}", e))
+    }
+
+    /// Run a transaction control statement (begin, commit or rollback)
+    pub(crate) async fn control(&mut self, sql: &str) -> Result<(), String> {
+        match self {
+            // sp_executesql rejects a transaction left open at the end of the
+            // call, so MSSQL control statements go through a plain batch
+            HeldConnection::Mssql(client) => {
+                client
+                    .simple_query(sql)
+                    .await
+                    .map_err(|e| format!("SQL 执行失败: {}", e))?
+                    .into_results()
+                    .await
+                    .map_err(|e| format!("SQL 执行失败: {}", e))?;
+                Ok(())
+            }
+            _ => self.execute(sql).await.map(|_| ()),
+        }
+    }
+
+    /// Roll back and release the connection. If the rollback fails the
+    /// connection is closed instead of going back to the pool mid-transaction.
+    pub(crate) async fn discard(mut self, db_type: &str) {
+        if self.control(rollback_statement(db_type)).await.is_ok() {
+            return;
+        }
+        match self {
+            HeldConnection::Sqlite(conn) => drop(conn.close().await),
+            HeldConnection::Mysql(conn) => drop(conn.close().await),
+            HeldConnection::Postgres(conn) => drop(conn.close().await),
+            HeldConnection::Mssql(client) => drop(client.close().await),
+        }
+    }
+}
+
+pub(crate) fn begin_statement(db_type: &str) -> &'static str {
+    match db_type {
+        "mysql" => "START TRANSACTION",
+        "mssql" => "BEGIN TRANSACTION",
+        _ => "BEGIN",
+    }
+}
+
+pub(crate) fn commit_statement(db_type: &str) -> &'static str {
+    match db_type {
+        "mssql" => "COMMIT TRANSACTION",
+        _ => "COMMIT",
+    }
+}
+
+pub(crate) fn rollback_statement(db_type: &str) -> &'static str {
+    match db_type {
+        // An error may already have rolled the transaction back
+        "mssql" => "IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION",
+        _ => "ROLLBACK",
+    }
+}
+
+/// A manual-mode transaction waiting for commit or rollback
+pub(crate) struct OpenTransaction {
+    pub db_type: String,
+    pub database: Option<String>,
+    /// Unix time in milliseconds
+    pub started_at: u64,
+    /// Statements run since the transaction began
+    pub statements: usize,
+    pub conn: HeldConnection,
+}
+
+impl OpenTransaction {
+    /// Take a connection for `database` and begin a transaction on it
+    pub(crate) async fn begin(
+        connection: &Connection,
+        database: Option<&str>,
+        pool_manager: &PoolManager,
+    ) -> Result<Self, String> {
+        let mut conn = HeldConnection::open(connection, database, pool_manager).await?;
+        conn.control(begin_statement(&connection.db_type)).await?;
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Ok(Self {
+            db_type: connection.db_type.clone(),
+            database: database.map(|s| s.to_string()),
+            started_at,
+            statements: 0,
+            conn,
+        })
+    }
+}
+
+/// Transactions left open by `execute_sql` on connections with autocommit
+/// off, keyed by connection id
+pub struct OpenTransactions {
+    entries: Arc<RwLock<HashMap<String, Arc<Mutex<OpenTransaction>>>>>,
+}
+
+impl OpenTransactions {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub(crate) async fn get(&self, connection_id: &str) -> Option<Arc<Mutex<OpenTransaction>>> {
+        let entries = self.entries.read().await;
+        entries.get(connection_id).cloned()
+    }
+
+    /// The open transaction of a connection, beginning one if there is none
+    pub(crate) async fn get_or_begin(
+        &self,
+        connection: &Connection,
+        database: Option<&str>,
+        pool_manager: &PoolManager,
+    ) -> Result<Arc<Mutex<OpenTransaction>>, String> {
+        let mut entries = self.entries.write().await;
+        if let Some(transaction) = entries.get(&connection.id) {
+            return Ok(transaction.clone());
+        }
+        let transaction = Arc::new(Mutex::new(OpenTransaction::begin(connection, database, pool_manager).await?));
+        entries.insert(connection.id.clone(), transaction.clone());
+        Ok(transaction)
+    }
+
+    /// Remove the open transaction of a connection, waiting for a statement
+    /// still running in it
+    async fn take(&self, connection_id: &str) -> Option<OpenTransaction> {
+        let mut shared = self.entries.write().await.remove(connection_id)?;
+        // Statements that looked the transaction up before it was removed
+        // still run in it; wait until the last of them lets go
+        loop {
+            match Arc::try_unwrap(shared) {
+                Ok(transaction) => return Some(transaction.into_inner()),
+                Err(still_shared) => {
+                    drop(still_shared.lock().await);
+                    tokio::task::yield_now().await;
+                    shared = still_shared;
+                }
+            }
+        }
+    }
+
+    pub(crate) async fn commit(&self, connection_id: &str) -> Result<bool, String> {
+        let Some(mut transaction) = self.take(connection_id).await else {
+            return Ok(false);
+        };
+        let statement = commit_statement(&transaction.db_type);
+        if let Err(e) = transaction.conn.control(statement).await {
+            transaction.conn.discard(&transaction.db_type).await;
+            return Err(format!("Commit failed, the transaction was rolled back: {}", e));
+        }
+        Ok(true)
+    }
+
+    pub(crate) async fn rollback(&self, connection_id: &str) -> bool {
+        match self.take(connection_id).await {
+            Some(transaction) => {
+                transaction.conn.discard(&transaction.db_type).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Roll back the open transactions of every connection
+    pub(crate) async fn rollback_all(&self) {
+        let connection_ids: Vec<String> = self.entries.read().await.keys().cloned().collect();
+        for connection_id in connection_ids {
+            self.rollback(&connection_id).await;
+        }
+    }
+}
+
+impl Default for OpenTransactions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `sql` is a bare COMMIT or ROLLBACK, which in manual mode ends the
+/// open transaction like the commands below
+pub(crate) fn transaction_end(sql: &str) -> Option<bool> {
+    let statement = sql.trim().trim_end_matches(';').trim().to_uppercase();
+    let mut words = statement.split_whitespace();
+    let keyword = words.next()?;
+    if !matches!(words.next(), None | Some("WORK") | Some("TRAN") | Some("TRANSACTION")) || words.next().is_some() {
+        return None;
+    }
+    match keyword {
+        "COMMIT" => Some(true),
+        "ROLLBACK" => Some(false),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionStatus {
+    /// Set while statements are waiting for commit or rollback
+    pub open: bool,
+    pub database: Option<String>,
+    pub statements: usize,
+    /// Unix time in milliseconds the transaction began
+    pub started_at: Option<u64>,
+}
+
+/// Whether a connection has uncommitted changes from manual transaction mode
+#[tauri::command]
+pub async fn get_transaction_status(
+    connection_id: String,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<TransactionStatus, String> {
+    let Some(transaction) = open_transactions.get(&connection_id).await else {
+        return Ok(TransactionStatus { open: false, database: None, statements: 0, started_at: None });
+    };
+    let transaction = transaction.lock().await;
+    Ok(TransactionStatus {
+        open: true,
+        database: transaction.database.clone(),
+        statements: transaction.statements,
+        started_at: Some(transaction.started_at),
+    })
+}
+
+/// Commit the open transaction of a connection. Returns false if none was open.
+#[tauri::command]
+pub async fn commit_transaction(
+    connection_id: String,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<bool, String> {
+    open_transactions.commit(&connection_id).await
+}
+
+/// Roll back the open transaction of a connection. Returns false if none was open.
+#[tauri::command]
+pub async fn rollback_transaction(
+    connection_id: String,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<bool, String> {
+    Ok(open_transactions.rollback(&connection_id).await)
+}
+
+/// Switch a connection between autocommit and manual transaction mode.
+/// Turning autocommit back on requires the open transaction to be ended first.
+#[tauri::command]
+pub async fn set_autocommit(
+    connection_id: String,
+    autocommit: bool,
+    app: tauri::AppHandle,
+    open_transactions: State<'_, OpenTransactions>,
+) -> Result<(), String> {
+    if autocommit && open_transactions.get(&connection_id).await.is_some() {
+        return Err("Commit or roll back the open transaction first".to_string());
+    }
+
+    let mut connections = load_connections(&app);
+    let connection = connections
+        .iter_mut()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    connection.autocommit = autocommit;
+    save_connections(&app, &connections)
+}
@@ -0,0 +1,225 @@
+use serde::Serialize;
+use tauri::{Manager, State};
+use crate::db::connections::{load_connections, maintenance_database, Connection};
+use crate::db::execute::run_query_with_limit;
+use crate::db::pool_manager::PoolManager;
+use crate::db::quote::quote_string;
+use crate::db::session::ActiveDatabases;
+
+// Catalogs are small; this only guards against a runaway result
+const MAX_ROWS: usize = 100_000;
+
+#[derive(Debug, Serialize)]
+pub struct DbUser {
+    pub name: String,
+    /// Host part of a MySQL account (`user@host`)
+    pub host: Option<String>,
+    /// `user` or `role` (PostgreSQL, MySQL), or the principal type as MSSQL
+    /// reports it, e.g. `sql_login`, `database_role`
+    pub kind: String,
+    /// `server` or `database` for MSSQL principals
+    pub scope: Option<String>,
+    pub can_login: Option<bool>,
+    pub superuser: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPrivilege {
+    /// Privilege name, e.g. `SELECT`; for MySQL the whole `GRANT` statement
+    /// as `SHOW GRANTS` prints it. Role memberships are `MEMBER OF`.
+    pub privilege: String,
+    /// Object the privilege applies to (`schema.table`, a role, or e.g.
+    /// `DATABASE`); None when it is part of the MySQL statement
+    pub object: Option<String>,
+    /// The grantee may pass the privilege on
+    pub grantable: Option<bool>,
+    /// MSSQL `DENY`; the privilege is explicitly refused
+    pub denied: bool,
+}
+
+fn text(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn flag(value: Option<&serde_json::Value>) -> Option<bool> {
+    match value? {
+        serde_json::Value::Bool(b) => Some(*b),
+        serde_json::Value::Number(n) => n.as_i64().map(|n| n != 0),
+        serde_json::Value::String(s) => Some(matches!(s.as_str(), "YES" | "Y" | "t" | "true" | "1")),
+        _ => None,
+    }
+}
+
+/// Database to run the catalog queries in: the requested or active one,
+/// else the maintenance database, as the user catalogs are server-wide
+async fn catalog_database(app: &tauri::AppHandle, connection: &Connection, database: Option<String>) -> Option<String> {
+    app.state::<ActiveDatabases>()
+        .resolve(connection, database)
+        .await
+        .or_else(|| maintenance_database(&connection.config).map(String::from))
+}
+
+/// Users and roles defined on the server. For MSSQL these are the server
+/// logins followed by the principals of the database. Needs the rights to
+/// read the catalogs (`mysql.user` on MySQL). SQLite has no users and
+/// returns an empty list.
+#[tauri::command]
+pub async fn list_users(
+    connection_id: String,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<DbUser>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+
+    let sql = match connection.db_type.as_str() {
+        // CAST: the columns have a binary collation
+        "mysql" => "SELECT CAST(User AS CHAR), CAST(Host AS CHAR) FROM mysql.user ORDER BY 1, 2",
+        "postgres" => {
+            "SELECT rolname, rolcanlogin, rolsuper FROM pg_roles
+             WHERE rolname NOT LIKE 'pg\\_%'
+             ORDER BY rolname"
+        }
+        "mssql" => {
+            "SELECT name, LOWER(type_desc), 'server' AS scope FROM sys.server_principals
+             WHERE type IN ('S', 'U', 'G', 'R', 'E', 'X') AND name NOT LIKE '##%'
+             UNION ALL
+             SELECT name, LOWER(type_desc), 'database' FROM sys.database_principals
+             WHERE type IN ('S', 'U', 'G', 'R', 'E', 'X', 'A') AND name NOT LIKE '##%'
+             ORDER BY scope DESC, name"
+        }
+        _ => return Ok(vec![]),
+    };
+
+    let database = catalog_database(&app, connection, database).await;
+    let result = run_query_with_limit(connection, database.as_deref(), sql, &app, &pool_manager, MAX_ROWS).await?;
+    let users = result
+        .rows
+        .iter()
+        .filter_map(|row| {
+            let name = text(row.first())?;
+            Some(match connection.db_type.as_str() {
+                "mysql" => DbUser {
+                    name,
+                    host: text(row.get(1)),
+                    kind: "user".to_string(),
+                    scope: None,
+                    can_login: None,
+                    superuser: None,
+                },
+                "postgres" => {
+                    let can_login = flag(row.get(1));
+                    DbUser {
+                        name,
+                        host: None,
+                        kind: if can_login == Some(true) { "user" } else { "role" }.to_string(),
+                        scope: None,
+                        can_login,
+                        superuser: flag(row.get(2)),
+                    }
+                }
+                _ => DbUser {
+                    name,
+                    host: None,
+                    kind: text(row.get(1)).unwrap_or_default(),
+                    scope: text(row.get(2)),
+                    can_login: None,
+                    superuser: None,
+                },
+            })
+        })
+        .collect();
+    Ok(users)
+}
+
+/// Privileges granted to a user or role, and the roles it is a member of.
+/// MySQL accounts are `username@host`, with `host` defaulting to `%`; its
+/// grants come back as the statements `SHOW GRANTS` prints. PostgreSQL lists
+/// table privileges of the database, MSSQL the permissions of the database
+/// principal (including denials). SQLite returns an empty list.
+#[tauri::command]
+pub async fn get_user_privileges(
+    connection_id: String,
+    username: String,
+    host: Option<String>,
+    database: Option<String>,
+    app: tauri::AppHandle,
+    pool_manager: State<'_, PoolManager>,
+) -> Result<Vec<UserPrivilege>, String> {
+    let connections = load_connections(&app);
+    let connection = connections
+        .iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    let db_type = connection.db_type.as_str();
+    let user = quote_string(db_type, &username);
+
+    let sql = match db_type {
+        "mysql" => format!(
+            "SHOW GRANTS FOR {}@{}",
+            user,
+            quote_string(db_type, host.as_deref().unwrap_or("%"))
+        ),
+        "postgres" => format!(
+            "SELECT privilege_type, table_schema || '.' || table_name, is_grantable = 'YES', false
+             FROM information_schema.role_table_grants WHERE grantee = {user}
+             UNION ALL
+             SELECT 'MEMBER OF', r.rolname, m.admin_option, false
+             FROM pg_auth_members m
+             JOIN pg_roles r ON r.oid = m.roleid
+             JOIN pg_roles u ON u.oid = m.member
+             WHERE u.rolname = {user}
+             ORDER BY 2, 1",
+            user = user
+        ),
+        "mssql" => format!(
+            "SELECT p.permission_name,
+                    CASE WHEN p.class = 1 THEN OBJECT_SCHEMA_NAME(p.major_id) + '.' + OBJECT_NAME(p.major_id)
+                         WHEN p.class = 3 THEN SCHEMA_NAME(p.major_id)
+                         ELSE p.class_desc END,
+                    CASE WHEN p.state = 'W' THEN 1 ELSE 0 END,
+                    CASE WHEN p.state = 'D' THEN 1 ELSE 0 END
+             FROM sys.database_permissions p
+             JOIN sys.database_principals u ON u.principal_id = p.grantee_principal_id
+             WHERE u.name = {user}
+             UNION ALL
+             SELECT 'MEMBER OF', r.name, 0, 0
+             FROM sys.database_role_members m
+             JOIN sys.database_principals r ON r.principal_id = m.role_principal_id
+             JOIN sys.database_principals u ON u.principal_id = m.member_principal_id
+             WHERE u.name = {user}
+             ORDER BY 2, 1",
+            user = user
+        ),
+        _ => return Ok(vec![]),
+    };
+
+    let database = catalog_database(&app, connection, database).await;
+    let result = run_query_with_limit(connection, database.as_deref(), &sql, &app, &pool_manager, MAX_ROWS).await?;
+    let privileges = result
+        .rows
+        .iter()
+        .filter_map(|row| {
+            let privilege = text(row.first())?;
+            Some(if db_type == "mysql" {
+                UserPrivilege { privilege, object: None, grantable: None, denied: false }
+            } else {
+                UserPrivilege {
+                    privilege,
+                    object: text(row.get(1)),
+                    grantable: flag(row.get(2)),
+                    denied: flag(row.get(3)).unwrap_or(false),
+                }
+            })
+        })
+        .collect();
+    Ok(privileges)
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use crate::db::store::write_json_atomic;
+
+/// An editor tab as it was left, so unsaved queries survive a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTab {
+    /// Connection the tab runs against; None for a tab not bound to one yet
+    #[serde(default)]
+    pub connection_id: Option<String>,
+    #[serde(default)]
+    pub database: Option<String>,
+    pub sql: String,
+    pub title: String,
+}
+
+pub(crate) fn get_workspace_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("Failed to get app data directory")
+        .join("workspace.json")
+}
+
+/// Replace the saved tabs with the currently open ones, in tab order. Kept
+/// apart from the history, which only records queries that were run.
+#[tauri::command]
+pub async fn save_workspace(
+    tabs: Vec<WorkspaceTab>,
+    app: AppHandle,
+) -> Result<(), String> {
+    write_json_atomic(&get_workspace_path(&app), &tabs)
+}
+
+/// Tabs saved by `save_workspace`; empty on first launch or when the file
+/// cannot be read
+#[tauri::command]
+pub async fn load_workspace(app: AppHandle) -> Result<Vec<WorkspaceTab>, String> {
+    let path = get_workspace_path(&app);
+    if path.exists() {
+        if let Ok(content) = fs::read_to_string(&path) {
+            if let Ok(tabs) = serde_json::from_str::<Vec<WorkspaceTab>>(&content) {
+                return Ok(tabs);
+            }
+        }
+    }
+    Ok(vec![])
+}
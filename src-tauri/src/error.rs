@@ -13,6 +13,27 @@ pub enum AppError {
 
     #[error("Database error: {0}")]
     DatabaseError(String),
+
+    #[error("SQL syntax error{}: {message}", .line.map(|l| format!(" at line {}", l)).unwrap_or_default())]
+    SyntaxError { line: Option<u32>, message: String },
+
+    #[error("CSV import error: {0}")]
+    CsvImport(String),
+
+    #[error("Export error: {0}")]
+    Export(String),
+
+    #[error("Confirmation does not match '{0}', nothing was dropped")]
+    ConfirmationMismatch(String),
+
+    #[error("{0} not found")]
+    NotFound(String),
+
+    #[error("Connection '{0}' is read-only")]
+    ReadOnly(String),
+
+    #[error("All {0} pooled connections are busy; wait for running queries to finish or close some query tabs")]
+    PoolExhausted(u32),
 }
 
 impl From<AppError> for String {
@@ -4,13 +4,51 @@
 mod db;
 mod error;
 
-use crate::db::connections::{create_connection, get_connections, update_connection, delete_connection, disconnect_connection, test_connection, list_databases, list_tables, describe_table};
-use crate::db::execute::execute_sql;
-use crate::db::pool_manager::PoolManager;
+use crate::db::connections::{close_all_connections, create_connection, get_connections, set_fetch_strategy, update_connection, delete_connection, disconnect_connection, disconnect_all, reconnect_all, refresh_pool, cancel_test, test_connection, test_connection_detailed, ConnectionTests, list_databases, list_schemas, list_tables, describe_table};
+use crate::db::execute::{cancel_query, cancel_sql_stream, cancel_tab_queries, execute_sql, execute_sql_multi, execute_sql_stream, fetch_full_cell, validate_sql, QueryQueue, RunningQueries};
+use crate::db::pool_manager::{get_pool_stats, run_keepalive, KeepAliveTask, PoolManager};
 use crate::db::history::{add_sql_history, get_sql_history, delete_sql_history};
-use crate::db::settings::{get_settings, update_settings};
+use crate::db::recent::get_recent_objects;
+use crate::db::snippets::{delete_snippet, get_snippets, save_snippet};
+use crate::db::workspace::{load_workspace, save_workspace};
+use crate::db::multi::execute_on_many;
+use crate::db::settings::{get_settings, load_settings, set_application_name, update_settings};
+use crate::db::bulk::{bulk_insert, execute_many};
+use crate::db::import::import_csv;
+use crate::db::export::{export_query_result_csv, export_query_result_xlsx, format_result_as};
+use crate::db::dump::{dump_database, dump_table, backup_sqlite};
+use crate::db::script::run_sql_file;
+use crate::db::session::{database_exists, get_active_database, get_search_path, set_active_database, set_search_path, ActiveDatabases, DatabaseListCache};
+use crate::db::template::{generate_query_template, rows_to_insert_sql, build_pk_where};
+use crate::db::diagram::{export_schema_diagram, list_foreign_keys};
+use crate::db::sort::sort_query_result;
+use crate::db::pivot::pivot_query_result;
+use crate::db::connection_import::import_connections_from;
+use crate::db::preview::preview_all_tables;
+use crate::db::store::check_store_integrity;
+use crate::db::schema_diff::diff_schemas;
+use crate::db::row_counts::compare_table_counts;
+use crate::db::migration::generate_migration_sql;
+use crate::db::explain::explain_query_json;
+use crate::db::benchmark::benchmark_query;
+use crate::db::risk::analyze_statement_risk;
+use crate::db::listen::{listen_channel, unlisten_channel, PgListeners};
+use crate::db::cursor::{close_cursor, fetch_cursor, open_cursor, OpenCursors};
+use crate::db::transaction::{commit_transaction, get_transaction_status, rollback_transaction, set_autocommit, OpenTransactions};
+use crate::db::query_cache::{clear_query_cache, QueryCache};
+use crate::db::profile::{get_column_distinct_values, get_column_stats, get_table_sizes};
+use crate::db::autocomplete::{get_autocomplete_schema, SchemaCache};
+use crate::db::dialect::get_sql_keywords;
+use crate::db::format::{format_json_cell, format_sql};
+use crate::db::server::{get_server_info, get_connection_charset, list_sessions, kill_session, get_blocking_queries, ServerInfoCache};
+use crate::db::users::{get_user_privileges, list_users};
+use crate::db::ddl::{rename_table, truncate_table, duplicate_table_structure, drop_table, drop_index, add_column, drop_column, rename_column, change_column_type, get_object_definition, list_temp_objects, cleanup_temp_objects};
+use std::time::Duration;
 use tauri::Manager;
 
+// How long exiting waits for connections to close cleanly
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
@@ -18,25 +56,139 @@ fn main() {
             // Initialize pool manager
             let pool_manager = PoolManager::new();
             app.manage(pool_manager);
+            app.manage(SchemaCache::new());
+            app.manage(ServerInfoCache::new());
+            app.manage(ActiveDatabases::new());
+            app.manage(RunningQueries::new());
+            app.manage(QueryQueue::new());
+            app.manage(QueryCache::new());
+            app.manage(DatabaseListCache::new());
+            app.manage(OpenTransactions::new());
+            app.manage(PgListeners::new());
+            app.manage(OpenCursors::new());
+            app.manage(ConnectionTests::new());
+            set_application_name(&load_settings(app.handle()).application_name);
+
+            // Ping idle pools in the background (interval from settings)
+            let keepalive = tauri::async_runtime::spawn(run_keepalive(app.handle().clone()));
+            app.manage(KeepAliveTask::new(keepalive));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             create_connection,
             get_connections,
+            set_fetch_strategy,
             update_connection,
             delete_connection,
             disconnect_connection,
+            disconnect_all,
+            reconnect_all,
+            refresh_pool,
             test_connection,
+            test_connection_detailed,
+            cancel_test,
             execute_sql,
+            execute_sql_multi,
+            execute_sql_stream,
+            cancel_sql_stream,
+            cancel_query,
+            cancel_tab_queries,
+            validate_sql,
+            fetch_full_cell,
+            sort_query_result,
+            pivot_query_result,
+            import_connections_from,
+            get_transaction_status,
+            commit_transaction,
+            rollback_transaction,
+            set_autocommit,
+            preview_all_tables,
+            check_store_integrity,
+            diff_schemas,
+            compare_table_counts,
+            generate_migration_sql,
+            explain_query_json,
+            benchmark_query,
+            analyze_statement_risk,
+            listen_channel,
+            unlisten_channel,
+            open_cursor,
+            fetch_cursor,
+            close_cursor,
+            clear_query_cache,
             list_databases,
+            list_schemas,
             list_tables,
             describe_table,
+            get_autocomplete_schema,
+            get_sql_keywords,
+            format_sql,
+            format_json_cell,
+            get_server_info,
+            get_connection_charset,
+            list_sessions,
+            kill_session,
+            get_blocking_queries,
+            list_users,
+            get_user_privileges,
+            rename_table,
+            truncate_table,
+            duplicate_table_structure,
+            drop_table,
+            drop_index,
+            add_column,
+            drop_column,
+            rename_column,
+            change_column_type,
+            get_object_definition,
+            list_temp_objects,
+            cleanup_temp_objects,
             add_sql_history,
             get_sql_history,
             delete_sql_history,
+            get_recent_objects,
+            save_snippet,
+            get_snippets,
+            delete_snippet,
+            save_workspace,
+            load_workspace,
+            execute_on_many,
             get_settings,
             update_settings,
+            bulk_insert,
+            execute_many,
+            import_csv,
+            export_query_result_csv,
+            format_result_as,
+            export_query_result_xlsx,
+            dump_database,
+            dump_table,
+            backup_sqlite,
+            run_sql_file,
+            get_pool_stats,
+            set_active_database,
+            get_active_database,
+            database_exists,
+            get_search_path,
+            set_search_path,
+            generate_query_template,
+            build_pk_where,
+            rows_to_insert_sql,
+            list_foreign_keys,
+            export_schema_diagram,
+            get_column_distinct_values,
+            get_column_stats,
+            get_table_sizes,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                app_handle.state::<KeepAliveTask>().stop();
+                // Bounded, so an unreachable server cannot hold up the exit
+                tauri::async_runtime::block_on(async {
+                    let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, close_all_connections(app_handle)).await;
+                });
+            }
+        });
 }